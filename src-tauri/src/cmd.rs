@@ -1,20 +1,27 @@
 /* src-tauri\src\cmd.rs */
-use std::sync::atomic::{AtomicU8, Ordering};
-use std::time::Duration;
-use tauri::{command, AppHandle, Emitter, Listener, State, Wry};
-use tauri_plugin_serialplugin::commands::{close, force_close, open, start_listening, stop_listening, write, write_data_terminal_ready, write_request_to_send};
-use tauri_plugin_serialplugin::desktop_api::SerialPort;
-
-use crate::convertation::*;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter, Manager, Wry};
+
+use crate::can_status::start_can_status_polling;
+use crate::codec::{init_protocol, teardown_protocol, ProtocolRegistry};
+use crate::command_retry::{run_with_retry, RetryOutcome};
+use crate::connections::ConnectionRegistry;
 use crate::models::*;
-use crate::poe_canable::send_poe_canable_command;
-use crate::poe_serial::send_poe_serial_command;
-use crate::simple_serial::send_simple_serial_command;
+use crate::poe_canable::CanMode;
+use crate::slcan_adapter::{apply_adapter_filter, identify_adapter};
+#[cfg(target_os = "linux")]
+use crate::transports::pty::pty_peer_path;
+use crate::transports::reconnect::ReconnectingTransport;
+use crate::transports::{create_transport, PTY_PREFIX};
 
 /// Уровень логирования приложения.
 /// 0: none, 1: error, 2: warning, 3: info, 4: debug
 static APP_LOG_LEVEL: AtomicU8 = AtomicU8::new(3);
 
+/// Счётчик для формирования путей пар псевдотерминалов (pty://N)
+static PTY_COUNTER: AtomicU32 = AtomicU32::new(0);
+
 /// Перечисление уровней логирования.
 #[derive(Debug)]
 pub enum LogLevel {
@@ -74,28 +81,43 @@ pub fn log(level: LogLevel, label: &str, args: String) {
 ///
 /// # Arguments
 /// * `app` - дескриптор приложения Tauri
-/// * `serial` - состояние серийного порта
 /// * `config` - конфигурация подключения (путь, скорость, протокол и т.д.)
 ///
+/// Для адаптеров SLCAN (POECanable, POECanableFD) после начала прослушивания запрашиваются
+/// версии и серийный номер адаптера, предупреждения о неподдерживаемых настройках
+/// публикуются в `app-status`.
+///
 /// # Returns
-/// * `Ok(String)` - путь к подключенному порту
+/// * `Ok(ConnectResult)` - путь к подключенному порту и сведения об адаптере SLCAN
 /// * `Err(String)` - ошибка подключения
 #[command]
-pub async fn connect_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPort<Wry>>, config: SerialConfig) -> Result<String, String> {
+pub async fn connect_serial_port(app: AppHandle<Wry>, config: SerialConfig) -> Result<ConnectResult, String> {
   log(LogLevel::Info, "connect_serial_port", format!("Попытка подключения к порту: {}", config.path));
 
+  /* Порт, уже открытый по этому пути, закрывается перед повторным открытием */
+  if app.state::<ConnectionRegistry>().close(&config.path) {
+    log(
+      LogLevel::Warn,
+      "connect_serial_port",
+      format!("Порт {} уже был открыт и закрыт перед повторным подключением", config.path),
+    );
+  }
+
   /* Открытие порта */
-  match open(
-    app.clone(),
-    serial.clone(),
-    config.path.clone(),
-    config.baud_rate.clone(),
-    data_bits_from_u32(config.data_bits.clone()),
-    flow_control_from_u32(config.flow_control.clone()),
-    parity_from_u32(config.parity.clone()),
-    stop_bits_from_u32(config.stop_bits.clone()),
-    config.timeout.clone(),
-  ) {
+  let mut transport = create_transport(&app, &config.path);
+  if let Some(policy) = config.reconnect.clone() {
+    // Переподключение по наличию устройства в системе поддерживается только для серийных портов
+    if config.path.contains("://") {
+      log(
+        LogLevel::Warn,
+        "connect_serial_port",
+        format!("Переподключение не поддерживается для порта {}", config.path),
+      );
+    } else {
+      transport = ReconnectingTransport::new(app.clone(), transport, config.clone(), policy);
+    }
+  }
+  match transport.open(&config) {
     Ok(_) => {
       log(LogLevel::Info, "connect_serial_port", format!("Порт {} успешно открыт", config.path));
     },
@@ -111,83 +133,29 @@ pub async fn connect_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPo
       {
         eprintln!("Failed to emit data: {}", e2);
       }
+      return Err(format!("Failed to open port {}: {}", config.path, e));
     },
   }
+  let codec = app.state::<ProtocolRegistry>().find(&config.protocol);
+  app
+    .state::<ConnectionRegistry>()
+    .register(transport.clone(), config.clone(), codec);
   tokio::time::sleep(Duration::from_millis(100)).await;
 
-  /* Открытие CAN порта*/
-  if config.protocol == "POECanable" || config.protocol == "POECanableFD" {
-    log(
-      LogLevel::Info,
-      "connect_serial_port",
-      format!("Инициализация CAN протокола: {}", config.protocol),
-    );
-    write(app.clone(), serial.clone(), config.path.clone().to_string(), "C\r".to_string()).map_err(|e| e.to_string())?;
-    if config.protocol == "POECanable" {
-      write(
-        app.clone(),
-        serial.clone(),
-        config.path.clone().to_string(),
-        format!("{}\r", config.can_bitrate.unwrap()),
-      )
-      .map_err(|e| {
-        log(LogLevel::Err, "connect_serial_port", format!("Не удалось отправить команду 'C': {}", e));
-        e.to_string()
-      })?;
-    } else {
-      /* ПОМЕНЯТЬ СТРОКУ  */
-      write(
-        app.clone(),
-        serial.clone(),
-        config.path.clone().to_string(),
-        format!("{}\r", config.canfd_bitrate.unwrap()),
-      )
-      .map_err(|e| {
-        log(
-          LogLevel::Err,
-          "connect_serial_port",
-          format!("Не удалось отправить команду скорости CANFD: {}", e),
-        );
-        e.to_string()
-      })?;
-      write(
-        app.clone(),
-        serial.clone(),
-        config.path.clone().to_string(),
-        format!("{}\r", config.canfd_data_bitrate.unwrap()),
-      )
-      .map_err(|e| {
-        log(
-          LogLevel::Err,
-          "connect_serial_port",
-          format!("Не удалось отправить команду скорости данных CANFD: {}", e),
-        );
-        e.to_string()
-      })?;
-    }
-    let init_commands = vec!["M0\r", "A0\r", "O\r"];
-    log(LogLevel::Info, "connect_serial_port", format!("Отправка команд инициализации для CAN"));
-
-    for command in init_commands {
-      write(app.clone(), serial.clone(), config.path.clone(), command.to_string()).map_err(|e| {
-        log(
-          LogLevel::Err,
-          "connect_serial_port",
-          format!("Не удалось отправить команду инициализации {}: {}", command, e),
-        );
-        e.to_string()
-      })?;
-    }
+  /* Инициализация протокола (для POECanable - открытие CAN канала адаптера) */
+  if let Err(e) = init_protocol(&app, transport.as_ref(), &config) {
+    app.state::<ConnectionRegistry>().close(&config.path);
+    return Err(e);
   }
 
   /* Установка флагов DTR и RTS */
   log(LogLevel::Info, "connect_serial_port", format!("Установка флагов DTR и RTS в false"));
 
-  let _ = write_data_terminal_ready(app.clone(), serial.clone(), config.path.to_string().clone(), false).map_err(|e| {
+  let _ = transport.write_dtr(false).map_err(|e| {
     log(LogLevel::Err, "connect_serial_port", format!("Не удалось установить флаг DTR: {}", e));
     e.to_string()
   });
-  let _ = write_request_to_send(app.clone(), serial.clone(), config.path.to_string().clone(), false).map_err(|e| {
+  let _ = transport.write_rts(false).map_err(|e| {
     log(LogLevel::Err, "connect_serial_port", format!("Не удалось установить флаг RTS: {}", e));
     e.to_string()
   });
@@ -195,7 +163,8 @@ pub async fn connect_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPo
   /* Начало прослушивания порта */
   log(LogLevel::Info, "connect_serial_port", format!("Начало прослушивания порта: {}", config.path));
 
-  match start_listening(app.clone(), serial.clone(), config.path.clone().to_string(), None, None) {
+  let mut adapter = None;
+  match transport.start_reading() {
     Ok(_) => {
       log(
         LogLevel::Info,
@@ -203,6 +172,37 @@ pub async fn connect_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPo
         format!("Прослушивание успешно начато на порту: {}", config.path),
       );
 
+      if CanMode::from_protocol(&config.protocol).is_ok() {
+        /* Сведения об адаптере (команды V, v, N), ответы ожидаются блокирующе */
+        if let Some(acks) = app.state::<ConnectionRegistry>().slcan_acks(&config.path) {
+          let (adapter_transport, adapter_config) = (transport.clone(), config.clone());
+          let info = tauri::async_runtime::spawn_blocking(move || identify_adapter(&acks, adapter_transport.as_ref(), &adapter_config))
+            .await
+            .map_err(|e| e.to_string())?;
+          for warning in info.warnings.iter() {
+            if let Err(e) = app.emit("app-status", format!("{}: {}", config.path, warning)) {
+              eprintln!("Failed to emit data: {}", e);
+            }
+          }
+          app
+            .state::<ConnectionRegistry>()
+            .set_adapter_info(&config.path, info.clone());
+          adapter = Some(info);
+
+          /* Фильтр приёма на адаптере - только для прошивки, которая его поддерживает */
+          if let Err(e) = apply_adapter_filter(&app, transport.as_ref(), &config) {
+            log(
+              LogLevel::Warn,
+              "connect_serial_port",
+              format!("Фильтр адаптера на порту {} не задан: {}", config.path, e),
+            );
+          }
+        }
+
+        /* Опрос состояния шины CAN (команда F) */
+        start_can_status_polling(app.clone(), transport.clone(), config.clone());
+      }
+
       if let Err(e) = app
         .clone()
         .emit("app-status", format!("Port {} opened successfully", config.path.clone()))
@@ -223,6 +223,8 @@ pub async fn connect_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPo
       {
         eprintln!("Failed to emit data: {}", e2);
       }
+      app.state::<ConnectionRegistry>().close(&config.path);
+      return Err(format!("Failed to start reading port {}: {}", config.path, e));
     },
   }
   log(
@@ -231,45 +233,79 @@ pub async fn connect_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPo
     format!("Процесс подключения завершён, возвращён путь: {}", config.path),
   );
 
-  Ok(config.path)
+  Ok(ConnectResult { path: config.path, adapter })
+}
+
+/// Создаёт пару псевдотерминалов и подключает одну из сторон как порт.
+///
+/// Вторую сторону открывает тестовый скрипт, играющий роль устройства. Порт закрывается
+/// обычным вызовом `close_serial_port` с возвращённым путём.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `config` - конфигурация подключения (путь заменяется на pty://N)
+///
+/// # Returns
+/// * `Ok(PtyPair)` - путь подключенного порта и путь второй стороны пары
+/// * `Err(String)` - ошибка создания пары или платформа не поддерживается
+#[command]
+pub async fn create_pty_pair(app: AppHandle<Wry>, mut config: SerialConfig) -> Result<PtyPair, String> {
+  config.path = format!("{}{}", PTY_PREFIX, PTY_COUNTER.fetch_add(1, Ordering::Relaxed) + 1);
+  log(LogLevel::Info, "create_pty_pair", format!("Создание пары PTY для порта: {}", config.path));
+
+  #[cfg(target_os = "linux")]
+  {
+    let path = connect_serial_port(app.clone(), config).await?.path;
+    match pty_peer_path(&path) {
+      Some(peer_path) => {
+        log(LogLevel::Info, "create_pty_pair", format!("Порт {} связан с {}", path, peer_path));
+        Ok(PtyPair { path, peer_path })
+      },
+      None => {
+        app.state::<ConnectionRegistry>().close(&path);
+        Err(format!("Failed to open PTY pair for {}", path))
+      },
+    }
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  {
+    let _ = app;
+    log(LogLevel::Err, "create_pty_pair", format!("Пары PTY поддерживаются только в Linux"));
+    Err("PTY pairs are only supported on Linux".to_string())
+  }
 }
 
 /// Закрывает подключенный серийный порт.
 ///
+/// Протокол и подписки обработчиков берутся из реестра подключений.
+///
 /// # Arguments
 /// * `app` - дескриптор приложения Tauri
-/// * `serial` - состояние серийного порта
 /// * `path` - путь к закрываемому порту
-/// * `event_id` - ID события прослушивания для отключения
-/// * `can_protocol` - флаг, указывающий, используется ли CAN протокол
 ///
 /// # Returns
 /// * `Ok(())` - успешно закрыто
-/// * `Err(String)` - ошибка закрытия
+/// * `Err(String)` - порт не открыт
 #[command]
-pub async fn close_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPort<Wry>>, path: String, event_id: u32, can_protocol: bool) -> Result<(), String> {
-  log(
-    LogLevel::Info,
-    "close_serial_port",
-    format!("Начало закрытия порта: {}, CAN протокол: {}", path, can_protocol),
-  );
+pub async fn close_serial_port(app: AppHandle<Wry>, path: String) -> Result<(), String> {
+  let connections = app.state::<ConnectionRegistry>();
+  let transport = connections.get_transport(&path)?;
+  let config = connections.get_config(&path);
 
-  /* Закрытие CAN порта */
-  if can_protocol == true {
-    log(LogLevel::Info, "close_serial_port", format!("Отправка команды 'C' для закрытия CAN порта"));
+  log(LogLevel::Info, "close_serial_port", format!("Начало закрытия порта: {}", path));
 
-    write(app.clone(), serial.clone(), path.clone().to_string(), "C\r".to_string()).map_err(|e| {
-      log(LogLevel::Err, "close_serial_port", format!("Не удалось отправить команду 'C': {}", e));
-      e.to_string()
-    })?;
+  /* Завершение протокола (для POECanable - закрытие CAN канала адаптера) */
+  if let Some(config) = &config {
+    teardown_protocol(&app, transport.as_ref(), config);
   }
 
   /* Отключение слушателей  */
-  log(LogLevel::Info, "close_serial_port", format!("Отключение слушателя с ID: {}", event_id));
-  app.unlisten(event_id);
+  log(LogLevel::Info, "close_serial_port", format!("Отключение слушателей порта: {}", path));
+  connections.remove_listeners(&path);
 
   log(LogLevel::Info, "close_serial_port", format!("Остановка прослушивания порта: {}", path));
-  let _ = stop_listening(app.clone(), serial.clone(), path.clone()).map_err(|e| {
+  let _ = transport.stop_reading().map_err(|e| {
     log(
       LogLevel::Err,
       "close_serial_port",
@@ -277,7 +313,7 @@ pub async fn close_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPort
     );
     e.to_string()
   });
-  match close(app.clone(), serial.clone(), path.clone()) {
+  match transport.close() {
     Ok(_) => {
       log(LogLevel::Info, "close_serial_port", format!("Порт {} успешно закрыт", path));
 
@@ -299,7 +335,7 @@ pub async fn close_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPort
       }
 
       log(LogLevel::Info, "close_serial_port", format!("Попытка принудительного закрытия порта: {}", path));
-      if let Err(e3) = force_close(app.clone(), serial.clone(), path.clone()) {
+      if let Err(e3) = transport.force_close() {
         log(
           LogLevel::Err,
           "close_serial_port",
@@ -316,6 +352,8 @@ pub async fn close_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPort
     },
   }
 
+  connections.remove(&path);
+
   log(LogLevel::Info, "close_serial_port", format!("Процесс закрытия порта завершён"));
 
   Ok(())
@@ -323,116 +361,110 @@ pub async fn close_serial_port(app: AppHandle<Wry>, serial: State<'_, SerialPort
 
 /// Отправляет данные в серийный порт по указанному протоколу.
 ///
+/// Команда кодируется протоколом из реестра `ProtocolRegistry`, итог отправки публикуется
+/// в событии `app-status` структурой `CommandStatus`. Если в конфигурации порта задан
+/// `command_retry` и протокол подтверждает команды, команда ждёт ответ устройства
+/// и отправляется повторно при его отсутствии или ошибке CRC.
+///
 /// # Arguments
 /// * `app` - дескриптор приложения Tauri
-/// * `serial` - состояние серийного порта
 /// * `protocol` - протокол передачи данных (SimpleSerial, POESerial, POECanable, POECanableFD)
 /// * `port_path` - путь к порту для отправки данных
 /// * `command_data` - JSON-объект с данными команды
 ///
 /// # Returns
-/// * `Ok(())` - данные успешно отправлены
-/// * `Err(String)` - ошибка отправки
+/// * `Ok(())` - команда обработана, итог отправки передан в `app-status`
+/// * `Err(String)` - порт не открыт или протокол не зарегистрирован
 #[command]
-pub async fn process_data_sending(
-  app: AppHandle,
-  serial: State<'_, SerialPort<Wry>>,
-  protocol: String,
-  port_path: String,
-  command_data: serde_json::Value,
-) -> Result<(), String> {
+pub async fn process_data_sending(app: AppHandle, protocol: String, port_path: String, command_data: serde_json::Value) -> Result<(), String> {
   log(
     LogLevel::Info,
     "process_data_sending",
     format!("Начало отправки данных по протоколу {} на порт {}", protocol, port_path),
   );
 
-  // Сопоставляем протокол с соответствующей функцией отправки
-  match protocol.clone().as_str() {
-    "SimpleSerial" => {
-      log(LogLevel::Info, "process_data_sending", format!("Отправка команды по протоколу SimpleSerial"));
-
-      // Вызываем функцию отправки для SimpleSerial
-      match send_simple_serial_command(app.clone(), serial.clone(), port_path.clone(), command_data) {
-        Ok(_) => {
-          log(LogLevel::Info, "process_data_sending", format!("Команда SimpleSerial успешно отправлена"));
-
-          if let Err(e) = app
-            .clone()
-            .emit("app-status", format!("Data was successfully sent"))
-          {
-            eprintln!("Failed to emit data: {}", e);
-          }
-        },
-        Err(e) => {
-          log(LogLevel::Err, "process_data_sending", format!("Ошибка отправки команды SimpleSerial: {}", e));
-
-          if let Err(e2) = app
-            .clone()
-            .emit("app-status", format!("Failed to send data: {}", e))
-          {
-            eprintln!("Failed to emit data: {}", e2);
-          }
-        },
-      }
-    },
-    "POESerial" => {
-      log(LogLevel::Info, "process_data_sending", format!("Отправка команды по протоколу POESerial"));
-
-      // Логируем начало отправки по POESerial протоколу
-      match send_poe_serial_command(app.clone(), serial.clone(), port_path.clone(), command_data) {
-        Ok(_) => {
-          log(LogLevel::Info, "process_data_sending", format!("Команда POESerial успешно отправлена"));
-
-          if let Err(e) = app
-            .clone()
-            .emit("app-status", format!("Data was successfully sent"))
-          {
-            eprintln!("Failed to emit data: {}", e);
-          }
-        },
-        Err(e) => {
-          if let Err(e2) = app
-            .clone()
-            .emit("app-status", format!("Failed to send data: {}", e))
-          {
-            eprintln!("Failed to emit data: {}", e2);
-          }
+  let connections = app.state::<ConnectionRegistry>();
+  let transport = connections.get_transport(&port_path)?;
+
+  let config = connections
+    .get_config(&port_path)
+    .ok_or_else(|| format!("Port {} is not open", port_path))?;
+  let codec = app.state::<ProtocolRegistry>().get(&protocol)?;
+  let retry_policy = config
+    .command_retry
+    .clone()
+    .filter(|_| codec.supports_reply());
+  let transaction_lock = connections
+    .transaction_lock(&port_path)
+    .ok_or_else(|| format!("Port {} is not open", port_path))?;
+
+  log(LogLevel::Info, "process_data_sending", format!("Отправка команды по протоколу {}", protocol));
+
+  let started = Instant::now();
+  let app_clone = app.clone();
+  let path_clone = port_path.clone();
+  let outcome = tauri::async_runtime::spawn_blocking(move || {
+    // Команды с ожиданием ответа выполняются по очереди, чтобы ответы не путались
+    let _guard = transaction_lock.lock().unwrap();
+
+    match retry_policy {
+      Some(policy) => run_with_retry(&policy, &path_clone, |timeout| {
+        codec.send_awaiting_reply(&app_clone, transport.as_ref(), &config, command_data.clone(), timeout)
+      }),
+      // Без политики повторов команда отправляется без ожидания ответа
+      None => RetryOutcome {
+        attempts: 1,
+        result: codec.send(&app_clone, transport.as_ref(), &config, command_data),
+      },
+    }
+  })
+  .await
+  .map_err(|e| e.to_string())?;
+
+  connections.record_send(&port_path, outcome.result.is_ok());
+  let status = match outcome.result {
+    Ok(()) => {
+      log(
+        LogLevel::Info,
+        "process_data_sending",
+        format!("Команда {} успешно отправлена, попыток: {}", protocol, outcome.attempts),
+      );
+      CommandStatus {
+        path: port_path.clone(),
+        protocol: protocol.clone(),
+        success: true,
+        attempts: outcome.attempts,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        error: None,
+        message: if outcome.attempts > 1 {
+          format!("Data was successfully sent after {} attempts", outcome.attempts)
+        } else {
+          format!("Data was successfully sent")
         },
       }
     },
-    "POECanable" | "POECanableFD" => {
-      log(LogLevel::Info, "process_data_sending", format!("Отправка команды по протоколу {}", protocol));
-
-      // Вызываем функцию отправки для POECanable
-      match send_poe_canable_command(app.clone(), serial.clone(), protocol.clone(), port_path.clone(), command_data) {
-        Ok(_) => {
-          log(LogLevel::Info, "process_data_sending", format!("Команда {} успешно отправлена", protocol));
-
-          if let Err(e) = app
-            .clone()
-            .emit("app-status", format!("Data was successfully sent"))
-          {
-            eprintln!("Failed to emit data: {}", e);
-          }
-        },
-        Err(e) => {
-          log(LogLevel::Err, "process_data_sending", format!("Ошибка отправки команды {}: {}", protocol, e));
-
-          if let Err(e2) = app
-            .clone()
-            .emit("app-status", format!("Failed to send data: {}", e))
-          {
-            eprintln!("Failed to emit data: {}", e2);
-          }
-        },
+    Err(e) => {
+      log(
+        LogLevel::Err,
+        "process_data_sending",
+        format!("Ошибка отправки команды {} после {} попыток: {}", protocol, outcome.attempts, e),
+      );
+      CommandStatus {
+        path: port_path.clone(),
+        protocol: protocol.clone(),
+        success: false,
+        attempts: outcome.attempts,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        message: format!("Failed to send data: {}", e),
+        error: Some(e),
       }
     },
-    _ => {
-      log(LogLevel::Warn, "process_data_sending", format!("Неизвестный протокол: {}", protocol));
-    },
   };
 
+  if let Err(e) = app.emit("app-status", status) {
+    eprintln!("Failed to emit data: {}", e);
+  }
+
   log(LogLevel::Info, "process_data_sending", format!("Процесс отправки данных завершён"));
 
   Ok(())
@@ -442,38 +474,39 @@ pub async fn process_data_sending(
 ///
 /// # Arguments
 /// * `app` - дескриптор приложения Tauri
-/// * `serial` - состояние серийного порта
 /// * `path` - путь к порту для перезапуска
 ///
 /// # Returns
 /// * `Ok(())` - перезапуск успешно выполнен
-/// * `Err(())` - ошибка выполнения
+/// * `Err(String)` - порт не открыт
 #[command]
-pub async fn hard_restart(app: AppHandle<Wry>, serial: State<'_, SerialPort<Wry>>, path: String) -> Result<(), ()> {
+pub async fn hard_restart(app: AppHandle<Wry>, path: String) -> Result<(), String> {
   log(
     LogLevel::Info,
     "hard_restart",
     format!("Начало жёсткого перезапуска устройства на порту: {}", path),
   );
 
+  let transport = app.state::<ConnectionRegistry>().get_transport(&path)?;
+
   // Шаг 1: Устанавливаем DTR в true и RTS в false
   log(LogLevel::Info, "hard_restart", format!("Установка DTR в true и RTS в false"));
-  let _ = write_data_terminal_ready(app.clone(), serial.clone(), path.to_string().clone(), true).map_err(|e| e.to_string());
-  let _ = write_request_to_send(app.clone(), serial.clone(), path.to_string().clone(), false).map_err(|e| e.to_string());
+  let _ = transport.write_dtr(true);
+  let _ = transport.write_rts(false);
 
   tokio::time::sleep(Duration::from_millis(100)).await;
 
   // Шаг 2: Устанавливаем DTR в false и RTS в true
   log(LogLevel::Info, "hard_restart", format!("Установка DTR в false и RTS в true"));
-  let _ = write_data_terminal_ready(app.clone(), serial.clone(), path.to_string().clone(), false).map_err(|e| e.to_string());
-  let _ = write_request_to_send(app.clone(), serial.clone(), path.to_string().clone(), true).map_err(|e| e.to_string());
+  let _ = transport.write_dtr(false);
+  let _ = transport.write_rts(true);
 
   tokio::time::sleep(Duration::from_millis(100)).await;
 
   // Шаг 3: Устанавливаем DTR и RTS в true (завершение последовательности)
   log(LogLevel::Info, "hard_restart", format!("Установка DTR и RTS в true"));
-  let _ = write_data_terminal_ready(app.clone(), serial.clone(), path.to_string().clone(), true).map_err(|e| e.to_string());
-  let _ = write_request_to_send(app.clone(), serial.clone(), path.to_string().clone(), true).map_err(|e| e.to_string());
+  let _ = transport.write_dtr(true);
+  let _ = transport.write_rts(true);
 
   Ok(())
 }
@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{command, State};
+
+use crate::codec::ProtocolCodec;
+use crate::models::{AdapterInfo, ConnectionInfo, ConnectionStatsSnapshot, SerialConfig};
+use crate::poe_canable::CanMode;
+use crate::slcan_ack::SlcanAckTracker;
+use crate::telemetry::{HeapTelemetry, HeapTelemetryEvents};
+use crate::transports::Transport;
+use crate::{log, LogLevel};
+
+/// Счётчики работы открытого порта
+#[derive(Default)]
+pub struct ConnectionStats {
+  pub bytes_received: AtomicU64,
+  pub commands_sent: AtomicU64,
+  pub send_errors: AtomicU64,
+  pub packets_received: AtomicU64,
+  pub crc_errors: AtomicU64,
+}
+
+impl ConnectionStats {
+  /// Снимок счётчиков для передачи на фронтенд
+  fn snapshot(&self) -> ConnectionStatsSnapshot {
+    ConnectionStatsSnapshot {
+      bytes_received: self.bytes_received.load(Ordering::Relaxed),
+      commands_sent: self.commands_sent.load(Ordering::Relaxed),
+      send_errors: self.send_errors.load(Ordering::Relaxed),
+      packets_received: self.packets_received.load(Ordering::Relaxed),
+      crc_errors: self.crc_errors.load(Ordering::Relaxed),
+    }
+  }
+}
+
+/// Открытый порт: транспорт, конфигурация, подписки обработчиков протоколов и статистика
+struct Connection {
+  transport: Arc<dyn Transport>,
+  config: SerialConfig,
+  /// Протокол порта, `None` - протокол не зарегистрирован
+  codec: Option<Arc<dyn ProtocolCodec>>,
+  listener_ids: Vec<u32>,
+  stats_listener_id: u32,
+  stats: Arc<ConnectionStats>,
+  transaction_lock: Arc<Mutex<()>>,
+  heap_telemetry: Arc<Mutex<HeapTelemetry>>,
+  heap_telemetry_events: Arc<HeapTelemetryEvents>,
+  /// Подтверждения команд адаптера SLCAN и ID его подписки, только для протоколов CAN
+  slcan_acks: Option<(Arc<SlcanAckTracker>, u32)>,
+  /// Сведения об адаптере SLCAN, полученные после открытия порта
+  adapter: Option<AdapterInfo>,
+  opened_at: u64,
+}
+
+impl Connection {
+  /// Отписывает от транспорта служебные обработчики порта
+  fn unsubscribe_internal(&self) {
+    self.transport.unsubscribe(self.stats_listener_id);
+    if let Some((_, id)) = &self.slcan_acks {
+      self.transport.unsubscribe(*id);
+    }
+  }
+
+  /// Отписывает от транспорта обработчики протоколов и служебные обработчики
+  fn unsubscribe_all(&self) {
+    for id in self.listener_ids.iter() {
+      self.transport.unsubscribe(*id);
+    }
+    self.unsubscribe_internal();
+  }
+
+  fn info(&self, path: &str) -> ConnectionInfo {
+    ConnectionInfo {
+      path: path.to_string(),
+      protocol: self.config.protocol.clone(),
+      config: self.config.clone(),
+      listener_ids: self.listener_ids.clone(),
+      opened_at: self.opened_at,
+      stats: self.stats.snapshot(),
+    }
+  }
+}
+
+/// Останавливает чтение и закрывает транспорт, при неудаче - принудительно
+fn shutdown_transport(transport: &dyn Transport) {
+  let _ = transport.stop_reading();
+  if let Err(e) = transport.close() {
+    log(
+      LogLevel::Warn,
+      "shutdown_transport",
+      format!("Не удалось закрыть порт {}: {}, принудительное закрытие", transport.path(), e),
+    );
+    if let Err(e2) = transport.force_close() {
+      log(
+        LogLevel::Err,
+        "shutdown_transport",
+        format!("Не удалось принудительно закрыть порт {}: {}", transport.path(), e2),
+      );
+    }
+  }
+}
+
+/// Реестр открытых портов, хранящийся в состоянии Tauri
+#[derive(Default)]
+pub struct ConnectionRegistry {
+  connections: Mutex<HashMap<String, Connection>>,
+}
+
+impl ConnectionRegistry {
+  /// Регистрирует открытый транспорт с конфигурацией подключения
+  ///
+  /// Подключение, ранее зарегистрированное по тому же пути, отписывается и закрывается,
+  /// состояние разбора протокола порта сбрасывается.
+  pub fn register(&self, transport: Arc<dyn Transport>, config: SerialConfig, codec: Option<Arc<dyn ProtocolCodec>>) {
+    if let Some(codec) = &codec {
+      codec.reset_port(transport.path());
+    }
+    let stats = Arc::new(ConnectionStats::default());
+    let stats_clone = stats.clone();
+    let stats_listener_id = transport.subscribe(Box::new(move |data| {
+      stats_clone
+        .bytes_received
+        .fetch_add(data.len() as u64, Ordering::Relaxed);
+    }));
+    let slcan_acks = CanMode::from_protocol(&config.protocol).ok().map(|_| {
+      let tracker = Arc::new(SlcanAckTracker::default());
+      let tracker_clone = tracker.clone();
+      let id = transport.subscribe(Box::new(move |data| tracker_clone.push(&data)));
+      (tracker, id)
+    });
+
+    let connection = Connection {
+      transport: transport.clone(),
+      config,
+      codec,
+      listener_ids: Vec::new(),
+      stats_listener_id,
+      stats,
+      transaction_lock: Arc::new(Mutex::new(())),
+      heap_telemetry: Arc::new(Mutex::new(HeapTelemetry::default())),
+      heap_telemetry_events: Arc::new(HeapTelemetryEvents::default()),
+      slcan_acks,
+      adapter: None,
+      opened_at: chrono::Local::now().timestamp_millis() as u64,
+    };
+
+    let previous = self
+      .connections
+      .lock()
+      .unwrap()
+      .insert(transport.path().to_string(), connection);
+    if let Some(previous) = previous {
+      log(
+        LogLevel::Warn,
+        "register",
+        format!("Порт {} открыт повторно, предыдущее подключение закрыто", transport.path()),
+      );
+      previous.unsubscribe_all();
+      if !Arc::ptr_eq(&previous.transport, &transport) {
+        shutdown_transport(previous.transport.as_ref());
+      }
+    }
+  }
+
+  /// Возвращает транспорт открытого порта
+  pub fn get_transport(&self, path: &str) -> Result<Arc<dyn Transport>, String> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .map(|connection| connection.transport.clone())
+      .ok_or_else(|| {
+        log(LogLevel::Err, "get_transport", format!("Транспорт для порта {} не найден", path));
+        format!("Port {} is not open", path)
+      })
+  }
+
+  /// Возвращает транспорт, если порт открыт, без записи ошибки в лог
+  pub fn find_transport(&self, path: &str) -> Option<Arc<dyn Transport>> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .map(|connection| connection.transport.clone())
+  }
+
+  /// Возвращает конфигурацию открытого порта
+  pub fn get_config(&self, path: &str) -> Option<SerialConfig> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .map(|connection| connection.config.clone())
+  }
+
+  /// Возвращает счётчики открытого порта
+  pub fn stats(&self, path: &str) -> Option<Arc<ConnectionStats>> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .map(|connection| connection.stats.clone())
+  }
+
+  /// Возвращает блокировку, через которую транзакции с портом выполняются по очереди
+  pub fn transaction_lock(&self, path: &str) -> Option<Arc<Mutex<()>>> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .map(|connection| connection.transaction_lock.clone())
+  }
+
+  /// Возвращает отслеживание подтверждений адаптера SLCAN на порту
+  pub fn slcan_acks(&self, path: &str) -> Option<Arc<SlcanAckTracker>> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .and_then(|connection| connection.slcan_acks.as_ref())
+      .map(|(tracker, _)| tracker.clone())
+  }
+
+  /// Сохраняет сведения об адаптере SLCAN на порту
+  pub fn set_adapter_info(&self, path: &str, info: AdapterInfo) {
+    let mut connections = self.connections.lock().unwrap();
+    if let Some(connection) = connections.get_mut(path) {
+      connection.adapter = Some(info);
+    }
+  }
+
+  /// Возвращает сведения об адаптере SLCAN на порту
+  pub fn adapter_info(&self, path: &str) -> Option<AdapterInfo> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .and_then(|connection| connection.adapter.clone())
+  }
+
+  /// Возвращает историю свободной памяти устройства на порту
+  pub fn heap_telemetry(&self, path: &str) -> Option<Arc<Mutex<HeapTelemetry>>> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .map(|connection| connection.heap_telemetry.clone())
+  }
+
+  /// Возвращает настройку периодической публикации телеметрии порта
+  pub fn heap_telemetry_events(&self, path: &str) -> Option<Arc<HeapTelemetryEvents>> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .map(|connection| connection.heap_telemetry_events.clone())
+  }
+
+  /// Учитывает результат отправки команды в статистике порта
+  pub fn record_send(&self, path: &str, success: bool) {
+    if let Some(stats) = self.stats(path) {
+      let counter = if success { &stats.commands_sent } else { &stats.send_errors };
+      counter.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  /// Запоминает ID подписки обработчика протокола на порт
+  pub fn add_listener(&self, path: &str, id: u32) {
+    let mut connections = self.connections.lock().unwrap();
+    if let Some(connection) = connections.get_mut(path) {
+      connection.listener_ids.push(id);
+    }
+  }
+
+  /// Отписывает от порта все обработчики протоколов
+  pub fn remove_listeners(&self, path: &str) {
+    let mut connections = self.connections.lock().unwrap();
+    if let Some(connection) = connections.get_mut(path) {
+      for id in connection.listener_ids.drain(..) {
+        connection.transport.unsubscribe(id);
+      }
+    }
+  }
+
+  /// Сбрасывает состояние разбора протокола порта, например после переподключения
+  pub fn reset_protocol_state(&self, path: &str) {
+    let codec = self
+      .connections
+      .lock()
+      .unwrap()
+      .get(path)
+      .and_then(|connection| connection.codec.clone());
+    if let Some(codec) = codec {
+      codec.reset_port(path);
+    }
+  }
+
+  /// Удаляет порт из реестра вместе со всеми подписками и состоянием разбора протокола
+  pub fn remove(&self, path: &str) -> Option<Arc<dyn Transport>> {
+    let connection = self.connections.lock().unwrap().remove(path)?;
+    connection.unsubscribe_all();
+    if let Some(codec) = &connection.codec {
+      codec.reset_port(path);
+    }
+    Some(connection.transport)
+  }
+
+  /// Удаляет порт из реестра и закрывает его транспорт
+  ///
+  /// # Returns
+  /// * `true` - порт был открыт и закрыт
+  /// * `false` - порт не зарегистрирован
+  pub fn close(&self, path: &str) -> bool {
+    match self.remove(path) {
+      Some(transport) => {
+        shutdown_transport(transport.as_ref());
+        true
+      },
+      None => false,
+    }
+  }
+
+  /// Возвращает сведения об открытом порте
+  pub fn info(&self, path: &str) -> Option<ConnectionInfo> {
+    let connections = self.connections.lock().unwrap();
+    connections
+      .get(path)
+      .map(|connection| connection.info(path))
+  }
+
+  /// Возвращает сведения обо всех открытых портах
+  pub fn list(&self) -> Vec<ConnectionInfo> {
+    let connections = self.connections.lock().unwrap();
+    let mut result: Vec<ConnectionInfo> = connections
+      .iter()
+      .map(|(path, connection)| connection.info(path))
+      .collect();
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+    result
+  }
+}
+
+/// Возвращает список открытых портов с конфигурацией, подписками и статистикой.
+///
+/// # Arguments
+/// * `connections` - реестр открытых портов
+///
+/// # Returns
+/// * `Vec<ConnectionInfo>` - сведения об открытых портах
+#[command]
+pub fn list_connections(connections: State<'_, ConnectionRegistry>) -> Vec<ConnectionInfo> {
+  connections.list()
+}
+
+/// Возвращает сведения об открытом порте.
+///
+/// # Arguments
+/// * `connections` - реестр открытых портов
+/// * `path` - путь к порту
+///
+/// # Returns
+/// * `Ok(ConnectionInfo)` - сведения о порте
+/// * `Err(String)` - порт не открыт
+#[command]
+pub fn get_connection(connections: State<'_, ConnectionRegistry>, path: String) -> Result<ConnectionInfo, String> {
+  connections
+    .info(&path)
+    .ok_or_else(|| format!("Port {} is not open", path))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::poe_canable::{PoeCanableCodec, PARTIAL_PACKETS};
+  use crate::transports::virtual_port::VirtualTransport;
+  use std::sync::atomic::AtomicUsize;
+  use std::thread;
+  use std::time::Duration;
+
+  /// Открывает виртуальный порт с эхом и запускает чтение
+  fn open_loopback(path: &str) -> Arc<dyn Transport> {
+    let transport: Arc<dyn Transport> = Arc::new(VirtualTransport::loopback(path.to_string()));
+    transport
+      .open(&SerialConfig::test(path, "SimpleSerial"))
+      .unwrap();
+    transport.start_reading().unwrap();
+    transport
+  }
+
+  #[test]
+  fn registering_same_path_closes_previous_connection() {
+    let registry = ConnectionRegistry::default();
+    let path = "loop://registry-replace";
+    let previous = open_loopback(path);
+    registry.register(previous.clone(), SerialConfig::test(path, "SimpleSerial"), None);
+
+    let received = Arc::new(AtomicUsize::new(0));
+    let received_clone = received.clone();
+    let listener_id = previous.subscribe(Box::new(move |data| {
+      received_clone.fetch_add(data.len(), Ordering::SeqCst);
+    }));
+    registry.add_listener(path, listener_id);
+
+    let current = open_loopback(path);
+    registry.register(current.clone(), SerialConfig::test(path, "SimpleSerial"), None);
+
+    assert!(previous.write(b"x").is_err());
+    assert!(Arc::ptr_eq(&registry.find_transport(path).unwrap(), &current));
+    assert_eq!(registry.list().len(), 1);
+
+    current.write(b"abc").unwrap();
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(
+      registry
+        .stats(path)
+        .unwrap()
+        .bytes_received
+        .load(Ordering::Relaxed),
+      3
+    );
+    assert_eq!(received.load(Ordering::SeqCst), 0);
+    assert!(registry.close(path));
+  }
+
+  #[test]
+  fn close_removes_and_closes_transport() {
+    let registry = ConnectionRegistry::default();
+    let path = "loop://registry-close";
+    let transport = open_loopback(path);
+    registry.register(transport.clone(), SerialConfig::test(path, "SimpleSerial"), None);
+
+    assert!(registry.close(path));
+    assert!(registry.find_transport(path).is_none());
+    assert!(transport.write(b"x").is_err());
+    assert!(!registry.close(path));
+  }
+
+  #[test]
+  fn codec_port_state_is_reset_on_register_and_close() {
+    let registry = ConnectionRegistry::default();
+    let path = "loop://registry-reset";
+    let codec: Arc<dyn ProtocolCodec> = Arc::new(PoeCanableCodec::can());
+    let has_partial_packets = || PARTIAL_PACKETS.lock().unwrap().contains_key(path);
+
+    PARTIAL_PACKETS
+      .lock()
+      .unwrap()
+      .insert(path.to_string(), HashMap::new());
+    registry.register(open_loopback(path), SerialConfig::test(path, "POECanable"), Some(codec));
+    assert!(!has_partial_packets());
+
+    PARTIAL_PACKETS
+      .lock()
+      .unwrap()
+      .insert(path.to_string(), HashMap::new());
+    assert!(registry.close(path));
+    assert!(!has_partial_packets());
+  }
+}
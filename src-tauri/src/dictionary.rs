@@ -0,0 +1,233 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tauri::{command, AppHandle, Manager, Wry};
+
+use crate::{log, LogLevel};
+
+/// Имя файла словаря в каталоге настроек приложения
+const DICTIONARY_FILE_NAME: &str = "dictionary.json";
+
+/// Словарь по умолчанию, записывается в каталог настроек при первом запуске
+const DEFAULT_DICTIONARY: &str = r#"{
+  "headers": {
+    "GET": 0,
+    "SET": 1,
+    "OK!": 2,
+    "ER!": 3
+  },
+  "arguments": {
+    "Restart": 0,
+    "DefaultConfig": 1,
+    "CreateBackup": 2,
+    "UpgradeDevice": 3,
+    "UpgradeByBinFile": 4,
+    "DeleteFile": 5,
+
+    "CheckWebSocket": 10,
+    "APList": 11,
+    "DeviceList": 12,
+    "FSInfo": 13,
+
+    "ModuleList": 20,
+    "ModuleCapture": 21,
+    "ModuleInfo": 22,
+    "ModuleMode": 23,
+    "ModuleConfig": 24,
+    "ModuleTelemetry": 25,
+    "ModuleStream-1": 26,
+    "ModuleStream-2": 27,
+    "ModuleStream-3": 28,
+
+    "UpdateStart": 50,
+    "UpdateProcess": 51,
+    "UpdateCompleted": 52,
+    "UpdateError": 53
+  }
+}
+"#;
+
+/// Словарь заголовков и аргументов протоколов POE (имя -> код)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProtocolDictionary {
+  pub headers: HashMap<String, u32>,
+  pub arguments: HashMap<String, u32>,
+}
+
+impl Default for ProtocolDictionary {
+  fn default() -> Self {
+    serde_json::from_str(DEFAULT_DICTIONARY).expect("default dictionary is valid JSON")
+  }
+}
+
+impl ProtocolDictionary {
+  /// Имя заголовка по коду
+  pub fn header_name(&self, code: u32) -> Option<String> {
+    find_name(&self.headers, code)
+  }
+
+  /// Имя аргумента по коду
+  pub fn argument_name(&self, code: u32) -> Option<String> {
+    find_name(&self.arguments, code)
+  }
+
+  /// Код заголовка по имени
+  pub fn header_code(&self, name: &str) -> Option<u32> {
+    self.headers.get(name).copied()
+  }
+
+  /// Код аргумента по имени
+  pub fn argument_code(&self, name: &str) -> Option<u32> {
+    self.arguments.get(name).copied()
+  }
+}
+
+/// Ищет имя по коду в таблице словаря
+fn find_name(table: &HashMap<String, u32>, code: u32) -> Option<String> {
+  table
+    .iter()
+    .find(|(_, value)| **value == code)
+    .map(|(name, _)| name.clone())
+}
+
+lazy_static! {
+  /// Текущий словарь протоколов, общий для всех портов
+  static ref DICTIONARY: RwLock<ProtocolDictionary> = RwLock::new(ProtocolDictionary::default());
+}
+
+/// Возвращает копию текущего словаря
+pub fn dictionary() -> ProtocolDictionary {
+  DICTIONARY.read().unwrap().clone()
+}
+
+/// Находит имена заголовка и аргумента по кодам (POECanable)
+pub fn resolve_codes(header: u32, argument: u32) -> (Option<String>, Option<String>) {
+  let dictionary = DICTIONARY.read().unwrap();
+  (dictionary.header_name(header), dictionary.argument_name(argument))
+}
+
+/// Находит коды заголовка и аргумента по именам (POESerial)
+pub fn resolve_names(header: &str, argument: &str) -> (Option<u32>, Option<u32>) {
+  let dictionary = DICTIONARY.read().unwrap();
+  (dictionary.header_code(header), dictionary.argument_code(argument))
+}
+
+/// Проверяет по словарю имена заголовка и аргумента команды POESerial
+///
+/// # Returns
+/// * `Ok(())` - заголовок и аргумент есть в словаре
+/// * `Err(String)` - неизвестный заголовок или аргумент
+pub fn validate_serial_command(header: &str, argument: &str) -> Result<(), String> {
+  let dictionary = DICTIONARY.read().unwrap();
+  if dictionary.header_code(header).is_none() {
+    return Err(format!("Unknown header: {}", header));
+  }
+  if dictionary.argument_code(argument).is_none() {
+    return Err(format!("Unknown argument: {}", argument));
+  }
+  Ok(())
+}
+
+/// Проверяет по словарю коды заголовка и аргумента команды POECanable
+///
+/// # Returns
+/// * `Ok(())` - коды есть в словаре
+/// * `Err(String)` - неизвестный код заголовка или аргумента
+pub fn validate_can_command(header: u32, argument: u32) -> Result<(), String> {
+  let dictionary = DICTIONARY.read().unwrap();
+  if dictionary.header_name(header).is_none() {
+    return Err(format!("Unknown header code: {}", header));
+  }
+  if dictionary.argument_name(argument).is_none() {
+    return Err(format!("Unknown argument code: {}", argument));
+  }
+  Ok(())
+}
+
+/// Путь к файлу словаря в каталоге настроек приложения
+fn dictionary_path(app: &AppHandle<Wry>) -> Result<PathBuf, String> {
+  app
+    .path()
+    .app_config_dir()
+    .map(|dir| dir.join(DICTIONARY_FILE_NAME))
+    .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+/// Загружает словарь из файла настроек, создавая файл со словарём по умолчанию при его отсутствии
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+///
+/// # Returns
+/// * `Ok(ProtocolDictionary)` - загруженный словарь
+/// * `Err(String)` - ошибка чтения или разбора файла, текущий словарь не меняется
+pub fn load_dictionary(app: &AppHandle<Wry>) -> Result<ProtocolDictionary, String> {
+  let path = dictionary_path(app)?;
+
+  if !path.exists() {
+    log(
+      LogLevel::Info,
+      "load_dictionary",
+      format!("Файл словаря не найден, создаётся словарь по умолчанию: {}", path.display()),
+    );
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    fs::write(&path, DEFAULT_DICTIONARY).map_err(|e| format!("Failed to write dictionary file: {}", e))?;
+  }
+
+  let content = fs::read_to_string(&path).map_err(|e| {
+    log(
+      LogLevel::Err,
+      "load_dictionary",
+      format!("Не удалось прочитать словарь {}: {}", path.display(), e),
+    );
+    format!("Failed to read dictionary file: {}", e)
+  })?;
+  let loaded: ProtocolDictionary = serde_json::from_str(&content).map_err(|e| {
+    log(
+      LogLevel::Err,
+      "load_dictionary",
+      format!("Не удалось разобрать словарь {}: {}", path.display(), e),
+    );
+    format!("Failed to parse dictionary file: {}", e)
+  })?;
+
+  log(
+    LogLevel::Info,
+    "load_dictionary",
+    format!(
+      "Словарь загружен из {}: заголовков {}, аргументов {}",
+      path.display(),
+      loaded.headers.len(),
+      loaded.arguments.len()
+    ),
+  );
+  *DICTIONARY.write().unwrap() = loaded.clone();
+  Ok(loaded)
+}
+
+/// Возвращает текущий словарь заголовков и аргументов.
+///
+/// # Returns
+/// * `ProtocolDictionary` - словарь (имя -> код)
+#[command]
+pub fn get_dictionary() -> ProtocolDictionary {
+  dictionary()
+}
+
+/// Перечитывает словарь из файла настроек после его редактирования.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+///
+/// # Returns
+/// * `Ok(ProtocolDictionary)` - загруженный словарь
+/// * `Err(String)` - ошибка чтения или разбора файла
+#[command]
+pub fn reload_dictionary(app: AppHandle<Wry>) -> Result<ProtocolDictionary, String> {
+  load_dictionary(&app)
+}
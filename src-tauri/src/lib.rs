@@ -1,16 +1,30 @@
+use crate::codec::{list_protocols, process_protocol, ProtocolRegistry};
 use crate::poe_canable::process_poe_canable;
 use crate::poe_serial::process_poe_serial;
+use crate::poe_serial_transaction::poe_serial_transact;
 use crate::simple_serial::process_simple_serial;
 
 pub mod cmd;
+pub mod connections;
 pub mod convertation;
+pub mod dictionary;
 pub mod models;
+pub mod ports;
 pub mod protocols;
+pub mod schemas;
+pub mod telemetry;
+pub mod transports;
 
 pub use cmd::*;
+pub use connections::*;
 pub use convertation::*;
+pub use dictionary::*;
 pub use models::*;
+pub use ports::*;
 pub use protocols::*;
+pub use schemas::*;
+pub use telemetry::*;
+pub use transports::*;
 
 /// Точка входа в приложение Tauri
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -20,17 +34,28 @@ pub fn run() {
     .plugin(tauri_plugin_fs::init()) // Плагин для работы с файловой системой
     .plugin(tauri_plugin_opener::init()) // Плагин для открытия файлов
     .plugin(tauri_plugin_serialplugin::init()) // Плагин для работы с серийными портами
-    .setup(|_app| {
+    .manage(ConnectionRegistry::default()) // Реестр открытых портов
+    .manage(ProtocolRegistry::default()) // Реестр протоколов
+    .setup(|app| {
       #[cfg(debug_assertions)] // Открываем devtools в режиме отладки
       {
         use tauri::Manager;
-        let window = _app.get_webview_window("main").unwrap();
+        let window = app.get_webview_window("main").unwrap();
         window.open_devtools();
       }
+      if let Err(e) = load_dictionary(app.handle()) {
+        eprintln!("Failed to load dictionary: {}", e); // Остаётся словарь по умолчанию
+      }
+      if let Err(e) = load_schemas(app.handle()) {
+        eprintln!("Failed to load schemas: {}", e); // Значения не проверяются
+      }
+      start_port_watcher(app.handle().clone()); // Наблюдатель за подключением и отключением портов
       Ok(())
     })
     .invoke_handler(tauri::generate_handler![
-      connect_serial_port, close_serial_port, process_data_sending, hard_restart, process_simple_serial, process_poe_serial, process_poe_canable
+      connect_serial_port, close_serial_port, create_pty_pair, list_ports, list_connections, get_connection, get_heap_telemetry, set_heap_telemetry_interval,
+      get_dictionary, reload_dictionary, get_schemas, reload_schemas, list_protocols, process_data_sending, hard_restart, process_simple_serial,
+      process_poe_serial, poe_serial_transact, process_poe_canable, process_protocol
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 /* Структура для параметров подключения */
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SerialConfig {
   pub path: String,
   pub baud_rate: u32,
@@ -12,10 +12,101 @@ pub struct SerialConfig {
   pub parity: u32,
   pub stop_bits: u32,
   pub timeout: Option<u64>,
+  pub connect_timeout_ms: Option<u64>,
   pub protocol: String,
   pub can_bitrate: Option<String>,
   pub canfd_bitrate: Option<String>,
   pub canfd_data_bitrate: Option<String>,
+  pub can_timestamps: Option<bool>,
+  pub can_status_interval_ms: Option<u64>,
+  pub can_filters: Option<Vec<CanFilter>>,
+  pub can_adapter_filter: Option<bool>,
+  pub reconnect: Option<ReconnectPolicy>,
+  pub drop_corrupt_packets: Option<bool>,
+  pub poe_serial_escaped: Option<bool>,
+  pub command_retry: Option<CommandRetryPolicy>,
+}
+
+#[cfg(test)]
+impl SerialConfig {
+  /// Конфигурация порта 115200 8N1 без дополнительных настроек для тестов
+  pub(crate) fn test(path: &str, protocol: &str) -> Self {
+    serde_json::from_value(serde_json::json!({
+      "path": path,
+      "baud_rate": 115200,
+      "data_bits": 8,
+      "flow_control": 0,
+      "parity": 0,
+      "stop_bits": 1,
+      "timeout": 500,
+      "protocol": protocol,
+    }))
+    .unwrap()
+  }
+}
+
+/* Политика автоматического переподключения при пропаже устройства */
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReconnectPolicy {
+  pub max_attempts: u32,
+  pub backoff_ms: u64,
+  pub max_backoff_ms: Option<u64>,
+  pub match_serial_number: bool,
+}
+
+/* Политика повтора команд POE при отсутствии ответа или ошибке CRC */
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommandRetryPolicy {
+  pub attempts: u32,
+  pub timeout_ms: u64,
+  pub backoff_ms: u64,
+}
+
+/* Фильтр принимаемых CAN-фреймов по полю расширенного ID POE: (значение поля & mask) == (id & mask) */
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CanFilter {
+  pub field: CanFilterField,
+  pub id: u32,
+  /* Маска сравниваемых бит поля, по умолчанию - все биты */
+  pub mask: Option<u32>,
+}
+
+/* Поле расширенного ID POE, к которому применяется фильтр */
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanFilterField {
+  TargetId,
+  ReturnId,
+  ArgumentCode,
+}
+
+/* Описание протокола для выбора на фронтенде */
+#[derive(Serialize, Clone, Debug)]
+pub struct ProtocolInfo {
+  pub name: String,
+  pub description: String,
+  pub parameters: serde_json::Value,
+  pub supports_reply: bool,
+}
+
+/* Итог отправки команды, публикуемый в событии app-status */
+#[derive(Serialize, Clone, Debug)]
+pub struct CommandStatus {
+  pub path: String,
+  pub protocol: String,
+  pub success: bool,
+  pub attempts: u32,
+  pub elapsed_ms: u64,
+  pub error: Option<String>,
+  pub message: String,
+}
+
+/* Состояние переподключения порта для фронтенда */
+#[derive(Serialize, Clone, Debug)]
+pub struct ReconnectStatus {
+  pub path: String,
+  pub device_path: Option<String>,
+  pub attempt: u32,
 }
 /* Структуры для передачи данных на фронтенд */
 #[derive(Serialize, Clone)]
@@ -30,6 +121,103 @@ pub struct SerialErrorPayload {
   pub error: String,
 }
 
+/* Сведения об открытом порте из реестра подключений */
+#[derive(Serialize, Clone, Debug)]
+pub struct ConnectionInfo {
+  pub path: String,
+  pub protocol: String,
+  pub config: SerialConfig,
+  pub listener_ids: Vec<u32>,
+  pub opened_at: u64,
+  pub stats: ConnectionStatsSnapshot,
+}
+
+/* Статистика работы открытого порта */
+#[derive(Serialize, Clone, Debug)]
+pub struct ConnectionStatsSnapshot {
+  pub bytes_received: u64,
+  pub commands_sent: u64,
+  pub send_errors: u64,
+  pub packets_received: u64,
+  pub crc_errors: u64,
+}
+
+/* Состояние шины по байту флагов адаптера SLCAN (ответ на команду F) */
+#[derive(Serialize, Clone, Debug)]
+pub struct CanBusStatus {
+  pub path: String,
+  pub flags: u8,
+  pub rx_fifo_full: bool,
+  pub tx_fifo_full: bool,
+  pub error_warning: bool,
+  pub data_overrun: bool,
+  pub error_passive: bool,
+  pub arbitration_lost: bool,
+  pub bus_error: bool,
+  pub bus_off: bool,
+  pub timestamp: u64,
+}
+
+/* Отсчёт свободной памяти устройства */
+#[derive(Serialize, Clone, Debug)]
+pub struct HeapSample {
+  pub timestamp: u64,
+  pub free_heap: u32,
+}
+
+/* Телеметрия свободной памяти устройства на порту */
+#[derive(Serialize, Clone, Debug)]
+pub struct HeapTelemetryReport {
+  pub path: String,
+  pub current: Option<u32>,
+  pub min: Option<u32>,
+  pub max: Option<u32>,
+  pub trend_bytes_per_min: Option<f64>,
+  pub leak_suspected: bool,
+  pub sample_count: usize,
+  pub samples: Vec<HeapSample>,
+}
+
+/* Информация о доступном порте и распознанном устройстве */
+#[derive(Serialize, Clone, Debug)]
+pub struct PortInfo {
+  pub path: String,
+  pub port_type: String,
+  pub vid: Option<u16>,
+  pub pid: Option<u16>,
+  pub serial_number: Option<String>,
+  pub manufacturer: Option<String>,
+  pub product: Option<String>,
+  pub interface: Option<u8>,
+  pub device: Option<String>,
+  pub protocol: Option<String>,
+}
+
+/* Результат подключения к порту */
+#[derive(Serialize, Clone, Debug)]
+pub struct ConnectResult {
+  pub path: String,
+  pub adapter: Option<AdapterInfo>,
+}
+
+/* Сведения об адаптере SLCAN, полученные командами V, v и N при подключении */
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct AdapterInfo {
+  pub hardware_version: Option<String>,
+  pub firmware_version: Option<String>,
+  pub serial_number: Option<String>,
+  pub fd_supported: Option<bool>,
+  pub acceptance_filter_supported: Option<bool>,
+  pub warnings: Vec<String>,
+}
+
+/* Пара псевдотерминалов: порт приложения и путь второй стороны для тестового скрипта */
+#[derive(Serialize, Clone)]
+pub struct PtyPair {
+  pub path: String,
+  pub peer_path: String,
+}
+
 /* Структура для приема данных из библиотеки */
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReadDataResult {
@@ -0,0 +1,213 @@
+use serialport::SerialPortType;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter, Manager, Wry};
+
+use crate::connections::ConnectionRegistry;
+use crate::models::PortInfo;
+use crate::{log, LogLevel};
+
+/// Период опроса списка портов наблюдателем
+const WATCH_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Известное устройство, распознаваемое по USB VID/PID
+struct KnownDevice {
+  vid: u16,
+  pid: u16,
+  name: &'static str,
+  protocol: &'static str,
+}
+
+/// Таблица известных устройств и протоколов, которые для них выбираются по умолчанию
+///
+/// Адаптеры candleLight (gs_usb, 1D50:606F) не создают последовательный порт и в списке
+/// не появляются: в Linux они подключаются через socketcan://.
+const KNOWN_DEVICES: &[KnownDevice] = &[
+  // CANable / CANable 2.0 с прошивкой slcan
+  KnownDevice {
+    vid: 0x16D0,
+    pid: 0x117E,
+    name: "CANable",
+    protocol: "POECanable",
+  },
+  // Платы POE на ESP32-C3/S3 со встроенным USB Serial/JTAG; другие PID Espressif
+  // (TinyUSB CDC, платы Arduino) принадлежат прочим платам ESP32 и не распознаются
+  KnownDevice {
+    vid: 0x303A,
+    pid: 0x1001,
+    name: "POE board",
+    protocol: "POESerial",
+  },
+];
+
+/// Ищет устройство в таблице известных по USB VID/PID
+fn find_known_device(vid: u16, pid: u16) -> Option<&'static KnownDevice> {
+  KNOWN_DEVICES
+    .iter()
+    .find(|device| device.vid == vid && device.pid == pid)
+}
+
+/// Возвращает список доступных серийных портов с информацией об USB-устройствах.
+///
+/// Для распознанных адаптеров CANable и плат POE заполняются поля
+/// `device` и `protocol`, по которым интерфейс выбирает протокол автоматически.
+///
+/// # Returns
+/// * `Ok(Vec<PortInfo>)` - список портов, отсортированный по пути
+/// * `Err(String)` - ошибка получения списка портов
+#[command]
+pub fn list_ports() -> Result<Vec<PortInfo>, String> {
+  let ports = enumerate_ports()?;
+  log(LogLevel::Info, "list_ports", format!("Найдено портов: {}", ports.len()));
+  Ok(ports)
+}
+
+/// Получает список доступных портов через serialport и распознаёт известные устройства
+pub(crate) fn enumerate_ports() -> Result<Vec<PortInfo>, String> {
+  let ports = serialport::available_ports().map_err(|e| {
+    log(LogLevel::Err, "enumerate_ports", format!("Не удалось получить список портов: {}", e));
+    format!("Failed to list ports: {}", e)
+  })?;
+
+  let mut result: Vec<PortInfo> = ports
+    .into_iter()
+    .map(|port| match port.port_type {
+      SerialPortType::UsbPort(usb) => {
+        let known_device = find_known_device(usb.vid, usb.pid);
+        PortInfo {
+          path: port.port_name,
+          port_type: "usb".to_string(),
+          vid: Some(usb.vid),
+          pid: Some(usb.pid),
+          serial_number: usb.serial_number,
+          manufacturer: usb.manufacturer,
+          product: usb.product,
+          interface: usb.interface,
+          device: known_device.map(|device| device.name.to_string()),
+          protocol: known_device.map(|device| device.protocol.to_string()),
+        }
+      },
+      other => PortInfo {
+        path: port.port_name,
+        port_type: match other {
+          SerialPortType::PciPort => "pci",
+          SerialPortType::BluetoothPort => "bluetooth",
+          _ => "unknown",
+        }
+        .to_string(),
+        vid: None,
+        pid: None,
+        serial_number: None,
+        manufacturer: None,
+        product: None,
+        interface: None,
+        device: None,
+        protocol: None,
+      },
+    })
+    .collect();
+  result.sort_by(|a, b| a.path.cmp(&b.path));
+
+  Ok(result)
+}
+
+/// Запускает наблюдатель за подключением и отключением портов.
+///
+/// Наблюдатель опрашивает список портов и публикует события `port-added` и `port-removed`
+/// с информацией о порте. Если пропал открытый порт, его обработчики отключаются,
+/// а сам порт закрывается (при неудаче - принудительно). Порты с политикой
+/// переподключения не закрываются: их восстанавливает `ReconnectingTransport`.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+pub fn start_port_watcher(app: AppHandle<Wry>) {
+  thread::spawn(move || {
+    log(LogLevel::Info, "start_port_watcher", "Наблюдатель за портами запущен".to_string());
+
+    let mut known_ports: HashMap<String, PortInfo> = enumerate_ports()
+      .unwrap_or_default()
+      .into_iter()
+      .map(|port| (port.path.clone(), port))
+      .collect();
+
+    loop {
+      thread::sleep(WATCH_INTERVAL);
+
+      let current_ports: HashMap<String, PortInfo> = match enumerate_ports() {
+        Ok(ports) => ports
+          .into_iter()
+          .map(|port| (port.path.clone(), port))
+          .collect(),
+        Err(_) => continue,
+      };
+
+      for (path, port) in current_ports.iter() {
+        if !known_ports.contains_key(path) {
+          log(LogLevel::Info, "start_port_watcher", format!("Подключён порт: {}", path));
+          if let Err(e) = app.emit("port-added", port.clone()) {
+            eprintln!("Failed to emit data: {}", e);
+          }
+        }
+      }
+
+      for (path, port) in known_ports.iter() {
+        if !current_ports.contains_key(path) {
+          log(LogLevel::Warn, "start_port_watcher", format!("Отключён порт: {}", path));
+          release_removed_port(&app, path);
+          if let Err(e) = app.emit("port-removed", port.clone()) {
+            eprintln!("Failed to emit data: {}", e);
+          }
+        }
+      }
+
+      known_ports = current_ports;
+    }
+  });
+}
+
+/// Освобождает открытый порт, устройство которого было отключено
+///
+/// Порт с автоматическим переподключением (`SerialConfig.reconnect`) остаётся в реестре
+/// вместе с подписчиками: его транспорт сам обнаруживает пропажу устройства и открывает
+/// порт заново, а после исчерпания попыток удаляет подключение из реестра. Закрытие
+/// здесь остановило бы переподключение, поэтому такие порты пропускаются.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `path` - путь к пропавшему порту
+fn release_removed_port(app: &AppHandle<Wry>, path: &str) {
+  let connections = app.state::<ConnectionRegistry>();
+  let Some(transport) = connections.find_transport(path) else {
+    return;
+  };
+  if transport.auto_reconnect() {
+    log(
+      LogLevel::Info,
+      "release_removed_port",
+      format!("Порт {} будет переподключён автоматически", path),
+    );
+    return;
+  }
+
+  log(LogLevel::Warn, "release_removed_port", format!("Закрытие отключённого порта: {}", path));
+
+  connections.close(path);
+
+  if let Err(e) = app.emit("app-status", format!("Port {} was disconnected", path)) {
+    eprintln!("Failed to emit data: {}", e);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn known_devices_match_exact_vid_and_pid() {
+    assert_eq!(find_known_device(0x16D0, 0x117E).unwrap().protocol, "POECanable");
+    assert_eq!(find_known_device(0x303A, 0x1001).unwrap().protocol, "POESerial");
+    assert!(find_known_device(0x303A, 0x0002).is_none());
+    assert!(find_known_device(0x1D50, 0x606F).is_none());
+  }
+}
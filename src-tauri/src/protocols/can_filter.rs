@@ -0,0 +1,177 @@
+use crate::models::{CanFilter, CanFilterField};
+use crate::{log, LogLevel};
+
+/// Поля расширенного ID POE, к которым применяются фильтры
+const FILTER_FIELDS: [CanFilterField; 3] = [CanFilterField::TargetId, CanFilterField::ReturnId, CanFilterField::ArgumentCode];
+
+/// Сдвиг и маска поля в расширенном ID
+fn field_layout(field: CanFilterField) -> (u32, u32) {
+  match field {
+    CanFilterField::TargetId => (8, 0xff),
+    CanFilterField::ReturnId => (0, 0xff),
+    CanFilterField::ArgumentCode => (16, 0x3ff),
+  }
+}
+
+/// Сравниваемые биты поля: маска фильтра, ограниченная шириной поля
+fn filter_mask(filter: &CanFilter) -> u32 {
+  let (_, width) = field_layout(filter.field);
+  filter.mask.unwrap_or(width) & width
+}
+
+/// Проверяет поле ID фрейма по одному фильтру
+fn filter_matches(filter: &CanFilter, can_id: u32) -> bool {
+  let (shift, _) = field_layout(filter.field);
+  let mask = filter_mask(filter);
+  (can_id >> shift) & mask == filter.id & mask
+}
+
+/// Проверяет, принимается ли фрейм фильтрами порта
+///
+/// Фильтры одного поля объединяются по ИЛИ, разные поля - по И. Поля POE есть только
+/// в расширенном ID, поэтому при заданных фильтрах фреймы со стандартным ID отбрасываются.
+///
+/// # Arguments
+/// * `filters` - фильтры из конфигурации порта
+/// * `can_id` - CAN ID фрейма
+/// * `is_extended` - фрейм с расширенным ID
+///
+/// # Returns
+/// * `true` - фильтров нет или фрейм им соответствует
+pub fn can_frame_accepted(filters: &[CanFilter], can_id: u32, is_extended: bool) -> bool {
+  if filters.is_empty() {
+    return true;
+  }
+  if !is_extended {
+    return false;
+  }
+
+  FILTER_FIELDS.iter().all(|field| {
+    let mut field_filters = filters
+      .iter()
+      .filter(|filter| filter.field == *field)
+      .peekable();
+    field_filters.peek().is_none() || field_filters.any(|filter| filter_matches(filter, can_id))
+  })
+}
+
+/// Формирует команды фильтра приёма адаптера SLCAN: `M` - код, `m` - маска приёма
+///
+/// Адаптер поддерживает одну пару код/маска (фильтр SJA1000 для расширенного ID: биты
+/// ID28..ID0 сдвинуты на 3, бит маски 1 - бит не сравнивается), поэтому на адаптер
+/// переносятся только фильтры, в которых каждое поле задано не более одного раза.
+/// Остальные фильтры применяются только программно.
+///
+/// Команды имеют такой смысл только в прошивках Lawicel: в прошивках CANable `M`/`m` задают
+/// режим работы, поэтому команды отправляются только после проверки прошивки адаптера
+/// (`apply_adapter_filter`).
+///
+/// # Arguments
+/// * `filters` - фильтры из конфигурации порта
+///
+/// # Returns
+/// * `Some(Vec<Vec<u8>>)` - команды `M` и `m`
+/// * `None` - фильтров нет или их нельзя выразить одной парой код/маска
+pub fn adapter_filter_commands(filters: &[CanFilter]) -> Option<Vec<Vec<u8>>> {
+  if filters.is_empty() {
+    return None;
+  }
+
+  let mut code = 0u32;
+  let mut care = 0u32;
+  for field in FILTER_FIELDS {
+    let mut field_filters = filters.iter().filter(|filter| filter.field == field);
+    let Some(filter) = field_filters.next() else {
+      continue;
+    };
+    if field_filters.next().is_some() {
+      log(
+        LogLevel::Warn,
+        "adapter_filter_commands",
+        format!("Несколько фильтров поля {:?}, фильтр адаптера не используется", field),
+      );
+      return None;
+    }
+    let (shift, _) = field_layout(field);
+    let mask = filter_mask(filter);
+    code |= (filter.id & mask) << shift;
+    care |= mask << shift;
+  }
+
+  let acceptance_code = code << 3;
+  let acceptance_mask = !(care << 3);
+  log(
+    LogLevel::Info,
+    "adapter_filter_commands",
+    format!("Фильтр адаптера: код {:08X}, маска {:08X}", acceptance_code, acceptance_mask),
+  );
+  Some(vec![
+    format!("M{:08X}\r", acceptance_code).into_bytes(),
+    format!("m{:08X}\r", acceptance_mask).into_bytes(),
+  ])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn filter(field: CanFilterField, id: u32, mask: Option<u32>) -> CanFilter {
+    CanFilter { field, id, mask }
+  }
+
+  /// Расширенный ID POE из кода аргумента, адресата и адреса ответа
+  fn poe_id(argument_code: u32, target_id: u32, return_id: u32) -> u32 {
+    (argument_code << 16) | (target_id << 8) | return_id
+  }
+
+  #[test]
+  fn no_filters_accept_every_frame() {
+    assert!(can_frame_accepted(&[], 0x123, false));
+    assert!(can_frame_accepted(&[], poe_id(5, 1, 2), true));
+  }
+
+  #[test]
+  fn standard_frames_are_rejected_when_filters_are_set() {
+    let filters = [filter(CanFilterField::TargetId, 0x23, None)];
+    assert!(!can_frame_accepted(&filters, 0x123, false));
+  }
+
+  #[test]
+  fn filters_of_one_field_are_ored_and_fields_are_anded() {
+    let filters = [
+      filter(CanFilterField::TargetId, 1, None),
+      filter(CanFilterField::TargetId, 2, None),
+      filter(CanFilterField::ArgumentCode, 0x3FF, None),
+    ];
+    assert!(can_frame_accepted(&filters, poe_id(0x3FF, 1, 9), true));
+    assert!(can_frame_accepted(&filters, poe_id(0x3FF, 2, 9), true));
+    assert!(!can_frame_accepted(&filters, poe_id(0x3FF, 3, 9), true));
+    assert!(!can_frame_accepted(&filters, poe_id(0x3FE, 1, 9), true));
+  }
+
+  #[test]
+  fn mask_limits_compared_bits() {
+    let filters = [filter(CanFilterField::ReturnId, 0x10, Some(0xF0))];
+    assert!(can_frame_accepted(&filters, poe_id(0, 0, 0x1F), true));
+    assert!(!can_frame_accepted(&filters, poe_id(0, 0, 0x2F), true));
+
+    // Биты маски за пределами поля не сравниваются
+    let filters = [filter(CanFilterField::ReturnId, 0x1FF, Some(0xFFFF))];
+    assert!(can_frame_accepted(&filters, poe_id(0, 0, 0xFF), true));
+  }
+
+  #[test]
+  fn adapter_commands_combine_one_filter_per_field() {
+    let filters = [filter(CanFilterField::TargetId, 0x12, None), filter(CanFilterField::ReturnId, 0x30, Some(0xF0))];
+    let code = ((0x12 << 8) | 0x30) << 3;
+    let mask = !(((0xFF << 8) | 0xF0) << 3);
+    assert_eq!(
+      adapter_filter_commands(&filters),
+      Some(vec![format!("M{:08X}\r", code).into_bytes(), format!("m{:08X}\r", mask as u32).into_bytes()])
+    );
+
+    let filters = [filter(CanFilterField::TargetId, 1, None), filter(CanFilterField::TargetId, 2, None)];
+    assert_eq!(adapter_filter_commands(&filters), None);
+    assert_eq!(adapter_filter_commands(&[]), None);
+  }
+}
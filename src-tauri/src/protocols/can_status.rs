@@ -0,0 +1,283 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::codec::init_protocol;
+use crate::connections::ConnectionRegistry;
+use crate::models::{CanBusStatus, SerialConfig};
+use crate::slcan_adapter::apply_adapter_filter;
+use crate::transports::Transport;
+use crate::{log, LogLevel};
+
+/// Период опроса состояния адаптера по умолчанию, мс
+const DEFAULT_STATUS_INTERVAL_MS: u64 = 1000;
+
+/// Минимальный период опроса состояния адаптера, мс
+const MIN_STATUS_INTERVAL_MS: u64 = 100;
+
+/// Минимальный интервал между повторными открытиями канала после bus-off
+const BUS_OFF_REOPEN_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// Число опросов подряд с признаком bus-off, после которого канал открывается заново
+const BUS_OFF_CONFIRM_POLLS: u32 = 3;
+
+/// Биты байта состояния SLCAN (ответ `Fxx`)
+const FLAG_RX_FIFO_FULL: u8 = 0x01;
+const FLAG_TX_FIFO_FULL: u8 = 0x02;
+const FLAG_ERROR_WARNING: u8 = 0x04;
+const FLAG_DATA_OVERRUN: u8 = 0x08;
+const FLAG_ERROR_PASSIVE: u8 = 0x20;
+const FLAG_ARBITRATION_LOST: u8 = 0x40;
+const FLAG_BUS_ERROR: u8 = 0x80;
+
+/// Разбирает байт состояния адаптера
+///
+/// Отдельного бита bus-off в SLCAN нет: контроллер уходит в bus-off из состояния
+/// error passive, поэтому признак bus-off выставляется при одновременных флагах
+/// error passive и bus error. Такое сочетание бывает и при кратковременной ошибке,
+/// поэтому опрос считает bus-off подтверждённым, только если признак держится
+/// несколько ответов подряд.
+///
+/// # Arguments
+/// * `path` - путь к порту
+/// * `flags` - байт состояния из ответа `Fxx`
+pub fn decode_can_status(path: &str, flags: u8) -> CanBusStatus {
+  let error_passive = flags & FLAG_ERROR_PASSIVE != 0;
+  let bus_error = flags & FLAG_BUS_ERROR != 0;
+  CanBusStatus {
+    path: path.to_string(),
+    flags,
+    rx_fifo_full: flags & FLAG_RX_FIFO_FULL != 0,
+    tx_fifo_full: flags & FLAG_TX_FIFO_FULL != 0,
+    error_warning: flags & FLAG_ERROR_WARNING != 0,
+    data_overrun: flags & FLAG_DATA_OVERRUN != 0,
+    error_passive,
+    arbitration_lost: flags & FLAG_ARBITRATION_LOST != 0,
+    bus_error,
+    bus_off: error_passive && bus_error,
+    timestamp: chrono::Local::now().timestamp_millis() as u64,
+  }
+}
+
+/// Выделяет байты состояния из ответов `Fxx` в потоке адаптера
+///
+/// Ответы отделяются символами '\r' и BEL, неполная строка хранится до следующего чтения.
+#[derive(Default)]
+struct StatusReplyParser {
+  line: String,
+}
+
+impl StatusReplyParser {
+  fn push(&mut self, data: &[u8]) -> Vec<u8> {
+    let mut replies = Vec::new();
+    for &byte in data {
+      match byte {
+        b'\r' | 0x07 => {
+          let line = std::mem::take(&mut self.line);
+          if line.len() == 3 && line.starts_with('F') {
+            if let Ok(flags) = u8::from_str_radix(&line[1..], 16) {
+              replies.push(flags);
+            }
+          }
+        },
+        // Строки длиннее ответа - фреймы, их содержимое не нужно
+        _ if self.line.len() <= 3 => self.line.push(byte as char),
+        _ => {},
+      }
+    }
+    replies
+  }
+}
+
+/// Запускает периодический опрос состояния адаптера командой `F` для порта CANable
+///
+/// Изменения состояния публикуются событием `can-bus-status`. Если признак bus-off держится
+/// `BUS_OFF_CONFIRM_POLLS` ответов подряд, канал адаптера открывается заново
+/// последовательностью инициализации протокола.
+/// Опрос завершается вместе с закрытием порта.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `transport` - транспорт открытого порта
+/// * `config` - конфигурация подключения, `can_status_interval_ms` = 0 выключает опрос
+pub fn start_can_status_polling(app: AppHandle<Wry>, transport: Arc<dyn Transport>, config: SerialConfig) {
+  let interval_ms = config
+    .can_status_interval_ms
+    .unwrap_or(DEFAULT_STATUS_INTERVAL_MS);
+  if interval_ms == 0 {
+    log(
+      LogLevel::Info,
+      "start_can_status_polling",
+      format!("Опрос состояния адаптера на порту {} выключен", config.path),
+    );
+    return;
+  }
+  let interval = Duration::from_millis(interval_ms.max(MIN_STATUS_INTERVAL_MS));
+
+  let latest: Arc<Mutex<Option<u8>>> = Arc::new(Mutex::new(None));
+  let parser = Mutex::new(StatusReplyParser::default());
+  let latest_clone = latest.clone();
+  let listener_id = transport.subscribe(Box::new(move |data| {
+    if let Some(flags) = parser.lock().unwrap().push(&data).pop() {
+      *latest_clone.lock().unwrap() = Some(flags);
+    }
+  }));
+
+  log(
+    LogLevel::Info,
+    "start_can_status_polling",
+    format!("Опрос состояния адаптера на порту {} каждые {} мс", config.path, interval.as_millis()),
+  );
+
+  thread::spawn(move || {
+    let path = transport.path().to_string();
+    let mut last_flags: Option<u8> = None;
+    let mut last_reopen: Option<Instant> = None;
+    let mut bus_off_polls: u32 = 0;
+    let unsupported = Arc::new(AtomicBool::new(false));
+
+    loop {
+      thread::sleep(interval);
+
+      // Поток завершается вместе с закрытием порта, в том числе если порт открыт заново
+      let current = app.state::<ConnectionRegistry>().find_transport(&path);
+      if !current.is_some_and(|current| Arc::ptr_eq(&current, &transport)) {
+        break;
+      }
+
+      if let Some(flags) = latest.lock().unwrap().take() {
+        let status = decode_can_status(&path, flags);
+        bus_off_polls = if status.bus_off { bus_off_polls + 1 } else { 0 };
+
+        if last_flags != Some(flags) {
+          last_flags = Some(flags);
+          if flags != 0 {
+            log(
+              LogLevel::Warn,
+              "can_status_polling",
+              format!("Состояние шины на порту {}: флаги 0x{:02X}", path, flags),
+            );
+          }
+          if let Err(e) = app.emit("can-bus-status", status) {
+            eprintln!("Failed to emit data: {}", e);
+          }
+        }
+
+        if bus_off_polls >= BUS_OFF_CONFIRM_POLLS && last_reopen.is_none_or(|reopened| reopened.elapsed() >= BUS_OFF_REOPEN_COOLDOWN) {
+          last_reopen = Some(Instant::now());
+          bus_off_polls = 0;
+          // После переоткрытия состояние публикуется заново по следующему ответу
+          last_flags = None;
+          reopen_after_bus_off(&app, transport.as_ref(), &config);
+        }
+      }
+
+      // Прошивки без команды F отвечают на неё BEL, опрос таких адаптеров прекращается
+      if unsupported.load(Ordering::SeqCst) {
+        log(
+          LogLevel::Warn,
+          "can_status_polling",
+          format!("Адаптер на порту {} не поддерживает запрос состояния", path),
+        );
+        break;
+      }
+      let written = match app.state::<ConnectionRegistry>().slcan_acks(&path) {
+        Some(acks) => {
+          let unsupported = unsupported.clone();
+          acks
+            .write_with_callback(
+              transport.as_ref(),
+              b"F\r",
+              Box::new(move |result| {
+                if result.is_err() {
+                  unsupported.store(true, Ordering::SeqCst);
+                }
+              }),
+            )
+            .map(|_| ())
+        },
+        None => transport.write(b"F\r").map(|_| ()),
+      };
+      if let Err(e) = written {
+        log(
+          LogLevel::Info,
+          "can_status_polling",
+          format!("Запрос состояния адаптера на порту {} не отправлен: {}", path, e),
+        );
+      }
+    }
+
+    transport.unsubscribe(listener_id);
+    log(
+      LogLevel::Info,
+      "can_status_polling",
+      format!("Опрос состояния адаптера на порту {} завершён", path),
+    );
+  });
+}
+
+/// Закрывает и заново открывает канал адаптера после перехода контроллера в bus-off
+fn reopen_after_bus_off(app: &AppHandle<Wry>, transport: &dyn Transport, config: &SerialConfig) {
+  log(
+    LogLevel::Warn,
+    "reopen_after_bus_off",
+    format!(
+      "Шина на порту {} в состоянии bus-off {} опросов подряд, канал адаптера открывается заново",
+      config.path, BUS_OFF_CONFIRM_POLLS
+    ),
+  );
+
+  // Повторное открытие возвращает адаптер в состояние без фильтра приёма, фильтр задаётся заново
+  let reopened = init_protocol(app, transport, config).and_then(|_| apply_adapter_filter(app, transport, config));
+  let message = match reopened {
+    Ok(_) => format!("CAN bus-off on {}, adapter channel reopened", config.path),
+    Err(e) => {
+      log(
+        LogLevel::Err,
+        "reopen_after_bus_off",
+        format!("Не удалось открыть канал адаптера на порту {}: {}", config.path, e),
+      );
+      format!("CAN bus-off on {}, failed to reopen adapter channel: {}", config.path, e)
+    },
+  };
+  if let Err(e) = app.emit("app-status", message) {
+    eprintln!("Failed to emit data: {}", e);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn decodes_each_flag() {
+    let status = decode_can_status("test", FLAG_RX_FIFO_FULL | FLAG_ERROR_WARNING | FLAG_ARBITRATION_LOST);
+    assert_eq!(status.path, "test");
+    assert!(status.rx_fifo_full && status.error_warning && status.arbitration_lost);
+    assert!(!status.tx_fifo_full && !status.data_overrun && !status.error_passive && !status.bus_error);
+    assert!(!status.bus_off);
+
+    let status = decode_can_status("test", FLAG_TX_FIFO_FULL | FLAG_DATA_OVERRUN);
+    assert!(status.tx_fifo_full && status.data_overrun);
+    assert!(!status.rx_fifo_full && !status.error_warning);
+  }
+
+  #[test]
+  fn bus_off_needs_error_passive_and_bus_error() {
+    assert!(!decode_can_status("test", FLAG_ERROR_PASSIVE).bus_off);
+    assert!(!decode_can_status("test", FLAG_BUS_ERROR).bus_off);
+    assert!(decode_can_status("test", FLAG_ERROR_PASSIVE | FLAG_BUS_ERROR).bus_off);
+    assert!(decode_can_status("test", 0xFF).bus_off);
+  }
+
+  #[test]
+  fn parser_extracts_status_replies_between_frames() {
+    let mut parser = StatusReplyParser::default();
+    assert_eq!(parser.push(b"F0"), Vec::<u8>::new());
+    assert_eq!(parser.push(b"4\r"), vec![0x04]);
+    assert_eq!(parser.push(b"T0000012320102\rF00\r\x07FA0\r"), vec![0x00, 0xA0]);
+    assert_eq!(parser.push(b"FZZ\rF1\r"), Vec::<u8>::new());
+  }
+}
@@ -0,0 +1,326 @@
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tauri::ipc::Channel;
+use tauri::{command, AppHandle, Emitter, Manager, State, Wry};
+
+use crate::command_retry::AttemptError;
+use crate::connections::ConnectionRegistry;
+use crate::models::{ProtocolInfo, SerialConfig};
+use crate::poe_canable::PoeCanableCodec;
+use crate::poe_serial::PoeSerialCodec;
+use crate::simple_serial::SimpleSerialCodec;
+use crate::slcan_ack::AckCallback;
+use crate::transports::Transport;
+use crate::{log, LogLevel};
+
+/// Декодер потока байт порта, хранит незавершённые данные между вызовами
+pub trait ProtocolDecoder: Send {
+  /// Обрабатывает принятые байты и возвращает полностью разобранные кадры
+  fn decode(&mut self, data: &[u8]) -> Vec<serde_json::Value>;
+}
+
+/// Протокол обмена с устройством
+///
+/// Чтобы добавить протокол, достаточно реализовать этот трейт и зарегистрировать тип
+/// в `ProtocolRegistry`: подключение, отправка команд и закрытие порта находят протокол
+/// по имени из конфигурации.
+pub trait ProtocolCodec: Send + Sync {
+  /// Имя протокола, совпадает с `SerialConfig.protocol`
+  fn name(&self) -> &str;
+
+  /// Описание протокола для интерфейса
+  fn description(&self) -> &str;
+
+  /// JSON Schema объекта команды, который принимает `process_data_sending`
+  fn parameters(&self) -> serde_json::Value;
+
+  /// Кодирует команду в последовательность посылок для записи в порт
+  fn encode(&self, config: &SerialConfig, command: serde_json::Value) -> Result<Vec<Vec<u8>>, String>;
+
+  /// Создаёт декодер потока для порта
+  ///
+  /// Дескриптор приложения нужен декодерам, которые ведут счётчики и телеметрию порта.
+  fn decoder(&self, app: &AppHandle<Wry>, config: &SerialConfig) -> Box<dyn ProtocolDecoder>;
+
+  /// Сбрасывает состояние разбора, которое протокол хранит для порта вне декодера
+  ///
+  /// Вызывается реестром подключений при регистрации и удалении порта и при переподключении.
+  fn reset_port(&self, _port_path: &str) {}
+
+  /// Посылки, отправляемые после открытия порта (и после переподключения)
+  fn init_sequence(&self, _config: &SerialConfig) -> Result<Vec<Vec<u8>>, String> {
+    Ok(Vec::new())
+  }
+
+  /// Посылки, отправляемые перед закрытием порта
+  fn teardown_sequence(&self, _config: &SerialConfig) -> Vec<Vec<u8>> {
+    Vec::new()
+  }
+
+  /// Отправляет команду без ожидания ответа
+  fn send(&self, _app: &AppHandle<Wry>, transport: &dyn Transport, config: &SerialConfig, command: serde_json::Value) -> Result<(), String> {
+    for packet in self.encode(config, command)? {
+      transport
+        .write(&packet)
+        .map_err(|e| format!("Failed to write: {}", e))?;
+    }
+    Ok(())
+  }
+
+  /// Отправляет команду и ждёт ответ устройства; протоколы без ответов только отправляют её
+  fn send_awaiting_reply(
+    &self,
+    app: &AppHandle<Wry>,
+    transport: &dyn Transport,
+    config: &SerialConfig,
+    command: serde_json::Value,
+    _timeout: Duration,
+  ) -> Result<(), AttemptError> {
+    self
+      .send(app, transport, config, command)
+      .map_err(AttemptError::Fail)
+  }
+
+  /// Протокол подтверждает команды ответом, к нему применима политика повторов
+  fn supports_reply(&self) -> bool {
+    false
+  }
+}
+
+/// Реестр протоколов, доступных для подключения
+pub struct ProtocolRegistry {
+  codecs: RwLock<Vec<Arc<dyn ProtocolCodec>>>,
+}
+
+impl Default for ProtocolRegistry {
+  /// Создаёт реестр со встроенными протоколами
+  fn default() -> Self {
+    let registry = Self {
+      codecs: RwLock::new(Vec::new()),
+    };
+    registry.register(Arc::new(SimpleSerialCodec));
+    registry.register(Arc::new(PoeSerialCodec));
+    registry.register(Arc::new(PoeCanableCodec::can()));
+    registry.register(Arc::new(PoeCanableCodec::can_fd()));
+    registry
+  }
+}
+
+impl ProtocolRegistry {
+  /// Регистрирует протокол; протокол с тем же именем заменяется
+  pub fn register(&self, codec: Arc<dyn ProtocolCodec>) {
+    let mut codecs = self.codecs.write().unwrap();
+    codecs.retain(|existing| existing.name() != codec.name());
+    log(LogLevel::Info, "ProtocolRegistry", format!("Зарегистрирован протокол {}", codec.name()));
+    codecs.push(codec);
+  }
+
+  /// Ищет протокол по имени
+  pub fn find(&self, name: &str) -> Option<Arc<dyn ProtocolCodec>> {
+    self
+      .codecs
+      .read()
+      .unwrap()
+      .iter()
+      .find(|codec| codec.name() == name)
+      .cloned()
+  }
+
+  /// Возвращает протокол по имени
+  ///
+  /// # Returns
+  /// * `Ok(Arc<dyn ProtocolCodec>)` - найденный протокол
+  /// * `Err(String)` - протокол не зарегистрирован
+  pub fn get(&self, name: &str) -> Result<Arc<dyn ProtocolCodec>, String> {
+    self.find(name).ok_or_else(|| {
+      log(LogLevel::Err, "ProtocolRegistry", format!("Неизвестный протокол: {}", name));
+      format!("Unknown protocol: {}", name)
+    })
+  }
+
+  /// Описания всех зарегистрированных протоколов
+  pub fn list(&self) -> Vec<ProtocolInfo> {
+    self
+      .codecs
+      .read()
+      .unwrap()
+      .iter()
+      .map(|codec| ProtocolInfo {
+        name: codec.name().to_string(),
+        description: codec.description().to_string(),
+        parameters: codec.parameters(),
+        supports_reply: codec.supports_reply(),
+      })
+      .collect()
+  }
+}
+
+/// Записывает в порт последовательность посылок протокола
+///
+/// На портах адаптеров SLCAN подтверждения команд приходят после начала чтения, поэтому
+/// отклонённая адаптером команда учитывается как ошибка отправки и публикуется в `app-status`.
+pub(crate) fn write_sequence(
+  app: &AppHandle<Wry>,
+  transport: &dyn Transport,
+  config: &SerialConfig,
+  sequence: Vec<Vec<u8>>,
+  label: &'static str,
+) -> Result<(), String> {
+  let acks = app.state::<ConnectionRegistry>().slcan_acks(&config.path);
+  for packet in sequence {
+    let result = match &acks {
+      Some(acks) => acks
+        .write_with_callback(transport, &packet, report_rejected_command(app.clone(), config.path.clone(), label))
+        .map(|_| ()),
+      None => transport.write(&packet).map(|_| ()),
+    };
+    result.map_err(|e| {
+      log(
+        LogLevel::Err,
+        label,
+        format!("Не удалось отправить {}: {}", String::from_utf8_lossy(&packet).trim_end(), e),
+      );
+      e
+    })?;
+  }
+  Ok(())
+}
+
+/// Обработчик подтверждения служебной команды адаптера: отклонённая команда - ошибка отправки
+fn report_rejected_command(app: AppHandle<Wry>, path: String, label: &'static str) -> AckCallback {
+  Box::new(move |result| {
+    let Err(reason) = result else {
+      return;
+    };
+    log(LogLevel::Err, label, format!("{} на порту {}", reason, path));
+    app.state::<ConnectionRegistry>().record_send(&path, false);
+    if let Err(e) = app.emit("app-status", format!("{} on {}", reason, path)) {
+      eprintln!("Failed to emit data: {}", e);
+    }
+  })
+}
+
+/// Отправляет последовательность инициализации протокола из конфигурации порта
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `transport` - транспорт открытого порта
+/// * `config` - конфигурация подключения
+///
+/// # Returns
+/// * `Ok(())` - последовательность отправлена или не требуется
+/// * `Err(String)` - протокол не зарегистрирован или ошибка записи в порт
+pub fn init_protocol(app: &AppHandle<Wry>, transport: &dyn Transport, config: &SerialConfig) -> Result<(), String> {
+  let codec = app.state::<ProtocolRegistry>().get(&config.protocol)?;
+  log(
+    LogLevel::Info,
+    "init_protocol",
+    format!("Инициализация протокола {} на порту {}", codec.name(), transport.path()),
+  );
+  write_sequence(app, transport, config, codec.init_sequence(config)?, "init_protocol")
+}
+
+/// Отправляет последовательность завершения протокола перед закрытием порта
+///
+/// Устройство могло быть отключено физически, поэтому ошибки записи только логируются.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `transport` - транспорт открытого порта
+/// * `config` - конфигурация подключения
+pub fn teardown_protocol(app: &AppHandle<Wry>, transport: &dyn Transport, config: &SerialConfig) {
+  let Some(codec) = app.state::<ProtocolRegistry>().find(&config.protocol) else {
+    return;
+  };
+  if let Err(e) = write_sequence(app, transport, config, codec.teardown_sequence(config), "teardown_protocol") {
+    log(
+      LogLevel::Warn,
+      "teardown_protocol",
+      format!("Последовательность завершения протокола {} не отправлена: {}", codec.name(), e),
+    );
+  }
+}
+
+/// Возвращает список протоколов с JSON Schema параметров их команд.
+///
+/// # Returns
+/// * `Vec<ProtocolInfo>` - зарегистрированные протоколы
+#[command]
+pub fn list_protocols(protocols: State<'_, ProtocolRegistry>) -> Vec<ProtocolInfo> {
+  protocols.list()
+}
+
+/// Подписывает декодер протокола порта на принятые данные
+///
+/// Все команды чтения порта разбирают данные через реестр протоколов, поэтому декодирование
+/// и учёт счётчиков порта не дублируются. Протоколы POECanable хранят незавершённые пакеты
+/// по пути порта, поэтому на одном порту должен быть подписан только один декодер.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `port_path` - путь к открытому порту
+/// * `expected` - допустимые протоколы порта, пустой список - любой протокол
+/// * `label` - имя команды для логирования
+/// * `on_frames` - обработчик разобранных кадров, вызывается только для непустых пакетов кадров
+///
+/// # Returns
+/// * `Ok(u32)` - ID события прослушивания
+/// * `Err(String)` - порт не открыт, протокол не зарегистрирован или не совпадает с ожидаемым
+pub(crate) fn subscribe_decoder<F>(app: &AppHandle<Wry>, port_path: &str, expected: &[&str], label: &'static str, on_frames: F) -> Result<u32, String>
+where
+  F: Fn(Vec<serde_json::Value>) + Send + Sync + 'static,
+{
+  let connections = app.state::<ConnectionRegistry>();
+  let transport = connections.get_transport(port_path)?;
+  let config = connections
+    .get_config(port_path)
+    .ok_or_else(|| format!("Port {} is not open", port_path))?;
+  if !expected.is_empty() && !expected.contains(&config.protocol.as_str()) {
+    log(
+      LogLevel::Err,
+      label,
+      format!("Порт {} использует протокол {}, ожидался {:?}", port_path, config.protocol, expected),
+    );
+    return Err(format!("Port {} uses protocol {}", port_path, config.protocol));
+  }
+  let codec = app.state::<ProtocolRegistry>().get(&config.protocol)?;
+
+  let decoder = Mutex::new(codec.decoder(app, &config));
+  let event_id = transport.subscribe(Box::new(move |data| {
+    let frames = decoder.lock().unwrap().decode(&data);
+    if !frames.is_empty() {
+      on_frames(frames);
+    }
+  }));
+
+  connections.add_listener(transport.path(), event_id);
+  log(
+    LogLevel::Info,
+    label,
+    format!("Декодер протокола {} подписан на порт {}", codec.name(), port_path),
+  );
+
+  Ok(event_id)
+}
+
+/// Подписывает декодер протокола порта на принятые данные и отправляет кадры через канал.
+///
+/// Кадры передаются в виде JSON, поэтому команда подходит для любого зарегистрированного
+/// протокола.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `port_path` - путь к открытому порту
+/// * `on_event` - канал для отправки разобранных кадров
+///
+/// # Returns
+/// * `Ok(u32)` - ID события прослушивания
+/// * `Err(String)` - порт не открыт или протокол не зарегистрирован
+#[command]
+pub fn process_protocol(app: AppHandle<Wry>, port_path: String, on_event: Channel<Vec<serde_json::Value>>) -> Result<u32, String> {
+  subscribe_decoder(&app, &port_path, &[], "process_protocol", move |frames| {
+    if let Err(e) = on_event.send(frames) {
+      log(LogLevel::Err, "process_protocol", format!("Ошибка отправки через канал: {}", e));
+    }
+  })
+}
@@ -0,0 +1,156 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::models::CommandRetryPolicy;
+use crate::{log, LogLevel};
+
+/// Ошибка одной попытки отправки команды
+pub enum AttemptError {
+  /// Ответ не пришёл или пришёл с неверной CRC, команду можно отправить повторно
+  Retry(String),
+  /// Повтор не поможет (ошибка записи, ответ ER!, некорректная команда)
+  Fail(String),
+}
+
+/// Итог отправки команды с повторами
+pub(crate) struct RetryOutcome {
+  pub(crate) attempts: u32,
+  pub(crate) result: Result<(), String>,
+}
+
+/// Выполняет попытки отправки команды согласно политике повторов
+///
+/// Пауза между попытками начинается с `backoff_ms` и удваивается после каждой неудачи.
+///
+/// # Arguments
+/// * `policy` - политика повторов порта
+/// * `port_path` - путь к порту (для логирования)
+/// * `attempt` - попытка отправки, принимает время ожидания ответа
+///
+/// # Returns
+/// * `RetryOutcome` - число выполненных попыток и результат последней
+pub(crate) fn run_with_retry<F>(policy: &CommandRetryPolicy, port_path: &str, mut attempt: F) -> RetryOutcome
+where
+  F: FnMut(Duration) -> Result<(), AttemptError>,
+{
+  let attempts = policy.attempts.max(1);
+  let timeout = Duration::from_millis(policy.timeout_ms);
+  let mut backoff = policy.backoff_ms;
+  let mut last_error = String::new();
+
+  for number in 1..=attempts {
+    match attempt(timeout) {
+      Ok(()) => {
+        return RetryOutcome {
+          attempts: number,
+          result: Ok(()),
+        }
+      },
+      Err(AttemptError::Fail(e)) => {
+        return RetryOutcome {
+          attempts: number,
+          result: Err(e),
+        }
+      },
+      Err(AttemptError::Retry(e)) => {
+        log(
+          LogLevel::Warn,
+          "run_with_retry",
+          format!("Попытка {} из {} на порту {} не удалась: {}", number, attempts, port_path, e),
+        );
+        last_error = e;
+      },
+    }
+
+    if number < attempts {
+      thread::sleep(Duration::from_millis(backoff));
+      backoff = backoff.saturating_mul(2);
+    }
+  }
+
+  RetryOutcome {
+    attempts,
+    result: Err(last_error),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Instant;
+
+  fn policy(attempts: u32, backoff_ms: u64) -> CommandRetryPolicy {
+    CommandRetryPolicy {
+      attempts,
+      timeout_ms: 100,
+      backoff_ms,
+    }
+  }
+
+  #[test]
+  fn stops_after_first_success() {
+    let mut calls = 0;
+    let outcome = run_with_retry(&policy(3, 0), "test", |timeout| {
+      assert_eq!(timeout, Duration::from_millis(100));
+      calls += 1;
+      if calls < 2 {
+        Err(AttemptError::Retry("timeout".to_string()))
+      } else {
+        Ok(())
+      }
+    });
+
+    assert_eq!(calls, 2);
+    assert_eq!(outcome.attempts, 2);
+    assert!(outcome.result.is_ok());
+  }
+
+  #[test]
+  fn fail_is_not_retried() {
+    let mut calls = 0;
+    let outcome = run_with_retry(&policy(3, 0), "test", |_| {
+      calls += 1;
+      Err(AttemptError::Fail("ER!".to_string()))
+    });
+
+    assert_eq!(calls, 1);
+    assert_eq!(outcome.attempts, 1);
+    assert_eq!(outcome.result, Err("ER!".to_string()));
+  }
+
+  #[test]
+  fn returns_last_error_when_attempts_run_out() {
+    let mut calls = 0;
+    let outcome = run_with_retry(&policy(3, 0), "test", |_| {
+      calls += 1;
+      Err(AttemptError::Retry(format!("timeout {}", calls)))
+    });
+
+    assert_eq!(outcome.attempts, 3);
+    assert_eq!(outcome.result, Err("timeout 3".to_string()));
+  }
+
+  #[test]
+  fn zero_attempts_still_sends_once() {
+    let mut calls = 0;
+    let outcome = run_with_retry(&policy(0, 0), "test", |_| {
+      calls += 1;
+      Err(AttemptError::Retry("timeout".to_string()))
+    });
+
+    assert_eq!(calls, 1);
+    assert_eq!(outcome.attempts, 1);
+  }
+
+  #[test]
+  fn backoff_doubles_between_attempts() {
+    let started = Instant::now();
+    let outcome = run_with_retry(&policy(3, 20), "test", |_| Err(AttemptError::Retry("timeout".to_string())));
+
+    // 20 мс после первой попытки и 40 мс после второй, после последней пауза не нужна
+    assert_eq!(outcome.attempts, 3);
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(60), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(1000), "{:?}", elapsed);
+  }
+}
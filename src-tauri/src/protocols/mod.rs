@@ -1,3 +1,11 @@
+pub mod can_filter;
+pub mod can_status;
+pub mod codec;
+pub mod command_retry;
 pub mod poe_canable;
 pub mod poe_serial;
+pub mod poe_serial_framer;
+pub mod poe_serial_transaction;
 pub mod simple_serial;
+pub mod slcan_ack;
+pub mod slcan_adapter;
@@ -1,14 +1,25 @@
-use crate::{log, LogLevel, ReadDataResult};
+use crate::{log, LogLevel};
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
+use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::ipc::Channel;
-use tauri::{command, AppHandle, Emitter, Listener, State, Wry};
-use tauri_plugin_serialplugin::commands::write;
-use tauri_plugin_serialplugin::desktop_api::SerialPort;
+use tauri::{command, AppHandle, Emitter, Manager, Wry};
+
+use crate::can_filter::can_frame_accepted;
+use crate::codec::{subscribe_decoder, ProtocolCodec, ProtocolDecoder};
+use crate::command_retry::AttemptError;
+use crate::connections::ConnectionRegistry;
+use crate::dictionary::{resolve_codes, validate_can_command};
+use crate::models::{CanFilter, SerialConfig};
+use crate::schemas::validate_value;
+use crate::slcan_ack::SLCAN_ACK_TIMEOUT;
+use crate::transports::slcan::parse_slcan_frame;
+use crate::transports::Transport;
 
 /// Структура для хранения расширенного ID CAN-фрейма
 #[derive(serde::Serialize, Clone, Debug)]
@@ -18,12 +29,22 @@ pub struct FullId {
   pub argument_code: u32,
   pub target_id: u32,
   pub return_id: u32,
+  /// Имена заголовка и аргумента из словаря, `None` - код не найден
+  pub header_name: Option<String>,
+  pub argument_name: Option<String>,
+  /// Оба кода найдены в словаре
+  pub is_known: bool,
 }
 
 /// Структура данных для сообщений POECanable
 #[derive(serde::Serialize, Clone, Debug)]
 pub struct MessageData {
+  /// Время приёма по часам хоста, мс
   pub timestamp: u64,
+  /// Время приёма по часам хоста, мкс; в режиме временных меток SLCAN - метка адаптера, пересчитанная на часы хоста
+  pub timestamp_us: u64,
+  /// Метка адаптера в режиме Z1 (0..59999 мс), `None` - адаптер не передаёт метки
+  pub device_timestamp: Option<u16>,
   pub full_id: FullId,
   pub main_id: u32,
   #[serde(with = "serde_bytes")]
@@ -31,15 +52,70 @@ pub struct MessageData {
   pub json: String,
   pub is_remote: bool,
   pub is_complete: bool,
+  /// Ошибка проверки данных ответа OK! по схеме аргумента
+  pub schema_error: Option<String>,
 }
 
 /// Структура для хранения частичного пакета
 #[derive(Clone, Debug)]
 pub struct PartialPacket {
   pub timestamp: u64,
+  pub timestamp_us: u64,
   pub data: Vec<u8>,
 }
 
+/// Режим CAN-шины порта, определяемый протоколом подключения
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CanMode {
+  /// Классический CAN, до 8 байт данных во фрейме (протокол POECanable)
+  Classic,
+  /// CAN FD, до 64 байт данных во фрейме (протокол POECanableFD)
+  Fd,
+}
+
+impl CanMode {
+  /// Определяет режим по имени протокола
+  ///
+  /// # Returns
+  /// * `Ok(CanMode)` - режим протокола
+  /// * `Err(String)` - протокол не относится к POECanable
+  pub fn from_protocol(protocol: &str) -> Result<Self, String> {
+    match protocol {
+      "POECanable" => Ok(CanMode::Classic),
+      "POECanableFD" => Ok(CanMode::Fd),
+      _ => Err(format!("Protocol {} is not a CAN protocol", protocol)),
+    }
+  }
+
+  /// Имя протокола режима
+  pub fn protocol(self) -> &'static str {
+    match self {
+      CanMode::Classic => "POECanable",
+      CanMode::Fd => "POECanableFD",
+    }
+  }
+
+  /// Максимальный размер данных одного фрейма
+  pub fn max_frame_size(self) -> usize {
+    match self {
+      CanMode::Classic => 8,
+      CanMode::Fd => 64,
+    }
+  }
+
+  /// Символ SLCAN фрейма данных с расширенным ID
+  ///
+  /// # Arguments
+  /// * `brs` - переключать скорость в фазе данных (только CAN FD)
+  pub fn data_frame_type(self, brs: bool) -> char {
+    match (self, brs) {
+      (CanMode::Classic, _) => 'T',
+      (CanMode::Fd, false) => 'B',
+      (CanMode::Fd, true) => 'D',
+    }
+  }
+}
+
 /// Структура команды для протокола POECanable
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 struct PoeCANableCommand {
@@ -49,6 +125,9 @@ struct PoeCANableCommand {
   return_id: u32,
   convert_to_base64: u32,
   data: Option<String>,
+  /// Фреймы CAN FD с переключением скорости (d/D), по умолчанию без него (b/B)
+  #[serde(default)]
+  brs: Option<bool>,
 }
 
 /// Тип для хранения сообщений (ID -> MessageData)
@@ -63,98 +142,57 @@ lazy_static! {
         Arc::new(Mutex::new(HashMap::new()));
 }
 
+/// Период счётчика временных меток SLCAN в режиме Z1, мс
+const SLCAN_TIMESTAMP_PERIOD_MS: u64 = 60_000;
+
+/// Допустимое отставание пересчитанной метки от часов хоста, мкс
+const SLCAN_CLOCK_MAX_LAG_US: u64 = 1_000_000;
+
+/// Соответствие меток адаптера часам хоста для порта
+#[derive(Debug)]
+struct SlcanClock {
+  last_device_ms: u16,
+  last_mapped_us: u64,
+}
+
 lazy_static! {
-    #[derive(Debug)]
-    static ref DATA_BUFFERS: Arc<Mutex<HashMap<String, Vec<(u32, MessageData)>>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+  static ref SLCAN_CLOCKS: Mutex<HashMap<String, SlcanClock>> = Mutex::new(HashMap::new());
 }
 
 /// Обрабатывает принятые данные по протоколу POECanable и отправляет их через канал
 ///
+/// Фреймы разбирает декодер протокола из реестра, команда подходит для POECanable и POECanableFD.
+///
 /// # Arguments
 /// * `app` - дескриптор приложения Tauri
 /// * `port_path` - путь к серийному порту
-/// * `on_event` - канал для отправки обработанных данных (Vec<(u32, MessageData)>)
+/// * `on_event` - канал для отправки обработанных данных (пары `(u32, MessageData)` в виде JSON)
 ///
 /// # Returns
 /// * `Ok(u32)` - ID события прослушивания
 /// * `Err(String)` - ошибка при создании слушателя
 #[command]
-pub fn process_poe_canable(app: AppHandle<Wry>, port_path: String, on_event: Channel<Vec<(u32, MessageData)>>) -> Result<u32, String> {
-  let app_clone = app.clone();
-
-  // Создаём буфер для накопления данных
-  let buffer: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
-  let buffer_clone = buffer.clone();
-
-  // Форматируем путь порта для использования в имени события
-  let formatted_port_path = port_path
-    .clone()
-    .replace(".", "-")
-    .replace("/", "-")
-    .replace("\\", "-");
-  log(
-    LogLevel::Info,
-    "process_poe_canable",
-    format!("Форматированный путь порта: {}", formatted_port_path),
-  );
-  let listen_event_name = format!("plugin-serialplugin-read-{}", formatted_port_path);
-
-  let event_id = app_clone.clone().listen(listen_event_name, move |event| {
-    // Разбираем полезную нагрузку события
-    if let Ok(payload) = serde_json::from_str::<ReadDataResult>(&event.payload()) {
-      // Преобразуем байты в строку
-      let data_str = match String::from_utf8(payload.data.clone()) {
-        Ok(s) => s,
-        Err(_) => String::from_utf8_lossy(&payload.data).to_string(),
-      };
-      log(LogLevel::Info, "process_poe_canable", format!("Данные в виде строки: {}", data_str));
-
-      // Добавляем данные в буфер
-      {
-        let mut buffer_guard = buffer_clone.lock().unwrap();
-        buffer_guard.push_str(&data_str);
-        log(
-          LogLevel::Info,
-          "process_poe_canable",
-          format!("Буфер обновлён, текущий размер: {}", buffer_guard.len()),
-        );
-        drop(buffer_guard);
-      }
-
-      let processed_remaining = {
-        let buffer_guard = buffer_clone.lock().unwrap();
-        let data = buffer_guard.clone();
-        drop(buffer_guard);
-
-        match process_poe_canable_data(&data, &on_event, &port_path) {
-          Ok(remaining) => remaining,
-          Err(e) => {
-            log(LogLevel::Err, "process_poe_canable", format!("Ошибка обработки данных: {}", e));
-            String::new()
-          },
-        }
-      };
-
-      let mut buffer_guard = buffer_clone.lock().unwrap();
-      *buffer_guard = processed_remaining;
+pub fn process_poe_canable(app: AppHandle<Wry>, port_path: String, on_event: Channel<Vec<serde_json::Value>>) -> Result<u32, String> {
+  let expected = [CanMode::Classic.protocol(), CanMode::Fd.protocol()];
+  subscribe_decoder(&app, &port_path, &expected, "process_poe_canable", move |messages| {
+    if let Err(e) = on_event.send(messages) {
+      log(LogLevel::Err, "process_poe_canable", format!("Ошибка отправки через канал: {}", e));
     }
-  });
-
-  Ok(event_id)
+  })
 }
 
 /// Вспомогательная функция для обработки данных POECanable
 ///
 /// # Arguments
 /// * `data` - строка с данными для обработки
-/// * `on_event` - канал для отправки обработанных данных
+/// * `messages` - собранные сообщения для отправки
 /// * `port_path` - путь к порту (для логирования и работы с частичными пакетами)
+/// * `filters` - фильтры фреймов порта, не прошедшие фреймы не разбираются
 ///
 /// # Returns
-/// * `Ok(String)` - оставшиеся необработанные данные
+/// * `Ok(String)` - незавершённая последняя строка данных
 /// * `Err(String)` - ошибка обработки
-fn process_poe_canable_data(data: &str, on_event: &Channel<Vec<(u32, MessageData)>>, port_path: &str) -> Result<String, String> {
+pub(crate) fn process_poe_canable_data(data: &str, messages: &mut Vec<(u32, MessageData)>, port_path: &str, filters: &[CanFilter]) -> Result<String, String> {
   const PACKET_TIMEOUT: u64 = 2000;
 
   log(
@@ -163,11 +201,12 @@ fn process_poe_canable_data(data: &str, on_event: &Channel<Vec<(u32, MessageData
     format!("Начало обработки POECanable данных для порта: {}", port_path),
   );
 
-  let mut remaining_data = data.to_string();
-  let now = std::time::SystemTime::now()
+  let remaining_data = data.to_string();
+  let now_us = std::time::SystemTime::now()
     .duration_since(std::time::UNIX_EPOCH)
     .unwrap()
-    .as_millis() as u64;
+    .as_micros() as u64;
+  let now = now_us / 1000;
 
   log(LogLevel::Info, "process_poe_canable_data", format!("Очистка устаревших частичных пакетов"));
 
@@ -176,7 +215,7 @@ fn process_poe_canable_data(data: &str, on_event: &Channel<Vec<(u32, MessageData
 
   let mut new_messages: MessagesMap = HashMap::new();
 
-  // Регулярное выражение для поиска CAN-фреймов
+  // Регулярное выражение для поиска CAN-фреймов; в режиме Z1 последние 4 символа данных - метка адаптера
   let can_frame_regex = Regex::new(r"([tTrRdDbB])([0-9A-F]{3,8})([0-9A-F])([0-9A-F]*)\r").map_err(|e| {
     log(
       LogLevel::Err,
@@ -187,7 +226,6 @@ fn process_poe_canable_data(data: &str, on_event: &Channel<Vec<(u32, MessageData
   })?;
 
   // Обрабатываем все совпадения в цикле
-  let mut processed_data = String::new();
   let mut last_processed_pos = 0;
 
   log(
@@ -195,19 +233,28 @@ fn process_poe_canable_data(data: &str, on_event: &Channel<Vec<(u32, MessageData
     "process_poe_canable_data",
     format!("Поиск CAN фреймов в строке: {}", remaining_data),
   );
-  for cap in can_frame_regex.captures_iter(&remaining_data) {
-    let match_start = cap.get(0).unwrap().start();
-    let match_end = cap.get(0).unwrap().end();
-
-    // Добавляем все данные до текущего совпадения в обработанные
-    processed_data.push_str(&remaining_data[last_processed_pos..match_start]);
+  // Последний фрейм с меткой адаптера принят ближе всего ко времени чтения данных
+  let captures: Vec<regex::Captures> = can_frame_regex.captures_iter(&remaining_data).collect();
+  let batch_end_ms = captures.iter().rev().find_map(frame_device_timestamp);
+  for cap in captures.iter() {
+    // Данные между фреймами - завершённые строки подтверждений и ответов адаптера, они не сохраняются
+    last_processed_pos = cap.get(0).unwrap().end();
+
+    // Фреймы, не прошедшие фильтры порта, пропускаются до разбора
+    let is_extended = cap[1].starts_with(|c: char| c.is_ascii_uppercase());
+    if let Ok(can_id) = u32::from_str_radix(&cap[2], 16) {
+      if !can_frame_accepted(filters, can_id, is_extended) {
+        log(LogLevel::Info, "process_poe_canable_data", format!("Фрейм с ID {:X} отброшен фильтром", can_id));
+        continue;
+      }
+    }
 
     log(
       LogLevel::Info,
       "process_poe_canable_data",
       format!("Найдено совпадение: {}", cap.get(0).unwrap().as_str()),
     );
-    match process_can_frame(&cap, now, port_path, &mut new_messages) {
+    match process_can_frame(cap, now_us, batch_end_ms, port_path, &mut new_messages) {
       Ok(_) => {
         log(LogLevel::Info, "process_poe_canable_data", format!("CAN фрейм успешно обработан"));
       },
@@ -216,12 +263,13 @@ fn process_poe_canable_data(data: &str, on_event: &Channel<Vec<(u32, MessageData
         eprintln!("Error processing frame: {}", e);
       },
     }
-
-    last_processed_pos = match_end;
   }
 
-  processed_data.push_str(&remaining_data[last_processed_pos..]);
-  remaining_data = processed_data;
+  // После последнего фрейма сохраняется только незавершённая строка, чтобы буфер не рос
+  // от ответов адаптера и не просматривался повторно
+  let tail = &remaining_data[last_processed_pos..];
+  let partial_start = tail.rfind(['\r', '\x07']).map_or(0, |pos| pos + 1);
+  let remaining_data = tail[partial_start..].to_string();
 
   // Обновляем хранилище сообщений для конкретного порта
   if !new_messages.is_empty() {
@@ -263,15 +311,13 @@ fn process_poe_canable_data(data: &str, on_event: &Channel<Vec<(u32, MessageData
       messages_to_send.push((main_id, message_data));
     }
 
-    // Отправляем все накопленные сообщения через канал
-    if !messages_to_send.is_empty() {
-      log(
-        LogLevel::Info,
-        "process_poe_canable_data",
-        format!("Отправка {} сообщений через канал", messages_to_send.len()),
-      );
-      on_event.send(messages_to_send).map_err(|e| e.to_string())?;
-    }
+    // Передаём все накопленные сообщения для отправки через канал
+    log(
+      LogLevel::Info,
+      "process_poe_canable_data",
+      format!("Отправка {} сообщений через канал", messages_to_send.len()),
+    );
+    messages.extend(messages_to_send);
   } else {
     log(LogLevel::Info, "process_poe_canable_data", format!("Новых сообщений не обнаружено"));
   }
@@ -284,14 +330,15 @@ fn process_poe_canable_data(data: &str, on_event: &Channel<Vec<(u32, MessageData
 ///
 /// # Arguments
 /// * `cap` - захваченные группы регулярного выражения
-/// * `now` - текущее время
+/// * `now_us` - время приёма данных по часам хоста, мкс
+/// * `batch_end_ms` - метка адаптера последнего фрейма в принятых данных
 /// * `port_path` - путь к порту
 /// * `new_messages` - mutable reference для добавления новых сообщений
 ///
 /// # Returns
 /// * `Ok(())` - фрейм успешно обработан
 /// * `Err(String)` - ошибка обработки
-fn process_can_frame(cap: &regex::Captures, now: u64, port_path: &str, new_messages: &mut MessagesMap) -> Result<(), String> {
+fn process_can_frame(cap: &regex::Captures, now_us: u64, batch_end_ms: Option<u16>, port_path: &str, new_messages: &mut MessagesMap) -> Result<(), String> {
   log(LogLevel::Info, "process_can_frame", format!("Начало обработки CAN фрейма"));
 
   // Извлекаем части фрейма из регулярного выражения
@@ -332,25 +379,23 @@ fn process_can_frame(cap: &regex::Captures, now: u64, port_path: &str, new_messa
   );
 
   // Рассчитываем количество байт данных в зависимости от типа фрейма
-  let data_bytes = if is_canfd {
-    let size = match dlc as usize {
-      0..=8 => dlc as usize,
-      9 => 12,
-      10 => 16,
-      11 => 20,
-      12 => 24,
-      13 => 32,
-      14 => 48,
-      15 => 64,
-      _ => 0,
-    };
-    log(LogLevel::Info, "process_can_frame", format!("Рассчитанный размер данных для CANFD: {}", size));
-    size
-  } else {
-    let size = std::cmp::min(dlc as usize, 8);
-    log(LogLevel::Info, "process_can_frame", format!("Рассчитанный размер данных для CAN: {}", size));
-    size
+  let data_bytes = can_data_bytes(dlc as usize, is_canfd);
+  log(LogLevel::Info, "process_can_frame", format!("Рассчитанный размер данных: {}", data_bytes));
+
+  // В режиме Z1 после данных адаптер передаёт метку времени из 4 HEX-символов
+  let data_len = if is_remote { 0 } else { data_bytes * 2 };
+  let device_timestamp = frame_device_timestamp(cap);
+  let hex_data = &hex_data[..std::cmp::min(hex_data.len(), data_len)];
+
+  // Время приёма фрейма: метка адаптера, пересчитанная на часы хоста, или время чтения данных
+  let received_us = match device_timestamp {
+    Some(device_ms) => {
+      log(LogLevel::Info, "process_can_frame", format!("Метка времени адаптера: {} мс", device_ms));
+      map_device_timestamp(port_path, device_ms, batch_end_ms.unwrap_or(device_ms), now_us)
+    },
+    None => now_us,
   };
+  let now = received_us / 1000;
 
   // Разбираем HEX-данные в байты
   let mut bytes = if !is_remote {
@@ -392,12 +437,25 @@ fn process_can_frame(cap: &regex::Captures, now: u64, port_path: &str, new_messa
   // Разбираем расширенный ID
   let decoded_id = if is_extended {
     log(LogLevel::Info, "process_can_frame", format!("Разбор расширенного ID"));
+    let header_code = (can_id >> 26) & 0x03;
+    let argument_code = (can_id >> 16) & 0x3ff;
+    let (header_name, argument_name) = resolve_codes(header_code, argument_code);
+    if header_name.is_none() || argument_name.is_none() {
+      log(
+        LogLevel::Warn,
+        "process_can_frame",
+        format!("Коды отсутствуют в словаре: заголовок {}, аргумент {}", header_code, argument_code),
+      );
+    }
     FullId {
       is_full_packet: (can_id >> 28) & 0x01,
-      header_code: (can_id >> 26) & 0x03,
-      argument_code: (can_id >> 16) & 0x3ff,
+      header_code,
+      argument_code,
       target_id: (can_id >> 8) & 0xff,
       return_id: can_id & 0xff,
+      is_known: header_name.is_some() && argument_name.is_some(),
+      header_name,
+      argument_name,
     }
   } else {
     log(LogLevel::Info, "process_can_frame", format!("ID не расширенный"));
@@ -407,6 +465,9 @@ fn process_can_frame(cap: &regex::Captures, now: u64, port_path: &str, new_messa
       argument_code: 0,
       target_id: 0,
       return_id: 0,
+      header_name: None,
+      argument_name: None,
+      is_known: false,
     }
   };
 
@@ -419,12 +480,15 @@ fn process_can_frame(cap: &regex::Captures, now: u64, port_path: &str, new_messa
     log(LogLevel::Info, "process_can_frame", format!("Создание remote сообщения с ID: {}", main_id));
     let message = MessageData {
       timestamp: now,
+      timestamp_us: received_us,
+      device_timestamp,
       full_id: decoded_id.clone(),
       main_id,
       can_data: Vec::new(),
       json: "{}".to_string(),
       is_remote: true,
       is_complete: true,
+      schema_error: None,
     };
     new_messages.insert(main_id, message);
   }
@@ -475,19 +539,36 @@ fn process_can_frame(cap: &regex::Captures, now: u64, port_path: &str, new_messa
         "{}".to_string()
       };
 
+      // Данные ответа проверяются по схеме аргумента, если она задана
+      let schema_error = if decoded_id.header_name.as_deref() == Some("OK!") && !complete_data.is_empty() {
+        validate_value(decoded_id.argument_code, String::from_utf8_lossy(&complete_data).trim_end_matches('\0')).err()
+      } else {
+        None
+      };
+      if let Some(error) = &schema_error {
+        log(
+          LogLevel::Warn,
+          "process_can_frame",
+          format!("Данные ответа с ID {} не прошли проверку по схеме: {}", main_id, error),
+        );
+      }
+
       // Получаем временную метку из частичного пакета или используем текущую
-      let timestamp = get_partial_packet(port_path, main_id)
-        .map(|p| p.timestamp)
-        .unwrap_or(now);
+      let (timestamp, timestamp_us) = get_partial_packet(port_path, main_id)
+        .map(|p| (p.timestamp, p.timestamp_us))
+        .unwrap_or((now, received_us));
 
       let message = MessageData {
         timestamp,
+        timestamp_us,
+        device_timestamp,
         full_id: decoded_id,
         main_id,
         can_data: complete_data,
         json: json_str,
         is_remote: false,
         is_complete: true,
+        schema_error,
       };
 
       new_messages.insert(main_id, message);
@@ -527,9 +608,12 @@ fn process_can_frame(cap: &regex::Captures, now: u64, port_path: &str, new_messa
         );
         remove_partial_packet(port_path, main_id);
       } else {
-        let timestamp = existing.map(|e| e.timestamp).unwrap_or(now);
+        let (timestamp, timestamp_us) = existing
+          .map(|e| (e.timestamp, e.timestamp_us))
+          .unwrap_or((now, received_us));
         let packet = PartialPacket {
           timestamp,
+          timestamp_us,
           data: new_data.clone(),
         };
 
@@ -543,6 +627,107 @@ fn process_can_frame(cap: &regex::Captures, now: u64, port_path: &str, new_messa
   Ok(())
 }
 
+/// Число байт данных фрейма по DLC
+fn can_data_bytes(dlc: usize, is_canfd: bool) -> usize {
+  match dlc {
+    0..=8 => dlc,
+    _ if !is_canfd => 8,
+    9 => 12,
+    10 => 16,
+    11 => 20,
+    12 => 24,
+    13 => 32,
+    14 => 48,
+    15 => 64,
+    _ => 0,
+  }
+}
+
+/// Метка адаптера (режим Z1) фрейма, найденного регулярным выражением
+///
+/// # Returns
+/// * `Some(u16)` - метка, мс
+/// * `None` - фрейм передан без метки
+fn frame_device_timestamp(cap: &regex::Captures) -> Option<u16> {
+  let frame_type = &cap[1];
+  let hex_data = &cap[4];
+  let dlc = usize::from_str_radix(&cap[3], 16).ok()?;
+  let data_len = if matches!(frame_type, "r" | "R") {
+    0
+  } else {
+    can_data_bytes(dlc, matches!(frame_type, "b" | "B" | "d" | "D")) * 2
+  };
+  if hex_data.len() != data_len + 4 {
+    return None;
+  }
+  u16::from_str_radix(&hex_data[data_len..], 16).ok()
+}
+
+/// Интервал между метками адаптера с учётом обнуления счётчика, мс
+fn device_delta_ms(from_ms: u16, to_ms: u16) -> u64 {
+  let from_ms = from_ms as u64 % SLCAN_TIMESTAMP_PERIOD_MS;
+  let to_ms = to_ms as u64 % SLCAN_TIMESTAMP_PERIOD_MS;
+  (to_ms + SLCAN_TIMESTAMP_PERIOD_MS - from_ms) % SLCAN_TIMESTAMP_PERIOD_MS
+}
+
+/// Пересчитывает метку адаптера SLCAN (режим Z1) на часы хоста
+///
+/// Счётчик адаптера считает миллисекунды и обнуляется каждые 60 секунд, поэтому метка
+/// отсчитывается от предыдущего фрейма порта, а число пропущенных периодов оценивается
+/// по часам хоста. При привязке ко времени чтения данных к нему относится последний фрейм
+/// принятого блока, а более ранние фреймы смещаются назад на разницу меток. Результат не
+/// опережает часы хоста и не убывает; при отставании больше секунды (уход часов адаптера,
+/// перезапуск адаптера) привязка выполняется заново.
+///
+/// # Arguments
+/// * `port_path` - путь к порту
+/// * `device_ms` - метка адаптера, мс
+/// * `batch_end_ms` - метка адаптера последнего фрейма в принятых данных, мс
+/// * `host_us` - время чтения данных по часам хоста, мкс
+///
+/// # Returns
+/// * `u64` - время приёма фрейма по часам хоста, мкс
+fn map_device_timestamp(port_path: &str, device_ms: u16, batch_end_ms: u16, host_us: u64) -> u64 {
+  let anchor_us = host_us.saturating_sub(device_delta_ms(device_ms, batch_end_ms) * 1000);
+  let mut clocks = SLCAN_CLOCKS.lock().unwrap();
+  let mapped_us = match clocks.get(port_path) {
+    Some(clock) => {
+      let delta_ms = device_delta_ms(clock.last_device_ms, device_ms);
+      let elapsed_ms = host_us.saturating_sub(clock.last_mapped_us) / 1000;
+      let periods = (elapsed_ms.saturating_sub(delta_ms) + SLCAN_TIMESTAMP_PERIOD_MS / 2) / SLCAN_TIMESTAMP_PERIOD_MS;
+      let mapped_us = clock.last_mapped_us + (delta_ms + periods * SLCAN_TIMESTAMP_PERIOD_MS) * 1000;
+
+      if host_us.saturating_sub(mapped_us) > SLCAN_CLOCK_MAX_LAG_US {
+        log(
+          LogLevel::Warn,
+          "map_device_timestamp",
+          format!("Метки адаптера на порту {} отстают от часов хоста, привязка обновлена", port_path),
+        );
+        anchor_us.max(clock.last_mapped_us)
+      } else {
+        mapped_us.min(host_us).max(clock.last_mapped_us)
+      }
+    },
+    None => {
+      log(
+        LogLevel::Info,
+        "map_device_timestamp",
+        format!("Привязка меток адаптера на порту {} к часам хоста", port_path),
+      );
+      anchor_us
+    },
+  };
+
+  clocks.insert(
+    port_path.to_string(),
+    SlcanClock {
+      last_device_ms: device_ms,
+      last_mapped_us: mapped_us,
+    },
+  );
+  mapped_us
+}
+
 /// Вставляет частичный пакет в хранилище
 fn insert_partial_packet(port_path: &str, main_id: u32, packet: PartialPacket) {
   log(
@@ -612,6 +797,25 @@ fn remove_partial_packet(port_path: &str, main_id: u32) {
   }
 }
 
+/// Сбрасывает состояние разбора порта: частичные пакеты и привязку меток адаптера
+///
+/// Вызывается при закрытии, повторном открытии и переподключении порта, чтобы данные
+/// нового подключения не собирались с остатками пакетов и метками предыдущего.
+///
+/// # Arguments
+/// * `port_path` - путь к порту
+pub(crate) fn reset_port_state(port_path: &str) {
+  let had_packets = PARTIAL_PACKETS.lock().unwrap().remove(port_path).is_some();
+  let had_clock = SLCAN_CLOCKS.lock().unwrap().remove(port_path).is_some();
+  if had_packets || had_clock {
+    log(
+      LogLevel::Info,
+      "reset_port_state",
+      format!("Состояние разбора POECanable порта {} сброшено", port_path),
+    );
+  }
+}
+
 /// Очищает устаревшие частичные пакеты из хранилища
 fn clear_expired_partial_packets(port_path: &str, now: u64, packet_timeout: u64) {
   log(
@@ -626,7 +830,7 @@ fn clear_expired_partial_packets(port_path: &str, now: u64, packet_timeout: u64)
   let mut packets = PARTIAL_PACKETS.lock().unwrap();
   if let Some(port_packets) = packets.get_mut(port_path) {
     let initial_count = port_packets.len();
-    port_packets.retain(|_main_id, packet| now - packet.timestamp <= packet_timeout);
+    port_packets.retain(|_main_id, packet| now.saturating_sub(packet.timestamp) <= packet_timeout);
     let removed_count = initial_count - port_packets.len();
     if removed_count > 0 {
       log(
@@ -644,87 +848,104 @@ fn clear_expired_partial_packets(port_path: &str, now: u64, packet_timeout: u64)
   }
 }
 
-/// Отправляет команду по протоколу POECanable в серийный порт
+/// Фреймы SLCAN, в которые кодируется команда POECanable
+pub(crate) struct PoeCanableFrames {
+  /// Строки фреймов, каждая завершается '\r'
+  pub(crate) frames: Vec<String>,
+  /// Данные после конвертации в Base64 (для подтверждения отправки на фронтенде)
+  pub(crate) converted_data: Option<String>,
+}
+
+/// Кодирует команду POECanable во фреймы SLCAN
 ///
 /// # Arguments
-/// * `app` - дескриптор приложения Tauri
-/// * `serial` - состояние серийного порта
-/// * `protocol` - протокол ("POECanable", "POECanableFD")
-/// * `port_path` - путь к серийному порту
+/// * `mode` - режим CAN-шины порта
 /// * `sending_data` - JSON-объект с командой
 ///
 /// # Returns
-/// * `Ok(())` - команда успешно отправлена
-/// * `Err(String)` - ошибка отправки
-pub fn send_poe_canable_command(
-  app: AppHandle,
-  serial: State<'_, SerialPort<Wry>>,
-  protocol: String,
-  port_path: String,
-  sending_data: serde_json::Value,
-) -> Result<(), String> {
-  log(
-    LogLevel::Info,
-    "send_poe_canable_command",
-    format!("Начало отправки POECanable команды по протоколу {} на порт: {}", protocol, port_path),
-  );
-
+/// * `Ok(PoeCanableFrames)` - фреймы для записи в порт
+/// * `Err(String)` - команда некорректна
+pub(crate) fn encode_poe_canable_command(mode: CanMode, sending_data: serde_json::Value) -> Result<PoeCanableFrames, String> {
   // Разбираем JSON в структуру команды
   let command: PoeCANableCommand = serde_json::from_value(sending_data).map_err(|e| {
     log(
       LogLevel::Err,
-      "send_poe_canable_command",
+      "encode_poe_canable_command",
       format!("Не удалось разобрать команду POECanable: {}", e),
     );
-    format!("Failed to parse simple serial command: {}", e)
+    format!("Failed to parse POECanable command: {}", e)
   })?;
 
   // Проверяем валидность параметров ID
   if command.header > 0x3 || command.argument > 0x3ff || command.target_id > 0xff || command.return_id > 0xff {
-    log(LogLevel::Err, "send_poe_canable_command", format!("Неверные параметры ID команды"));
+    log(LogLevel::Err, "encode_poe_canable_command", format!("Неверные параметры ID команды"));
     return Err("Invalid ID parameters".to_string());
   }
+  validate_can_command(command.header, command.argument).map_err(|e| {
+    log(
+      LogLevel::Err,
+      "encode_poe_canable_command",
+      format!("Команда не прошла проверку по словарю: {}", e),
+    );
+    e
+  })?;
+  if resolve_codes(command.header, command.argument).0.as_deref() == Some("SET") {
+    if let Some(payload) = command_payload_text(&command) {
+      validate_value(command.argument, &payload).map_err(|e| {
+        log(
+          LogLevel::Err,
+          "encode_poe_canable_command",
+          format!("Данные не прошли проверку по схеме: {}", e),
+        );
+        e
+      })?;
+    }
+  }
+
+  let brs = command.brs.unwrap_or(false);
+  if brs && mode == CanMode::Classic {
+    log(
+      LogLevel::Err,
+      "encode_poe_canable_command",
+      format!("Переключение скорости (BRS) доступно только в режиме CAN FD"),
+    );
+    return Err("BRS is only available for CAN FD connections".to_string());
+  }
+  let frame_type = mode.data_frame_type(brs);
+  let max_frame_size = mode.max_frame_size();
 
   // Рассчитываем CAN ID
   let can_id =
     (((command.header & 0x03) << 26) | ((command.argument & 0x3ff) << 16) | ((command.target_id & 0xff) << 8) | (command.return_id & 0xff)) & 0x1fffffff;
 
-  log(LogLevel::Info, "send_poe_canable_command", format!("Рассчитанный CAN ID: 0x{:08X}", can_id));
+  log(LogLevel::Info, "encode_poe_canable_command", format!("Рассчитанный CAN ID: 0x{:08X}", can_id));
 
-  // Обрабатываем данные команды
-  if let Some(ref data_str) = command.data {
-    if data_str.trim().is_empty() {
-      log(LogLevel::Info, "send_poe_canable_command", format!("Данные пусты, отправка remote фрейма"));
-      let frame_id = can_id | (1 << 28);
-      let formatted_str = format_can_frame('R', frame_id, None, 0)?;
-      log(
-        LogLevel::Info,
-        "send_poe_canable_command",
-        format!("Сформирован remote фрейм: {}", formatted_str),
-      );
+  let mut frames = Vec::new();
+  let mut converted_data = None;
 
-      let _ = write(app.clone(), serial.clone(), port_path.clone(), formatted_str.clone());
-    } else {
+  // Обрабатываем данные команды
+  match command.data {
+    Some(ref data_str) if !data_str.trim().is_empty() => {
       if command.convert_to_base64 == 1 {
         let mut bytes: Vec<u8> = Vec::new();
         for hex_part in data_str.split_whitespace() {
           match u8::from_str_radix(hex_part, 16) {
             Ok(byte) => {
               bytes.push(byte);
-              log(LogLevel::Info, "send_poe_canable_command", format!("Добавлен байт из HEX: 0x{:02X}", byte));
+              log(LogLevel::Info, "encode_poe_canable_command", format!("Добавлен байт из HEX: 0x{:02X}", byte));
             },
             Err(_) => {
-              log(LogLevel::Err, "send_poe_canable_command", format!("Ошибка разбора HEX: {}", hex_part));
+              log(LogLevel::Err, "encode_poe_canable_command", format!("Ошибка разбора HEX: {}", hex_part));
               eprintln!("Error HEX {}", hex_part)
             },
           }
         }
-        log(LogLevel::Info, "send_poe_canable_command", format!("Данные для отправки: {}", data_str));
+        log(LogLevel::Info, "encode_poe_canable_command", format!("Данные для отправки: {}", data_str));
 
         let base64_str = if bytes.is_empty() {
           log(
             LogLevel::Info,
-            "send_poe_canable_command",
+            "encode_poe_canable_command",
             format!("Данные не являются HEX, конвертируем в байты как строку"),
           );
           let mut temp = data_str.bytes().collect::<Vec<u8>>();
@@ -738,75 +959,138 @@ pub fn send_poe_canable_command(
           temp
         };
 
-        let max_frame_size = if protocol == "POECanableFD" { 64 } else { 8 };
         log(
           LogLevel::Info,
-          "send_poe_canable_command",
-          format!("Максимальный размер фрейма: {}", max_frame_size),
+          "encode_poe_canable_command",
+          format!("Максимальный размер фрейма: {}, тип фрейма: {}", max_frame_size, frame_type),
         );
 
-        // Отправляем данные по частям
+        // Разбиваем данные на фреймы
         for (offset, chunk) in base64_str.chunks(max_frame_size).enumerate() {
           let is_final = offset * max_frame_size + chunk.len() >= base64_str.len();
           let frame_id = can_id | if is_final { 1 << 28 } else { 0 };
 
-          let frame_type = if protocol == "POECanableFD" { 'B' } else { 'T' };
           log(
             LogLevel::Info,
-            "send_poe_canable_command",
-            format!("Отправка фрейма {}, финальный: {}, ID: 0x{:08X}", offset, is_final, frame_id),
+            "encode_poe_canable_command",
+            format!("Фрейм {}, финальный: {}, ID: 0x{:08X}", offset, is_final, frame_id),
           );
 
           let formatted_str = format_can_frame(frame_type, frame_id, Some(chunk.to_vec()), chunk.len() as u32)?;
-          log(LogLevel::Info, "send_poe_canable_command", format!("Сформирован фрейм: {}", formatted_str));
-
-          if !chunk.to_vec().is_empty() {
-            log(LogLevel::Info, "send_poe_canable_command", format!("Отправка подтверждения отправки данных"));
-
-            if let Err(e) = app.emit(
-              &format!("poe-canable-sending-data-{}", port_path.clone()),
-              String::from_utf8_lossy(&base64_str).to_string(),
-            ) {
-              log(LogLevel::Err, "send_poe_canable_command", format!("Ошибка отправки подтверждения: {}", e));
-
-              eprintln!("Failed to emit data: {}", e);
-            }
-          }
-
-          let _ = write(app.clone(), serial.clone(), port_path.clone(), formatted_str.clone());
+          log(LogLevel::Info, "encode_poe_canable_command", format!("Сформирован фрейм: {}", formatted_str));
+          frames.push(formatted_str);
         }
+        converted_data = Some(String::from_utf8_lossy(&base64_str).to_string());
       } else {
         // Отправляем данные без конвертации
         let bytes = data_str.as_bytes();
-        let max_frame_size = if protocol == "poe_canfd" { 64 } else { 8 };
 
         for (offset, chunk) in bytes.chunks(max_frame_size).enumerate() {
           let is_final = offset * max_frame_size + chunk.len() >= bytes.len();
           let frame_id = can_id | if is_final { 1 << 28 } else { 0 };
 
-          let frame_type = if protocol == "poe_canfd" { 'B' } else { 'T' };
-          let formatted_str = format_can_frame(frame_type, frame_id, Some(chunk.to_vec()), chunk.len() as u32)?;
-
-          let _ = write(app.clone(), serial.clone(), port_path.clone(), formatted_str.clone());
+          frames.push(format_can_frame(frame_type, frame_id, Some(chunk.to_vec()), chunk.len() as u32)?);
         }
       }
+    },
+    _ => {
+      log(
+        LogLevel::Info,
+        "encode_poe_canable_command",
+        format!("Данные отсутствуют, формирование remote фрейма"),
+      );
+
+      let frame_id = can_id | (1 << 28);
+      let formatted_str = format_can_frame('R', frame_id, None, 0)?;
+      log(
+        LogLevel::Info,
+        "encode_poe_canable_command",
+        format!("Сформирован remote фрейм: {}", formatted_str),
+      );
+      frames.push(formatted_str);
+    },
+  }
+
+  Ok(PoeCanableFrames { frames, converted_data })
+}
+
+/// Отправляет команду по протоколу POECanable в серийный порт
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `transport` - транспорт открытого порта
+/// * `mode` - режим CAN-шины порта
+/// * `sending_data` - JSON-объект с командой
+///
+/// # Returns
+/// * `Ok(())` - команда отправлена; фреймы без подтверждения адаптера публикуются в `app-status`
+/// * `Err(String)` - ошибка отправки или адаптер отклонил фрейм
+pub fn send_poe_canable_command(app: AppHandle, transport: &dyn Transport, mode: CanMode, sending_data: serde_json::Value) -> Result<(), String> {
+  let port_path = transport.path().to_string();
+  log(
+    LogLevel::Info,
+    "send_poe_canable_command",
+    format!("Начало отправки POECanable команды по протоколу {} на порт: {}", mode.protocol(), port_path),
+  );
+
+  let encoded = encode_poe_canable_command(mode, sending_data)?;
+
+  if let Some(converted_data) = encoded.converted_data {
+    log(LogLevel::Info, "send_poe_canable_command", format!("Отправка подтверждения отправки данных"));
+
+    if let Err(e) = app.emit(&format!("poe-canable-sending-data-{}", port_path), converted_data) {
+      log(LogLevel::Err, "send_poe_canable_command", format!("Ошибка отправки подтверждения: {}", e));
+
+      eprintln!("Failed to emit data: {}", e);
     }
-  } else {
-    log(
-      LogLevel::Info,
-      "send_poe_canable_command",
-      format!("Данные отсутствуют, отправка remote фрейма"),
-    );
+  }
+
+  // Каждый фрейм подтверждается адаптером, отклонённый фрейм прерывает отправку команды
+  let acks = app.state::<ConnectionRegistry>().slcan_acks(&port_path);
+  let mut unconfirmed = 0;
+  for frame in encoded.frames.iter() {
+    match &acks {
+      Some(acks) => acks
+        .write_confirmed(transport, frame.as_bytes(), SLCAN_ACK_TIMEOUT)
+        .map(|confirmed| {
+          if !confirmed {
+            unconfirmed += 1;
+          }
+        }),
+      None => transport
+        .write(frame.as_bytes())
+        .map(|_| ())
+        .map_err(|e| format!("Failed to write: {}", e)),
+    }
+    .map_err(|e| {
+      log(
+        LogLevel::Err,
+        "send_poe_canable_command",
+        format!("Фрейм {} не отправлен: {}", frame.trim_end(), e),
+      );
+      e
+    })?;
+  }
 
-    let frame_id = can_id | (1 << 28);
-    let formatted_str = format_can_frame('R', frame_id, None, 0)?;
+  // Фреймы без подтверждения записаны в порт, но их приём адаптером не известен
+  if unconfirmed > 0 {
     log(
-      LogLevel::Info,
+      LogLevel::Warn,
       "send_poe_canable_command",
-      format!("Сформирован remote фрейм: {}", formatted_str),
+      format!(
+        "Адаптер не подтвердил {} из {} фреймов на порту {}",
+        unconfirmed,
+        encoded.frames.len(),
+        port_path
+      ),
     );
-
-    let _ = write(app.clone(), serial.clone(), port_path.clone(), formatted_str.clone());
+    if let Err(e) = app.emit(
+      "app-status",
+      format!("Adapter did not confirm {} of {} frames on {}", unconfirmed, encoded.frames.len(), port_path),
+    ) {
+      eprintln!("Failed to emit data: {}", e);
+    }
+    return Ok(());
   }
 
   log(LogLevel::Info, "send_poe_canable_command", format!("Команда POECanable успешно отправлена"));
@@ -814,6 +1098,100 @@ pub fn send_poe_canable_command(
   Ok(())
 }
 
+/// Текст данных команды для проверки по схеме
+///
+/// При `convert_to_base64 == 1` данные, целиком записанные в HEX, сначала переводятся в байты.
+///
+/// # Returns
+/// * `Some(String)` - данные команды
+/// * `None` - данных нет (remote фрейм)
+fn command_payload_text(command: &PoeCANableCommand) -> Option<String> {
+  let data_str = command.data.as_deref()?.trim();
+  if data_str.is_empty() {
+    return None;
+  }
+  if command.convert_to_base64 == 1 {
+    let bytes: Result<Vec<u8>, _> = data_str
+      .split_whitespace()
+      .map(|hex_part| u8::from_str_radix(hex_part, 16))
+      .collect();
+    if let Ok(bytes) = bytes {
+      return Some(String::from_utf8_lossy(&bytes).to_string());
+    }
+  }
+  Some(data_str.to_string())
+}
+
+/// Отправляет команду по протоколу POECanable и ждёт ответ устройства
+///
+/// Ответом считается последний фрейм пакета с заголовком OK! или ER!, тем же аргументом
+/// и адресованный отправителю команды (целевой и обратный ID меняются местами).
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `transport` - транспорт открытого порта
+/// * `mode` - режим CAN-шины порта
+/// * `sending_data` - JSON-объект с командой
+/// * `timeout` - время ожидания ответа
+///
+/// # Returns
+/// * `Ok(())` - устройство ответило OK!
+/// * `Err(AttemptError)` - ошибка попытки: `Retry` при отсутствии ответа
+pub(crate) fn send_poe_canable_command_awaiting_reply(
+  app: AppHandle,
+  transport: &dyn Transport,
+  mode: CanMode,
+  sending_data: serde_json::Value,
+  timeout: Duration,
+) -> Result<(), AttemptError> {
+  let command: PoeCANableCommand =
+    serde_json::from_value(sending_data.clone()).map_err(|e| AttemptError::Fail(format!("Failed to parse POECanable command: {}", e)))?;
+
+  // Подписываемся до отправки, чтобы не пропустить быстрый ответ
+  let (sender, receiver) = mpsc::channel::<u32>();
+  let line = Mutex::new(String::new());
+  let listener_id = transport.subscribe(Box::new(move |data| {
+    let mut line = line.lock().unwrap();
+    for &byte in data.iter() {
+      // BEL - подтверждение ошибки адаптера, оно завершает строку так же, как '\r'
+      if byte != b'\r' && byte != 0x07 {
+        line.push(byte as char);
+        continue;
+      }
+      let Ok(frame) = parse_slcan_frame(line.trim()) else {
+        line.clear();
+        continue;
+      };
+      line.clear();
+
+      let header = (frame.id >> 26) & 0x03;
+      let is_reply = frame.extended
+        && (frame.id >> 28) & 0x01 == 1
+        && (header == 2 || header == 3)
+        && (frame.id >> 16) & 0x3ff == command.argument
+        && (frame.id >> 8) & 0xff == command.return_id
+        && frame.id & 0xff == command.target_id;
+      if is_reply {
+        let _ = sender.send(header);
+      }
+    }
+  }));
+
+  let result = send_poe_canable_command(app, transport, mode, sending_data)
+    .map_err(AttemptError::Fail)
+    .and_then(|_| {
+      receiver
+        .recv_timeout(timeout)
+        .map_err(|_| AttemptError::Retry(format!("No reply after {} ms", timeout.as_millis())))
+    });
+  transport.unsubscribe(listener_id);
+
+  match result? {
+    3 => Err(AttemptError::Fail("Device returned an error".to_string())),
+    _ => Ok(()),
+  }
+}
+
 /// Формирует строку CAN-фрейма по заданным параметрам
 ///
 /// # Arguments
@@ -825,7 +1203,7 @@ pub fn send_poe_canable_command(
 /// # Returns
 /// * `Ok(String)` - сформированная строка фрейма
 /// * `Err(String)` - ошибка форматирования
-fn format_can_frame(frame_type: char, id: u32, data: Option<Vec<u8>>, dlc: u32) -> Result<String, String> {
+pub(crate) fn format_can_frame(frame_type: char, id: u32, data: Option<Vec<u8>>, dlc: u32) -> Result<String, String> {
   log(
     LogLevel::Info,
     "format_can_frame",
@@ -884,3 +1262,219 @@ fn format_can_frame(frame_type: char, id: u32, data: Option<Vec<u8>>, dlc: u32)
 
   Ok(result)
 }
+
+/// Протокол POECanable поверх адаптера SLCAN: классический CAN или CAN FD
+pub struct PoeCanableCodec {
+  mode: CanMode,
+}
+
+impl PoeCanableCodec {
+  /// Протокол POECanable (классический CAN)
+  pub fn can() -> Self {
+    Self { mode: CanMode::Classic }
+  }
+
+  /// Протокол POECanableFD
+  pub fn can_fd() -> Self {
+    Self { mode: CanMode::Fd }
+  }
+}
+
+/// Декодер POECanable, накапливающий незавершённые строки SLCAN
+struct PoeCanableDecoder {
+  buffer: String,
+  port_path: String,
+  filters: Vec<CanFilter>,
+}
+
+impl ProtocolDecoder for PoeCanableDecoder {
+  fn decode(&mut self, data: &[u8]) -> Vec<serde_json::Value> {
+    self.buffer.push_str(&String::from_utf8_lossy(data));
+
+    let mut messages = Vec::new();
+    self.buffer = match process_poe_canable_data(&self.buffer, &mut messages, &self.port_path, &self.filters) {
+      Ok(remaining) => remaining,
+      Err(e) => {
+        log(LogLevel::Err, "PoeCanableDecoder", format!("Ошибка обработки данных: {}", e));
+        String::new()
+      },
+    };
+    messages
+      .into_iter()
+      .filter_map(|message| serde_json::to_value(message).ok())
+      .collect()
+  }
+}
+
+/// Команда скорости адаптера из конфигурации
+fn bitrate_command(bitrate: &Option<String>, label: &str) -> Result<Vec<u8>, String> {
+  bitrate
+    .as_ref()
+    .map(|bitrate| format!("{}\r", bitrate).into_bytes())
+    .ok_or_else(|| {
+      log(LogLevel::Err, "PoeCanableCodec", format!("Не задана скорость {}", label));
+      format!("{} is not set", label)
+    })
+}
+
+impl ProtocolCodec for PoeCanableCodec {
+  fn name(&self) -> &str {
+    self.mode.protocol()
+  }
+
+  fn description(&self) -> &str {
+    match self.mode {
+      CanMode::Classic => "POE packets over a CAN adapter with SLCAN firmware",
+      CanMode::Fd => "POE packets over a CAN FD adapter with SLCAN firmware",
+    }
+  }
+
+  fn parameters(&self) -> serde_json::Value {
+    serde_json::json!({
+      "type": "object",
+      "properties": {
+        "header": { "type": "integer", "minimum": 0, "maximum": 3 },
+        "argument": { "type": "integer", "minimum": 0, "maximum": 1023 },
+        "target_id": { "type": "integer", "minimum": 0, "maximum": 255 },
+        "return_id": { "type": "integer", "minimum": 0, "maximum": 255 },
+        "convert_to_base64": { "type": "integer", "enum": [0, 1] },
+        "data": { "type": ["string", "null"] },
+        "brs": { "type": ["boolean", "null"] }
+      },
+      "required": ["header", "argument", "target_id", "return_id", "convert_to_base64"]
+    })
+  }
+
+  fn encode(&self, _config: &SerialConfig, command: serde_json::Value) -> Result<Vec<Vec<u8>>, String> {
+    let encoded = encode_poe_canable_command(self.mode, command)?;
+    Ok(encoded.frames.into_iter().map(String::into_bytes).collect())
+  }
+
+  fn decoder(&self, _app: &AppHandle<Wry>, config: &SerialConfig) -> Box<dyn ProtocolDecoder> {
+    Box::new(PoeCanableDecoder {
+      buffer: String::new(),
+      port_path: config.path.clone(),
+      filters: config.can_filters.clone().unwrap_or_default(),
+    })
+  }
+
+  /// Закрытие канала, скорость, режим, автоповтор, временные метки, фильтр и открытие канала адаптера
+  fn init_sequence(&self, config: &SerialConfig) -> Result<Vec<Vec<u8>>, String> {
+    let mut sequence = vec![b"C\r".to_vec()];
+    match self.mode {
+      CanMode::Classic => sequence.push(bitrate_command(&config.can_bitrate, "CAN bitrate")?),
+      CanMode::Fd => {
+        sequence.push(bitrate_command(&config.canfd_bitrate, "CANFD bitrate")?);
+        sequence.push(bitrate_command(&config.canfd_data_bitrate, "CANFD data bitrate")?);
+      },
+    }
+    sequence.extend([b"M0\r".to_vec(), b"A0\r".to_vec()]);
+    // Z1 задаётся при закрытом канале: адаптер добавляет к фреймам метку времени в мс
+    if config.can_timestamps.unwrap_or(false) {
+      sequence.push(b"Z1\r".to_vec());
+    }
+    // Фильтр приёма адаптера задаётся после определения прошивки (apply_adapter_filter)
+    sequence.push(b"O\r".to_vec());
+    Ok(sequence)
+  }
+
+  fn reset_port(&self, port_path: &str) {
+    reset_port_state(port_path);
+  }
+
+  fn teardown_sequence(&self, _config: &SerialConfig) -> Vec<Vec<u8>> {
+    vec![b"C\r".to_vec()]
+  }
+
+  fn send(&self, app: &AppHandle<Wry>, transport: &dyn Transport, _config: &SerialConfig, command: serde_json::Value) -> Result<(), String> {
+    send_poe_canable_command(app.clone(), transport, self.mode, command)
+  }
+
+  fn send_awaiting_reply(
+    &self,
+    app: &AppHandle<Wry>,
+    transport: &dyn Transport,
+    _config: &SerialConfig,
+    command: serde_json::Value,
+    timeout: Duration,
+  ) -> Result<(), AttemptError> {
+    send_poe_canable_command_awaiting_reply(app.clone(), transport, self.mode, command, timeout)
+  }
+
+  fn supports_reply(&self) -> bool {
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const HOST_US: u64 = 1_700_000_000_000_000;
+
+  #[test]
+  fn first_batch_is_anchored_on_its_last_frame() {
+    let port = "test-anchor";
+    reset_port_state(port);
+
+    let mapped: Vec<u64> = [1_000, 1_010, 1_020]
+      .into_iter()
+      .map(|device_ms| map_device_timestamp(port, device_ms, 1_020, HOST_US))
+      .collect();
+    assert_eq!(mapped, [HOST_US - 20_000, HOST_US - 10_000, HOST_US]);
+  }
+
+  #[test]
+  fn later_frames_follow_device_clock_across_wraparound() {
+    let port = "test-wrap";
+    reset_port_state(port);
+
+    assert_eq!(map_device_timestamp(port, 59_990, 59_990, HOST_US), HOST_US);
+    // Счётчик адаптера обнулился: 59_990 -> 10 соответствует 20 мс
+    assert_eq!(map_device_timestamp(port, 10, 10, HOST_US + 25_000), HOST_US + 20_000);
+    // Метка не опережает часы хоста
+    assert_eq!(map_device_timestamp(port, 110, 110, HOST_US + 50_000), HOST_US + 50_000);
+  }
+
+  #[test]
+  fn lagging_clock_is_reanchored_and_reset_clears_it() {
+    let port = "test-lag";
+    reset_port_state(port);
+
+    assert_eq!(map_device_timestamp(port, 100, 100, HOST_US), HOST_US);
+    let later_us = HOST_US + 5_000_000;
+    assert_eq!(map_device_timestamp(port, 200, 200, later_us), later_us);
+
+    reset_port_state(port);
+    assert!(!SLCAN_CLOCKS.lock().unwrap().contains_key(port));
+    assert_eq!(map_device_timestamp(port, 300, 310, later_us), later_us - 10_000);
+  }
+
+  #[test]
+  fn only_trailing_partial_line_is_kept() {
+    let port = "test-buffer";
+    let mut messages = Vec::new();
+
+    let remaining = process_poe_canable_data("\r\r\x07F00\rV1013\rT00000", &mut messages, port, &[]).unwrap();
+    assert_eq!(remaining, "T00000");
+
+    let remaining = process_poe_canable_data("F00\rT0000012320102\rz\rv10", &mut messages, port, &[]).unwrap();
+    assert_eq!(remaining, "v10");
+
+    let remaining = process_poe_canable_data("N1234\r", &mut messages, port, &[]).unwrap();
+    assert_eq!(remaining, "");
+    reset_port_state(port);
+  }
+
+  #[test]
+  fn device_timestamp_is_taken_after_data_bytes() {
+    let regex = Regex::new(r"([tTrRdDbB])([0-9A-F]{3,8})([0-9A-F])([0-9A-F]*)\r").unwrap();
+    let timestamp = |frame: &str| frame_device_timestamp(&regex.captures(frame).unwrap());
+
+    assert_eq!(timestamp("T000001232AABB1F40\r"), Some(0x1F40));
+    assert_eq!(timestamp("T000001232AABB\r"), None);
+    assert_eq!(timestamp("R0000012380010\r"), Some(0x0010));
+    // DLC 9 у CAN FD - 12 байт данных
+    assert_eq!(timestamp(&format!("B000001239{}EA5F\r", "00".repeat(12))), Some(0xEA5F));
+  }
+}
@@ -1,15 +1,22 @@
-use lazy_static::lazy_static;
-use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use tauri::ipc::Channel;
-use tauri::{command, AppHandle, Listener, State, Wry};
-use tauri_plugin_serialplugin::commands::write;
-use tauri_plugin_serialplugin::desktop_api::SerialPort;
-
-use crate::{log, LogLevel, ReadDataResult};
-
-/* [SOH] HEADER [US] ARGUMENT [STX] VALUE [ETX] CRC8 [EOT] */
+use tauri::{command, AppHandle, Manager, Wry};
+
+use crate::codec::{subscribe_decoder, ProtocolCodec, ProtocolDecoder};
+use crate::command_retry::AttemptError;
+use crate::connections::{ConnectionRegistry, ConnectionStats};
+use crate::dictionary::{dictionary, resolve_names, validate_serial_command};
+use crate::models::SerialConfig;
+use crate::poe_serial_framer::{PoeSerialFramer, RawPoeSerialPacket};
+use crate::poe_serial_transaction::{transact, PoeSerialTransactError};
+use crate::schemas::validate_named_value;
+use crate::telemetry::{record_free_heap, HeapTelemetry};
+use crate::transports::Transport;
+use crate::{log, LogLevel};
+
+/* [SOH] HEADER [US] ARGUMENT [STX] VALUE [ETX] CRC8 [US] FREE_HEAP [EOT] */
 
 /// Структура данных для протокола POESerial
 #[derive(serde::Serialize, Clone)]
@@ -17,8 +24,17 @@ pub struct PoeSerialData {
   header: String,
   argument: String,
   value: String,
+  value_bytes: Vec<u8>,
   crc_hex: String,
+  crc_ok: bool,
   free_heap_size: String,
+  /// Коды заголовка и аргумента из словаря, `None` - имя не найдено
+  header_code: Option<u32>,
+  argument_code: Option<u32>,
+  /// Заголовок и аргумент найдены в словаре
+  is_known: bool,
+  /// Ошибка проверки значения ответа OK! по схеме аргумента
+  schema_error: Option<String>,
 }
 
 /// Структура команды для протокола POESerial
@@ -27,209 +43,140 @@ struct PoeSerialCommand {
   header: String,
   argument: String,
   value: String,
+  /// Двоичное значение, передаётся вместо `value` (только в режиме экранирования)
+  value_bytes: Option<Vec<u8>>,
 }
 
 /// Константы управляющих символов для протокола POESerial
-const SOH: u8 = 0x01;
-const STX: u8 = 0x02;
-const ETX: u8 = 0x03;
-const EOT: u8 = 0x04;
-const US: u8 = 0x1F;
-
-/// Структура для хранения частичного пакета с временной меткой
-#[derive(Debug)]
-struct PartialPacket {
-  data: String,
-  timestamp: Instant,
-}
+pub(crate) const SOH: u8 = 0x01;
+pub(crate) const STX: u8 = 0x02;
+pub(crate) const ETX: u8 = 0x03;
+pub(crate) const EOT: u8 = 0x04;
+pub(crate) const US: u8 = 0x1F;
 
-lazy_static! {
-  static ref DATA_BUFFERS: Arc<Mutex<HashMap<String, Vec<PoeSerialData>>>> = Arc::new(Mutex::new(HashMap::new()));
-  static ref PARTIAL_PACKETS: Arc<Mutex<HashMap<String, PartialPacket>>> = Arc::new(Mutex::new(HashMap::new()));
-}
+/// Символ экранирования (DLE) и маска, которой инвертируется экранированный байт
+pub(crate) const DLE: u8 = 0x10;
+pub(crate) const ESCAPE_MASK: u8 = 0x20;
 
 /// Обрабатывает принятые данные по протоколу POESerial и отправляет их через канал
 ///
+/// Пакеты выделяются из потока байт фреймером, поле VALUE передаётся как есть (`value_bytes`)
+/// и в виде строки UTF-8 с заменой некорректных последовательностей (`value`).
+/// Если в конфигурации порта включён `poe_serial_escaped`, управляющие символы внутри полей
+/// принимаются в экранированном виде (DLE). Режим не согласуется с устройством и должен
+/// совпадать с прошивкой: при расхождении пакеты с управляющими символами в VALUE отбрасываются.
+/// CRC8 каждого принятого пакета сверяется с рассчитанным, результат передаётся в поле `crc_ok`.
+/// Если в конфигурации порта включён `drop_corrupt_packets`, пакеты с неверной CRC отбрасываются.
+/// Свободная память устройства из пакетов с верной CRC записывается в телеметрию порта.
+///
 /// # Arguments
 /// * `app` - дескриптор приложения Tauri
 /// * `port_path` - путь к серийному порту
-/// * `on_event` - канал для отправки обработанных данных (Vec<PoeSerialData>)
+/// * `on_event` - канал для отправки обработанных данных (пакеты `PoeSerialData` в виде JSON)
 ///
 /// # Returns
 /// * `Ok(u32)` - ID события прослушивания
 /// * `Err(String)` - ошибка при создании слушателя
 #[command]
-pub fn process_poe_serial(app: AppHandle<Wry>, port_path: String, on_event: Channel<Vec<PoeSerialData>>) -> Result<u32, String> {
-  let app_clone = app.clone();
-
-  // Создаём буфер для накопления данных
-  let buffer: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
-  let buffer_clone = buffer.clone();
-
-  // Форматируем путь порта для использования в имени события
-  let formatted_port_path = port_path
-    .clone()
-    .replace(".", "-")
-    .replace("/", "-")
-    .replace("\\", "-");
-  log(
-    LogLevel::Info,
-    "process_poe_serial",
-    format!("Форматированный путь порта: {}", formatted_port_path),
-  );
-  let listen_event_name = format!("plugin-serialplugin-read-{}", formatted_port_path);
-
-  let event_id = app_clone.clone().listen(listen_event_name, move |event| {
-    // Разбираем полезную нагрузку события
-    if let Ok(payload) = serde_json::from_str::<ReadDataResult>(&event.payload()) {
-      // Преобразуем байты в строку
-      let data_str = match String::from_utf8(payload.data.clone()) {
-        Ok(s) => s,
-        Err(_) => String::from_utf8_lossy(&payload.data).to_string(),
-      };
-      log(LogLevel::Info, "process_poe_serial", format!("Данные в виде строки: {}", data_str));
-
-      // Добавляем данные в буфер
-      {
-        let mut buffer_guard = buffer_clone.lock().unwrap();
-        buffer_guard.push_str(&data_str);
-        log(
-          LogLevel::Info,
-          "process_poe_serial",
-          format!("Буфер обновлён, текущий размер: {}", buffer_guard.len()),
-        );
-        drop(buffer_guard);
-      }
-
-      // Обработка буфера
-      let processed_remaining = {
-        let buffer_guard = buffer_clone.lock().unwrap();
-        let data = buffer_guard.clone();
-        drop(buffer_guard);
-
-        // Обрабатываем данные
-        match process_poe_serial_data(&data, &on_event, &port_path) {
-          Ok(remaining) => remaining,
-          Err(e) => {
-            log(LogLevel::Err, "process_poe_serial", format!("Ошибка обработки данных: {}", e));
-            String::new()
-          },
-        }
-      };
-
-      // Сохраняем остаток в буфере
-      let mut buffer_guard = buffer_clone.lock().unwrap();
-      *buffer_guard = processed_remaining;
+pub fn process_poe_serial(app: AppHandle<Wry>, port_path: String, on_event: Channel<Vec<serde_json::Value>>) -> Result<u32, String> {
+  subscribe_decoder(&app, &port_path, &["POESerial"], "process_poe_serial", move |packets| {
+    log(LogLevel::Info, "process_poe_serial", format!("Отправка {} пакетов через канал", packets.len()));
+    if let Err(e) = on_event.send(packets) {
+      log(LogLevel::Err, "process_poe_serial", format!("Ошибка отправки через канал: {}", e));
     }
-  });
-
-  Ok(event_id)
+  })
 }
 
 /// Вспомогательная функция для обработки данных POESerial
 ///
 /// # Arguments
-/// * `data` - строка с данными для обработки
-/// * `on_event` - канал для отправки обработанных данных
-/// * `port_path` - путь к порту (для логирования и работы с частичными пакетами)
+/// * `data` - принятые байты
+/// * `framer` - фреймер порта
+/// * `port_path` - путь к порту (для логирования)
+/// * `drop_corrupt` - отбрасывать пакеты с неверной CRC
+/// * `stats` - счётчики порта для учёта принятых пакетов и ошибок CRC
 ///
 /// # Returns
-/// * `Ok(String)` - оставшиеся необработанные данные
-/// * `Err(String)` - ошибка обработки
-fn process_poe_serial_data(data: &str, on_event: &Channel<Vec<PoeSerialData>>, port_path: &str) -> Result<String, String> {
+/// * `Vec<PoeSerialData>` - разобранные пакеты для отправки
+pub(crate) fn process_poe_serial_data(
+  data: &[u8],
+  framer: &mut PoeSerialFramer,
+  port_path: &str,
+  drop_corrupt: bool,
+  stats: Option<&ConnectionStats>,
+) -> Vec<PoeSerialData> {
   log(
     LogLevel::Info,
     "process_poe_serial_data",
     format!("Начало обработки POESerial данных для порта: {}", port_path),
   );
 
-  let current_time = Instant::now();
-
-  // Очистка устаревших частичных пакетов (старше 0.5 секунды)
-  {
-    let mut partial_packets = PARTIAL_PACKETS.lock().unwrap();
-    partial_packets.retain(|_, packet| current_time.duration_since(packet.timestamp) < Duration::from_millis(500));
-  }
+  let mut packets_to_send = Vec::new();
 
-  let mut remaining_data = data.to_string();
-  let mut partial_data = String::new();
-  {
-    let mut partial_packets = PARTIAL_PACKETS.lock().unwrap();
-    if let Some(partial_packet) = partial_packets.remove(port_path) {
-      if current_time.duration_since(partial_packet.timestamp) < Duration::from_millis(500) {
-        partial_data = partial_packet.data;
-        log(LogLevel::Info, "process_poe_serial_data", format!("Восстановлены частичные данные из буфера"));
+  for packet in framer.push_bytes(data, port_path) {
+    // Проверяем CRC пакета
+    let crc_ok = verify_crc8(&packet);
+    if let Some(stats) = stats {
+      stats.packets_received.fetch_add(1, Ordering::Relaxed);
+      if !crc_ok {
+        stats.crc_errors.fetch_add(1, Ordering::Relaxed);
       }
     }
-  }
-
-  partial_data.push_str(&remaining_data);
-  remaining_data = partial_data;
-  log(
-    LogLevel::Info,
-    "process_poe_serial_data",
-    format!("Данные после объединения с частичными: {}", remaining_data),
-  );
-
-  let mut packets_to_send = Vec::new();
-
-  // Обрабатываем пакеты в данных
-  while remaining_data.as_bytes().contains(&SOH) && remaining_data.as_bytes().contains(&EOT) {
-    // Поиск начала и конца пакета
-    let eot_index = match remaining_data.find(EOT as char) {
-      Some(index) => index,
-      None => {
-        log(LogLevel::Info, "process_poe_serial_data", format!("Не найден символ EOT, прерывание цикла"));
-        break;
-      },
-    };
-    let soh_index = match remaining_data.find(SOH as char) {
-      Some(index) => index,
-      None => {
-        log(LogLevel::Info, "process_poe_serial_data", format!("Не найден символ SOH, прерывание цикла"));
-        break;
-      },
-    };
 
-    if soh_index >= eot_index {
-      log(LogLevel::Warn, "process_poe_serial_data", format!("Нарушен порядок SOH и EOT, пропуск пакета"));
-
-      break;
+    let header = String::from_utf8_lossy(&packet.header).to_string();
+    let argument = String::from_utf8_lossy(&packet.argument).to_string();
+    let (header_code, argument_code) = resolve_names(&header, &argument);
+    if header_code.is_none() || argument_code.is_none() {
+      log(
+        LogLevel::Warn,
+        "process_poe_serial_data",
+        format!("Имена отсутствуют в словаре: заголовок {}, аргумент {}", header, argument),
+      );
     }
 
-    // Выделяем полный пакет
-    let packet = remaining_data[soh_index..=eot_index].to_string();
-
-    log(LogLevel::Info, "process_poe_serial_data", format!("Найден пакет: {}", packet));
-
-    if !packet.as_bytes().contains(&US) || !packet.as_bytes().contains(&STX) || !packet.as_bytes().contains(&ETX) {
+    // Значение ответа проверяется по схеме аргумента, если она задана
+    let schema_error = if header == "OK!" && !packet.value.is_empty() {
+      validate_named_value(&argument, &packet.value).err()
+    } else {
+      None
+    };
+    if let Some(error) = &schema_error {
       log(
-        LogLevel::Info,
+        LogLevel::Warn,
         "process_poe_serial_data",
-        format!("Пакет не содержит необходимые разделители, пропуск"),
+        format!("Значение ответа на порту {} не прошло проверку по схеме: {}", port_path, error),
       );
-      remaining_data = remaining_data[eot_index + 1..].to_string();
-      continue;
     }
 
-    let trimmed_packet = packet[packet.find(ETX as char).unwrap()..].to_string();
-
-    // Разделение пакета по элементам
-    let header = packet[1..packet.find(US as char).unwrap()].to_string();
-    let argument = packet[packet.find(US as char).unwrap() + 1 as usize..packet.find(STX as char).unwrap()].to_string();
-    let value = packet[packet.find(STX as char).unwrap() + 1 as usize..packet.find(ETX as char).unwrap()].to_string();
-    let crc_hex = trimmed_packet[trimmed_packet.find(ETX as char).unwrap() + 1 as usize..trimmed_packet.find(US as char).unwrap()].to_string();
-    let free_heap_size = trimmed_packet[trimmed_packet.find(US as char).unwrap() + 1 as usize..trimmed_packet.find(EOT as char).unwrap()].to_string();
-
-    // Создаём структуру данных пакета
     let serial_data = PoeSerialData {
       header,
       argument,
-      value,
-      crc_hex,
-      free_heap_size,
+      value: String::from_utf8_lossy(&packet.value).to_string(),
+      value_bytes: packet.value,
+      crc_hex: String::from_utf8_lossy(&packet.crc).to_string(),
+      crc_ok,
+      free_heap_size: String::from_utf8_lossy(&packet.free_heap).to_string(),
+      header_code,
+      argument_code,
+      is_known: header_code.is_some() && argument_code.is_some(),
+      schema_error,
     };
 
+    if !crc_ok {
+      log(
+        LogLevel::Warn,
+        "process_poe_serial_data",
+        format!(
+          "Неверная CRC пакета ({}) на порту {}: argument={}",
+          serial_data.crc_hex, port_path, serial_data.argument
+        ),
+      );
+      if drop_corrupt {
+        log(LogLevel::Warn, "process_poe_serial_data", format!("Пакет с неверной CRC отброшен"));
+        continue;
+      }
+    }
+
     log(
       LogLevel::Info,
       "process_poe_serial_data",
@@ -239,126 +186,258 @@ fn process_poe_serial_data(data: &str, on_event: &Channel<Vec<PoeSerialData>>, p
       ),
     );
 
-    log(
-      LogLevel::Info,
-      "process_poe_serial_data",
-      format!("Режим отправки: добавление пакета в очередь для отправки"),
-    );
-
     packets_to_send.push(serial_data);
+  }
 
-    // Удаляем обработанный пакет из remaining_data
-    remaining_data = remaining_data[eot_index + 1..].to_string();
-    log(
-      LogLevel::Info,
-      "process_poe_serial_data",
-      format!("Оставшиеся данные после обработки пакета: {}", remaining_data),
-    );
+  log(LogLevel::Info, "process_poe_serial_data", format!("Обработка POESerial завершена"));
+  packets_to_send
+}
+
+/// Рассчитывает CRC8 (полином 0x8C) для полей HEADER, ARGUMENT и VALUE пакета POESerial
+///
+/// # Arguments
+/// * `data` - байты полей пакета, записанные подряд
+///
+/// # Returns
+/// * `u8` - значение CRC8
+pub(crate) fn calculate_crc8(data: &[u8]) -> u8 {
+  let mut crc: u8 = 0x00;
+
+  for &byte in data {
+    let mut extract = byte;
+
+    for _ in 0..8 {
+      let sum = (crc ^ extract) & 0x01;
+      crc >>= 1;
+      if sum != 0 {
+        crc ^= 0x8C;
+      }
+      extract >>= 1;
+    }
   }
 
-  // Отправляем все накопленные пакеты через канал
-  if !packets_to_send.is_empty() {
-    log(
-      LogLevel::Info,
-      "process_poe_serial_data",
-      format!("Отправка {} пакетов через канал", packets_to_send.len()),
-    );
-    on_event.send(packets_to_send).map_err(|e| e.to_string())?;
+  crc
+}
+
+/// Сверяет CRC8 принятого пакета с рассчитанной по полям HEADER, ARGUMENT и VALUE
+///
+/// # Arguments
+/// * `packet` - принятый пакет
+///
+/// # Returns
+/// * `bool` - `true`, если CRC совпадает
+fn verify_crc8(packet: &RawPoeSerialPacket) -> bool {
+  let crc_hex = String::from_utf8_lossy(&packet.crc);
+  match u8::from_str_radix(crc_hex.trim(), 16) {
+    Ok(received) => received == calculate_crc8(&packet.checksum_data()),
+    Err(_) => false,
   }
+}
 
-  // Если остались неполные данные, сохраняем их с таймстампом
-  if !remaining_data.is_empty() {
-    log(
-      LogLevel::Info,
-      "process_poe_serial_data",
-      format!("Сохранение частичных данных в буфере: {}", remaining_data),
-    );
+/// Дописывает поле пакета, экранируя управляющие символы в режиме экранирования
+///
+/// Каждый из байт SOH, STX, ETX, EOT, US и DLE заменяется парой DLE, байт ^ 0x20,
+/// поэтому внутри экранированного пакета управляющие символы встречаются только как разделители.
+fn push_field(packet: &mut Vec<u8>, field: &[u8], escaped: bool) {
+  for &byte in field {
+    if escaped && matches!(byte, SOH | STX | ETX | EOT | US | DLE) {
+      packet.push(DLE);
+      packet.push(byte ^ ESCAPE_MASK);
+    } else {
+      packet.push(byte);
+    }
+  }
+}
 
-    let mut partial_packets = PARTIAL_PACKETS.lock().unwrap();
-    partial_packets.insert(
-      port_path.to_string(),
-      PartialPacket {
-        data: remaining_data.clone(),
-        timestamp: current_time,
-      },
-    );
-    remaining_data.clear();
+/// Формирует пакет POESerial
+///
+/// CRC8 рассчитывается по неэкранированным полям HEADER, ARGUMENT и VALUE.
+///
+/// # Arguments
+/// * `header` - заголовок пакета
+/// * `argument` - аргумент пакета
+/// * `value` - значение пакета
+/// * `free_heap` - размер свободной памяти (только в ответах устройства)
+/// * `escaped` - экранировать управляющие символы внутри полей
+///
+/// # Returns
+/// * `Vec<u8>` - байты пакета
+pub(crate) fn encode_poe_serial_packet(header: &[u8], argument: &[u8], value: &[u8], free_heap: Option<u32>, escaped: bool) -> Vec<u8> {
+  let mut checksum_data = Vec::with_capacity(header.len() + argument.len() + value.len());
+  checksum_data.extend_from_slice(header);
+  checksum_data.extend_from_slice(argument);
+  checksum_data.extend_from_slice(value);
+  let crc = calculate_crc8(&checksum_data);
+
+  let mut packet = Vec::with_capacity(checksum_data.len() + 16);
+  packet.push(SOH);
+  push_field(&mut packet, header, escaped);
+  packet.push(US);
+  push_field(&mut packet, argument, escaped);
+  packet.push(STX);
+  push_field(&mut packet, value, escaped);
+  packet.push(ETX);
+  packet.extend_from_slice(format!("{:02X}", crc).as_bytes());
+  if let Some(free_heap) = free_heap {
+    packet.push(US);
+    packet.extend_from_slice(free_heap.to_string().as_bytes());
   }
+  packet.push(EOT);
+  packet
+}
 
-  log(LogLevel::Info, "process_poe_serial_data", format!("Обработка POESerial завершена"));
-  Ok(remaining_data)
+/// Проверяет, содержит ли поле управляющие символы POESerial
+pub(crate) fn contains_control_bytes(field: &[u8]) -> bool {
+  field
+    .iter()
+    .any(|byte| matches!(*byte, SOH | STX | ETX | EOT | US))
 }
 
-/// Отправляет команду по протоколу POESerial в серийный порт
+/// Формирует пакет POESerial из полей
 ///
 /// # Arguments
-/// * `app` - дескриптор приложения Tauri
-/// * `serial` - состояние серийного порта
-/// * `port_path` - путь к серийному порту
-/// * `sending_data` - JSON-объект с командой
+/// * `header` - заголовок пакета
+/// * `argument` - аргумент пакета
+/// * `value` - значение пакета
+/// * `escaped` - экранировать управляющие символы внутри полей
 ///
 /// # Returns
-/// * `Ok(())` - команда успешно отправлена
-/// * `Err(String)` - ошибка отправки
-pub fn send_poe_serial_command(app: AppHandle, serial: State<'_, SerialPort<Wry>>, port_path: String, sending_data: serde_json::Value) -> Result<(), String> {
+/// * `Ok(Vec<u8>)` - пакет для записи в порт
+/// * `Err(String)` - поля содержат управляющие символы без экранирования
+pub(crate) fn build_poe_serial_packet(header: &[u8], argument: &[u8], value: &[u8], escaped: bool) -> Result<Vec<u8>, String> {
+  // Без экранирования управляющие символы в полях нарушат разбор пакета на устройстве
+  if !escaped {
+    if [header, argument, value]
+      .iter()
+      .any(|field| contains_control_bytes(field))
+    {
+      log(
+        LogLevel::Err,
+        "build_poe_serial_packet",
+        format!("Команда содержит управляющие символы, а экранирование на порту выключено"),
+      );
+      return Err("Command contains control characters, enable escaped mode to send them".to_string());
+    }
+  }
+
+  // Формируем посылку
+  let packet = encode_poe_serial_packet(header, argument, value, None, escaped);
+
   log(
     LogLevel::Info,
-    "send_poe_serial_command",
-    format!("Начало отправки POESerial команды на порт: {}", port_path),
+    "build_poe_serial_packet",
+    format!(
+      "Сформированный пакет для отправки (экранирование: {}): {}",
+      escaped,
+      String::from_utf8_lossy(&packet)
+    ),
   );
 
+  Ok(packet)
+}
+
+/// Формирует пакет POESerial и записывает его в порт
+///
+/// # Arguments
+/// * `transport` - транспорт открытого порта
+/// * `header` - заголовок пакета
+/// * `argument` - аргумент пакета
+/// * `value` - значение пакета
+/// * `escaped` - экранировать управляющие символы внутри полей
+///
+/// # Returns
+/// * `Ok(())` - пакет записан
+/// * `Err(String)` - поля содержат управляющие символы без экранирования или ошибка записи
+pub(crate) fn write_poe_serial_packet(transport: &dyn Transport, header: &[u8], argument: &[u8], value: &[u8], escaped: bool) -> Result<(), String> {
+  let packet = build_poe_serial_packet(header, argument, value, escaped)?;
+
+  transport.write(&packet).map_err(|e| {
+    log(LogLevel::Err, "write_poe_serial_packet", format!("Не удалось записать данные в порт: {}", e));
+    format!("Failed to write: {}", e)
+  })?;
+
+  Ok(())
+}
+
+/// Кодирует команду POESerial в пакет после проверки по словарю и схеме значения
+///
+/// # Arguments
+/// * `sending_data` - JSON-объект с командой
+/// * `escaped` - экранировать управляющие символы внутри полей (режим порта `poe_serial_escaped`)
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - пакет для записи в порт
+/// * `Err(String)` - команда некорректна
+pub(crate) fn encode_poe_serial_command(sending_data: serde_json::Value, escaped: bool) -> Result<Vec<u8>, String> {
   // Разбираем JSON в структуру команды
   let command: PoeSerialCommand = serde_json::from_value(sending_data).map_err(|e| {
     log(
       LogLevel::Err,
-      "send_poe_serial_command",
+      "encode_poe_serial_command",
       format!("Не удалось разобрать команду POESerial: {}", e),
     );
-    format!("Failed to parse simple serial command: {}", e)
+    format!("Failed to parse POESerial command: {}", e)
   })?;
 
   log(
     LogLevel::Info,
-    "send_poe_serial_command",
+    "encode_poe_serial_command",
     format!(
       "Разобранные данные команды: header={}, argument={}, value={}",
       command.header, command.argument, command.value
     ),
   );
 
-  let mut crc: u8 = 0x00;
-  let data_to_checksum = format!("{}{}{}", command.header, command.argument, command.value);
+  validate_serial_command(&command.header, &command.argument).map_err(|e| {
+    log(
+      LogLevel::Err,
+      "encode_poe_serial_command",
+      format!("Команда не прошла проверку по словарю: {}", e),
+    );
+    e
+  })?;
 
-  // Расчет CRC
-  for ch in data_to_checksum.chars() {
-    let byte = ch as u8;
-    let mut extract = byte;
+  let PoeSerialCommand {
+    header,
+    argument,
+    value,
+    value_bytes,
+  } = command;
+  let value = value_bytes.unwrap_or_else(|| value.into_bytes());
 
-    for _ in 0..8 {
-      let sum = (crc ^ extract) & 0x01;
-      crc >>= 1;
-      if sum != 0 {
-        crc ^= 0x8C;
-      }
-      extract >>= 1;
-    }
+  if header == "SET" {
+    validate_named_value(&argument, &value).map_err(|e| {
+      log(
+        LogLevel::Err,
+        "encode_poe_serial_command",
+        format!("Значение не прошло проверку по схеме: {}", e),
+      );
+      e
+    })?;
   }
 
-  log(LogLevel::Info, "send_poe_serial_command", format!("Рассчитанное CRC: 0x{:02X}", crc));
-
-  // Формируем посылку
-  let formatted_str = format!(
-    "{}{}{}{}{}{}{}{:02X}{}",
-    SOH as char, command.header, US as char, command.argument, STX as char, command.value, ETX as char, crc, EOT as char
-  );
+  build_poe_serial_packet(header.as_bytes(), argument.as_bytes(), &value, escaped)
+}
 
+/// Отправляет команду по протоколу POESerial в серийный порт
+///
+/// # Arguments
+/// * `transport` - транспорт открытого порта
+/// * `sending_data` - JSON-объект с командой
+/// * `escaped` - экранировать управляющие символы внутри полей (режим порта `poe_serial_escaped`)
+///
+/// # Returns
+/// * `Ok(())` - команда успешно отправлена
+/// * `Err(String)` - ошибка отправки
+pub fn send_poe_serial_command(transport: &dyn Transport, sending_data: serde_json::Value, escaped: bool) -> Result<(), String> {
   log(
     LogLevel::Info,
     "send_poe_serial_command",
-    format!("Сформированная строка для отправки: {}", formatted_str),
+    format!("Начало отправки POESerial команды на порт: {}", transport.path()),
   );
 
-  write(app.clone(), serial.clone(), port_path.clone(), formatted_str.clone()).map_err(|e| {
+  let packet = encode_poe_serial_command(sending_data, escaped)?;
+  transport.write(&packet).map_err(|e| {
     log(LogLevel::Err, "send_poe_serial_command", format!("Не удалось записать данные в порт: {}", e));
     format!("Failed to write: {}", e)
   })?;
@@ -366,3 +445,174 @@ pub fn send_poe_serial_command(app: AppHandle, serial: State<'_, SerialPort<Wry>
   log(LogLevel::Info, "send_poe_serial_command", format!("Команда POESerial успешно отправлена"));
   Ok(())
 }
+
+/// Отправляет команду по протоколу POESerial и ждёт ответ устройства
+///
+/// Ответ ожидается только на команды GET и SET, остальные отправляются без ожидания.
+///
+/// # Arguments
+/// * `transport` - транспорт открытого порта
+/// * `sending_data` - JSON-объект с командой
+/// * `escaped` - экранировать управляющие символы внутри полей
+/// * `timeout` - время ожидания ответа
+///
+/// # Returns
+/// * `Ok(())` - устройство ответило OK!
+/// * `Err(AttemptError)` - ошибка попытки: `Retry` при отсутствии ответа или неверной CRC
+pub(crate) fn send_poe_serial_command_awaiting_reply(
+  transport: &dyn Transport,
+  sending_data: serde_json::Value,
+  escaped: bool,
+  timeout: Duration,
+) -> Result<(), AttemptError> {
+  let command: PoeSerialCommand =
+    serde_json::from_value(sending_data.clone()).map_err(|e| AttemptError::Fail(format!("Failed to parse POESerial command: {}", e)))?;
+
+  if command.header != "GET" && command.header != "SET" {
+    return send_poe_serial_command(transport, sending_data, escaped).map_err(AttemptError::Fail);
+  }
+
+  let value = command
+    .value_bytes
+    .unwrap_or_else(|| command.value.into_bytes());
+  match transact(transport, escaped, &command.header, &command.argument, &value, timeout) {
+    Ok(_) => Ok(()),
+    Err(e @ PoeSerialTransactError::Timeout(_)) | Err(e @ PoeSerialTransactError::Crc(_)) => Err(AttemptError::Retry(e.to_string())),
+    Err(e) => Err(AttemptError::Fail(e.to_string())),
+  }
+}
+
+/// Протокол POESerial: пакеты GET/SET с ответами OK!/ER! и CRC8
+pub struct PoeSerialCodec;
+
+/// Декодер POESerial на основе фреймера порта
+///
+/// Принятые пакеты учитываются в счётчиках порта, свободная память из пакетов с верной CRC
+/// записывается в телеметрию порта.
+struct PoeSerialDecoder {
+  app: AppHandle<Wry>,
+  framer: PoeSerialFramer,
+  port_path: String,
+  drop_corrupt: bool,
+  stats: Option<Arc<ConnectionStats>>,
+  heap_telemetry: Option<Arc<Mutex<HeapTelemetry>>>,
+}
+
+impl ProtocolDecoder for PoeSerialDecoder {
+  fn decode(&mut self, data: &[u8]) -> Vec<serde_json::Value> {
+    let packets = process_poe_serial_data(data, &mut self.framer, &self.port_path, self.drop_corrupt, self.stats.as_deref());
+    if let Some(heap_telemetry) = &self.heap_telemetry {
+      for packet in packets.iter().filter(|packet| packet.crc_ok) {
+        record_free_heap(&self.app, heap_telemetry, &self.port_path, &packet.free_heap_size);
+      }
+    }
+    packets
+      .into_iter()
+      .filter_map(|packet| serde_json::to_value(packet).ok())
+      .collect()
+  }
+}
+
+/// Режим экранирования порта из конфигурации
+fn is_escaped(config: &SerialConfig) -> bool {
+  config.poe_serial_escaped.unwrap_or(false)
+}
+
+impl ProtocolCodec for PoeSerialCodec {
+  fn name(&self) -> &str {
+    "POESerial"
+  }
+
+  fn description(&self) -> &str {
+    "POE board packets with header, argument, value and CRC8"
+  }
+
+  fn parameters(&self) -> serde_json::Value {
+    // Допустимые заголовки и аргументы берутся из текущего словаря
+    let dictionary = dictionary();
+    let mut headers: Vec<String> = dictionary.headers.into_keys().collect();
+    let mut arguments: Vec<String> = dictionary.arguments.into_keys().collect();
+    headers.sort();
+    arguments.sort();
+    serde_json::json!({
+      "type": "object",
+      "properties": {
+        "header": { "type": "string", "enum": headers },
+        "argument": { "type": "string", "enum": arguments },
+        "value": { "type": "string" },
+        "value_bytes": { "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 } }
+      },
+      "required": ["header", "argument", "value"]
+    })
+  }
+
+  fn encode(&self, config: &SerialConfig, command: serde_json::Value) -> Result<Vec<Vec<u8>>, String> {
+    Ok(vec![encode_poe_serial_command(command, is_escaped(config))?])
+  }
+
+  fn decoder(&self, app: &AppHandle<Wry>, config: &SerialConfig) -> Box<dyn ProtocolDecoder> {
+    let connections = app.state::<ConnectionRegistry>();
+    Box::new(PoeSerialDecoder {
+      app: app.clone(),
+      framer: PoeSerialFramer::new(is_escaped(config)),
+      port_path: config.path.clone(),
+      drop_corrupt: config.drop_corrupt_packets.unwrap_or(false),
+      stats: connections.stats(&config.path),
+      heap_telemetry: connections.heap_telemetry(&config.path),
+    })
+  }
+
+  fn send(&self, _app: &AppHandle<Wry>, transport: &dyn Transport, config: &SerialConfig, command: serde_json::Value) -> Result<(), String> {
+    send_poe_serial_command(transport, command, is_escaped(config))
+  }
+
+  fn send_awaiting_reply(
+    &self,
+    _app: &AppHandle<Wry>,
+    transport: &dyn Transport,
+    config: &SerialConfig,
+    command: serde_json::Value,
+    timeout: Duration,
+  ) -> Result<(), AttemptError> {
+    send_poe_serial_command_awaiting_reply(transport, command, is_escaped(config), timeout)
+  }
+
+  fn supports_reply(&self) -> bool {
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn crc8_matches_dallas_check_value() {
+    assert_eq!(calculate_crc8(b""), 0x00);
+    assert_eq!(calculate_crc8(b"123456789"), 0xA1);
+  }
+
+  #[test]
+  fn crc8_detects_single_bit_change() {
+    let crc = calculate_crc8(b"HEADARGVALUE");
+    assert_ne!(calculate_crc8(b"HEADARGVALUF"), crc);
+  }
+
+  #[test]
+  fn verify_crc8_checks_header_argument_and_value() {
+    let mut packet = RawPoeSerialPacket {
+      header: b"HDR".to_vec(),
+      argument: b"ARG".to_vec(),
+      value: b"VAL".to_vec(),
+      ..RawPoeSerialPacket::default()
+    };
+    packet.crc = format!("{:02X}", calculate_crc8(b"HDRARGVAL")).into_bytes();
+    assert!(verify_crc8(&packet));
+
+    packet.value = b"VAM".to_vec();
+    assert!(!verify_crc8(&packet));
+
+    packet.crc = b"ZZ".to_vec();
+    assert!(!verify_crc8(&packet));
+  }
+}
@@ -0,0 +1,323 @@
+use std::time::{Duration, Instant};
+
+use crate::poe_serial::{DLE, EOT, ESCAPE_MASK, ETX, SOH, STX, US};
+use crate::{log, LogLevel};
+
+/* [SOH] HEADER [US] ARGUMENT [STX] VALUE [ETX] CRC8 [US] FREE_HEAP [EOT] */
+
+/// Время, после которого незавершённый пакет считается потерянным
+const PARTIAL_PACKET_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Максимальный размер пакета, после которого накопленные байты отбрасываются
+const MAX_PACKET_SIZE: usize = 64 * 1024;
+
+/// Пакет POESerial, выделенный из потока байт
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RawPoeSerialPacket {
+  pub(crate) header: Vec<u8>,
+  pub(crate) argument: Vec<u8>,
+  pub(crate) value: Vec<u8>,
+  pub(crate) crc: Vec<u8>,
+  /// Свободная память устройства, пусто если пакет пришёл без этого поля
+  pub(crate) free_heap: Vec<u8>,
+}
+
+impl RawPoeSerialPacket {
+  /// Данные, по которым рассчитывается CRC8: HEADER, ARGUMENT и VALUE подряд
+  pub(crate) fn checksum_data(&self) -> Vec<u8> {
+    let mut data = Vec::with_capacity(self.header.len() + self.argument.len() + self.value.len());
+    data.extend_from_slice(&self.header);
+    data.extend_from_slice(&self.argument);
+    data.extend_from_slice(&self.value);
+    data
+  }
+}
+
+/// Поле пакета, которое сейчас принимает фреймер
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FramerState {
+  /// Ожидание SOH, байты вне пакета
+  Idle,
+  Header,
+  Argument,
+  Value,
+  Crc,
+  FreeHeap,
+}
+
+/// Результат обработки одного байта фреймером
+pub(crate) enum FrameEvent {
+  /// Байт принят в текущий пакет
+  Pending,
+  /// Байт находится вне пакета
+  Outside(u8),
+  /// Пакет собран полностью
+  Packet(RawPoeSerialPacket),
+  /// Текущий пакет отброшен, в строке - причина
+  Discarded(String),
+}
+
+/// Побайтовый конечный автомат, выделяющий пакеты POESerial из потока
+///
+/// Управляющие символы принимаются только в порядке формата пакета. SOH внутри пакета
+/// начинает новый пакет (текущий отбрасывается), любой другой управляющий символ не на своём
+/// месте отбрасывает текущий пакет, и фреймер ждёт следующий SOH. Поле свободной памяти
+/// необязательно: пакет может закончиться EOT сразу после CRC.
+///
+/// В режиме экранирования пара DLE, байт внутри полей заменяется на байт ^ 0x20,
+/// что позволяет передавать в VALUE двоичные данные и управляющие символы.
+pub(crate) struct PoeSerialFramer {
+  escaped: bool,
+  escape_pending: bool,
+  state: FramerState,
+  packet: RawPoeSerialPacket,
+  size: usize,
+  last_byte: Option<Instant>,
+}
+
+impl PoeSerialFramer {
+  /// Создаёт фреймер
+  ///
+  /// # Arguments
+  /// * `escaped` - поля пакетов передаются с экранированием DLE
+  pub(crate) fn new(escaped: bool) -> Self {
+    Self {
+      escaped,
+      escape_pending: false,
+      state: FramerState::Idle,
+      packet: RawPoeSerialPacket::default(),
+      size: 0,
+      last_byte: None,
+    }
+  }
+
+  /// Начинает приём нового пакета
+  fn start_packet(&mut self) {
+    self.escape_pending = false;
+    self.state = FramerState::Header;
+    self.packet = RawPoeSerialPacket::default();
+    self.size = 0;
+  }
+
+  /// Отбрасывает текущий пакет; если причиной стал SOH, сразу начинает новый
+  fn discard(&mut self, byte: u8, reason: &str) -> FrameEvent {
+    let state = self.state;
+    self.escape_pending = false;
+    if byte == SOH {
+      self.start_packet();
+    } else {
+      self.state = FramerState::Idle;
+      self.packet = RawPoeSerialPacket::default();
+      self.size = 0;
+    }
+    FrameEvent::Discarded(format!("{} (field: {:?}, byte: 0x{:02X})", reason, state, byte))
+  }
+
+  /// Обрабатывает один байт потока
+  pub(crate) fn push(&mut self, byte: u8) -> FrameEvent {
+    if self.state == FramerState::Idle {
+      if byte == SOH {
+        self.start_packet();
+        return FrameEvent::Pending;
+      }
+      return FrameEvent::Outside(byte);
+    }
+
+    self.size += 1;
+    if self.size > MAX_PACKET_SIZE {
+      return self.discard(byte, "Packet is too long");
+    }
+
+    let is_control = matches!(byte, SOH | STX | ETX | EOT | US);
+    if self.escape_pending {
+      if is_control {
+        return self.discard(byte, "Control byte after DLE");
+      }
+      self.escape_pending = false;
+      self.push_field_byte(byte ^ ESCAPE_MASK);
+      return FrameEvent::Pending;
+    }
+    if self.escaped && byte == DLE && matches!(self.state, FramerState::Header | FramerState::Argument | FramerState::Value) {
+      self.escape_pending = true;
+      return FrameEvent::Pending;
+    }
+
+    match (self.state, byte) {
+      (_, SOH) => self.discard(byte, "Unexpected SOH inside packet"),
+      (FramerState::Header, US) => {
+        self.state = FramerState::Argument;
+        FrameEvent::Pending
+      },
+      (FramerState::Argument, STX) => {
+        self.state = FramerState::Value;
+        FrameEvent::Pending
+      },
+      (FramerState::Value, ETX) => {
+        self.state = FramerState::Crc;
+        FrameEvent::Pending
+      },
+      (FramerState::Crc, US) => {
+        self.state = FramerState::FreeHeap;
+        FrameEvent::Pending
+      },
+      (FramerState::Crc, EOT) | (FramerState::FreeHeap, EOT) => {
+        self.state = FramerState::Idle;
+        self.size = 0;
+        FrameEvent::Packet(std::mem::take(&mut self.packet))
+      },
+      (_, _) if is_control => self.discard(byte, "Unexpected control byte"),
+      (_, _) => {
+        self.push_field_byte(byte);
+        FrameEvent::Pending
+      },
+    }
+  }
+
+  /// Дописывает байт в поле, которое сейчас принимается
+  fn push_field_byte(&mut self, byte: u8) {
+    let field = match self.state {
+      FramerState::Header => &mut self.packet.header,
+      FramerState::Argument => &mut self.packet.argument,
+      FramerState::Value => &mut self.packet.value,
+      FramerState::Crc => &mut self.packet.crc,
+      FramerState::FreeHeap => &mut self.packet.free_heap,
+      FramerState::Idle => return,
+    };
+    field.push(byte);
+  }
+
+  /// Обрабатывает блок принятых байт и возвращает собранные пакеты
+  ///
+  /// Незавершённый пакет, продолжение которого не пришло за 500 мс, отбрасывается.
+  /// Байты вне пакетов и отброшенные пакеты записываются в лог.
+  ///
+  /// # Arguments
+  /// * `data` - принятые байты
+  /// * `port_path` - путь к порту (для логирования)
+  ///
+  /// # Returns
+  /// * `Vec<RawPoeSerialPacket>` - пакеты, полностью собранные в этом блоке
+  pub(crate) fn push_bytes(&mut self, data: &[u8], port_path: &str) -> Vec<RawPoeSerialPacket> {
+    let now = Instant::now();
+    if self.state != FramerState::Idle {
+      if let Some(last_byte) = self.last_byte {
+        if now.duration_since(last_byte) >= PARTIAL_PACKET_TIMEOUT {
+          log(
+            LogLevel::Warn,
+            "PoeSerialFramer",
+            format!("Незавершённый пакет на порту {} отброшен по таймауту", port_path),
+          );
+          self.state = FramerState::Idle;
+          self.packet = RawPoeSerialPacket::default();
+          self.size = 0;
+        }
+      }
+    }
+    self.last_byte = Some(now);
+
+    let mut packets = Vec::new();
+    let mut skipped = 0;
+    for &byte in data {
+      match self.push(byte) {
+        FrameEvent::Pending => {},
+        FrameEvent::Outside(_) => skipped += 1,
+        FrameEvent::Packet(packet) => packets.push(packet),
+        FrameEvent::Discarded(reason) => {
+          log(LogLevel::Warn, "PoeSerialFramer", format!("Пакет на порту {} отброшен: {}", port_path, reason));
+        },
+      }
+    }
+
+    if skipped > 0 {
+      log(
+        LogLevel::Warn,
+        "PoeSerialFramer",
+        format!("Пропущено {} байт вне пакетов на порту {}", skipped, port_path),
+      );
+    }
+
+    packets
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::poe_serial::{calculate_crc8, encode_poe_serial_packet};
+
+  const PORT: &str = "test";
+
+  #[test]
+  fn frames_plain_packet_with_free_heap() {
+    let bytes = encode_poe_serial_packet(b"HDR", b"ARG", b"VAL", Some(1024), false);
+    let packets = PoeSerialFramer::new(false).push_bytes(&bytes, PORT);
+
+    assert_eq!(packets.len(), 1);
+    let packet = &packets[0];
+    assert_eq!(packet.header, b"HDR");
+    assert_eq!(packet.argument, b"ARG");
+    assert_eq!(packet.value, b"VAL");
+    assert_eq!(packet.free_heap, b"1024");
+    assert_eq!(String::from_utf8_lossy(&packet.crc), format!("{:02X}", calculate_crc8(b"HDRARGVAL")));
+  }
+
+  #[test]
+  fn frames_packet_split_across_blocks() {
+    let bytes = encode_poe_serial_packet(b"HDR", b"ARG", b"VAL", None, false);
+    let mut framer = PoeSerialFramer::new(false);
+    let (first, second) = bytes.split_at(5);
+
+    assert!(framer.push_bytes(first, PORT).is_empty());
+    let packets = framer.push_bytes(second, PORT);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].value, b"VAL");
+    assert!(packets[0].free_heap.is_empty());
+  }
+
+  #[test]
+  fn unescapes_control_bytes_in_value() {
+    let value = [b'A', SOH, STX, ETX, EOT, US, DLE, b'B'];
+    let bytes = encode_poe_serial_packet(b"HDR", b"ARG", &value, None, true);
+    let packets = PoeSerialFramer::new(true).push_bytes(&bytes, PORT);
+
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].value, value);
+    assert_eq!(packets[0].checksum_data(), [b"HDRARG".as_slice(), &value].concat());
+  }
+
+  #[test]
+  fn control_byte_after_dle_discards_packet() {
+    let mut framer = PoeSerialFramer::new(true);
+    let mut bytes = vec![SOH, b'H', US, b'A', STX, DLE, ETX];
+    bytes.extend(encode_poe_serial_packet(b"HDR", b"ARG", b"VAL", None, true));
+
+    let packets = framer.push_bytes(&bytes, PORT);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].header, b"HDR");
+  }
+
+  #[test]
+  fn dle_is_plain_data_without_escaping() {
+    let bytes = encode_poe_serial_packet(b"HDR", b"ARG", &[DLE, b'x'], None, false);
+    let packets = PoeSerialFramer::new(false).push_bytes(&bytes, PORT);
+
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].value, [DLE, b'x']);
+  }
+
+  #[test]
+  fn resynchronizes_on_soh_and_skips_noise() {
+    let mut bytes = b"noise".to_vec();
+    bytes.extend([SOH, b'H', US, b'A']);
+    bytes.extend(encode_poe_serial_packet(b"HDR", b"ARG", b"VAL", None, false));
+    bytes.extend([SOH, b'H', EOT]);
+    bytes.extend(encode_poe_serial_packet(b"HD2", b"AR2", b"VA2", None, false));
+
+    let packets = PoeSerialFramer::new(false).push_bytes(&bytes, PORT);
+    let headers: Vec<&[u8]> = packets
+      .iter()
+      .map(|packet| packet.header.as_slice())
+      .collect();
+    assert_eq!(headers, [b"HDR".as_slice(), b"HD2".as_slice()]);
+  }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Manager, Wry};
+
+use crate::connections::ConnectionRegistry;
+use crate::dictionary::validate_serial_command;
+use crate::poe_serial::{calculate_crc8, contains_control_bytes, write_poe_serial_packet};
+use crate::poe_serial_framer::{PoeSerialFramer, RawPoeSerialPacket};
+use crate::schemas::validate_named_value;
+use crate::transports::Transport;
+use crate::{log, LogLevel};
+
+/// Время ожидания ответа по умолчанию
+const DEFAULT_TRANSACT_TIMEOUT_MS: u64 = 1000;
+
+/// Заголовки ответов устройства
+const HEADER_OK: &str = "OK!";
+const HEADER_ERROR: &str = "ER!";
+
+/// Ответ устройства на транзакцию POESerial
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct PoeSerialReply {
+  pub header: String,
+  pub argument: String,
+  pub value: String,
+  pub value_bytes: Vec<u8>,
+  pub free_heap_size: Option<u32>,
+  pub elapsed_ms: u64,
+}
+
+/// Ошибка транзакции POESerial
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum PoeSerialTransactError {
+  /// Порт не открыт
+  NotOpen(String),
+  /// Некорректный запрос (заголовок не GET/SET, имя не из словаря, значение не по схеме,
+  /// управляющие символы без экранирования)
+  InvalidRequest(String),
+  /// Не удалось записать запрос в порт
+  Send(String),
+  /// Ответ не пришёл за отведённое время
+  Timeout(String),
+  /// Ответ пришёл с неверной CRC
+  Crc(String),
+  /// Устройство ответило ER!, в сообщении - значение из ответа
+  Device(String),
+  /// Значение ответа не соответствует схеме аргумента
+  Schema(String),
+}
+
+impl fmt::Display for PoeSerialTransactError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PoeSerialTransactError::NotOpen(message) => write!(f, "Port is not open: {}", message),
+      PoeSerialTransactError::InvalidRequest(message) => write!(f, "Invalid request: {}", message),
+      PoeSerialTransactError::Send(message) => write!(f, "Failed to send request: {}", message),
+      PoeSerialTransactError::Timeout(message) => write!(f, "No reply: {}", message),
+      PoeSerialTransactError::Crc(message) => write!(f, "Reply CRC mismatch: {}", message),
+      PoeSerialTransactError::Device(message) => write!(f, "Device returned an error: {}", message),
+      PoeSerialTransactError::Schema(message) => write!(f, "Invalid reply value: {}", message),
+    }
+  }
+}
+
+/// Разбирает размер свободной памяти из поля пакета
+fn parse_free_heap(packet: &RawPoeSerialPacket) -> Option<u32> {
+  String::from_utf8_lossy(&packet.free_heap)
+    .trim()
+    .parse()
+    .ok()
+}
+
+/// Отправляет запрос POESerial и ждёт ответ OK!/ER! с тем же аргументом
+///
+/// Ответ ожидается отдельным подписчиком порта, поэтому обработчик протокола
+/// на фронтенде продолжает получать все пакеты, включая ответ на транзакцию.
+///
+/// # Arguments
+/// * `transport` - транспорт открытого порта
+/// * `escaped` - режим экранирования порта
+/// * `header` - заголовок запроса (GET или SET)
+/// * `argument` - аргумент запроса
+/// * `value` - значение запроса
+/// * `timeout` - время ожидания ответа
+///
+/// # Returns
+/// * `Ok(PoeSerialReply)` - ответ OK!
+/// * `Err(PoeSerialTransactError)` - ошибка транзакции
+pub(crate) fn transact(
+  transport: &dyn Transport,
+  escaped: bool,
+  header: &str,
+  argument: &str,
+  value: &[u8],
+  timeout: Duration,
+) -> Result<PoeSerialReply, PoeSerialTransactError> {
+  if header != "GET" && header != "SET" {
+    return Err(PoeSerialTransactError::InvalidRequest(format!("Unsupported header {}", header)));
+  }
+  validate_serial_command(header, argument).map_err(PoeSerialTransactError::InvalidRequest)?;
+  if header == "SET" {
+    validate_named_value(argument, value).map_err(PoeSerialTransactError::InvalidRequest)?;
+  }
+  if !escaped && (contains_control_bytes(argument.as_bytes()) || contains_control_bytes(value)) {
+    return Err(PoeSerialTransactError::InvalidRequest(
+      "Request contains control characters, enable escaped mode to send them".to_string(),
+    ));
+  }
+
+  // Подписываемся до отправки, чтобы не пропустить быстрый ответ
+  let (sender, receiver) = mpsc::channel::<RawPoeSerialPacket>();
+  let framer = Mutex::new(PoeSerialFramer::new(escaped));
+  let expected_argument = argument.as_bytes().to_vec();
+  let listener_id = transport.subscribe(Box::new(move |data| {
+    let packets = framer
+      .lock()
+      .unwrap()
+      .push_bytes(&data, "poe_serial_transact");
+    for packet in packets {
+      let is_reply = packet.header == HEADER_OK.as_bytes() || packet.header == HEADER_ERROR.as_bytes();
+      if is_reply && packet.argument == expected_argument {
+        let _ = sender.send(packet);
+      }
+    }
+  }));
+
+  let started = Instant::now();
+  let result = write_poe_serial_packet(transport, header.as_bytes(), argument.as_bytes(), value, escaped)
+    .map_err(PoeSerialTransactError::Send)
+    .and_then(|_| {
+      receiver
+        .recv_timeout(timeout)
+        .map_err(|_| PoeSerialTransactError::Timeout(format!("{} {} after {} ms", header, argument, timeout.as_millis())))
+    });
+  transport.unsubscribe(listener_id);
+  let packet = result?;
+
+  let crc_hex = String::from_utf8_lossy(&packet.crc).to_string();
+  if u8::from_str_radix(crc_hex.trim(), 16).ok() != Some(calculate_crc8(&packet.checksum_data())) {
+    return Err(PoeSerialTransactError::Crc(format!("{} {}, received {}", header, argument, crc_hex)));
+  }
+
+  let reply = PoeSerialReply {
+    header: String::from_utf8_lossy(&packet.header).to_string(),
+    argument: argument.to_string(),
+    value: String::from_utf8_lossy(&packet.value).to_string(),
+    free_heap_size: parse_free_heap(&packet),
+    value_bytes: packet.value,
+    elapsed_ms: started.elapsed().as_millis() as u64,
+  };
+
+  if reply.header == HEADER_ERROR {
+    return Err(PoeSerialTransactError::Device(reply.value));
+  }
+  if !reply.value_bytes.is_empty() {
+    validate_named_value(argument, &reply.value_bytes).map_err(PoeSerialTransactError::Schema)?;
+  }
+  Ok(reply)
+}
+
+/// Отправляет команду GET/SET по протоколу POESerial и ждёт ответ устройства
+///
+/// Транзакции с одним портом выполняются по очереди, чтобы ответы не путались.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `port_path` - путь к открытому порту
+/// * `header` - заголовок запроса (GET или SET)
+/// * `argument` - аргумент запроса
+/// * `value` - значение запроса (для GET может отсутствовать)
+/// * `timeout_ms` - время ожидания ответа, по умолчанию 1000 мс
+///
+/// # Returns
+/// * `Ok(PoeSerialReply)` - ответ OK! с разобранным значением
+/// * `Err(PoeSerialTransactError)` - типизированная ошибка транзакции
+#[command]
+pub async fn poe_serial_transact(
+  app: AppHandle<Wry>,
+  port_path: String,
+  header: String,
+  argument: String,
+  value: Option<String>,
+  timeout_ms: Option<u64>,
+) -> Result<PoeSerialReply, PoeSerialTransactError> {
+  let connections = app.state::<ConnectionRegistry>();
+  let transport = connections
+    .find_transport(&port_path)
+    .ok_or_else(|| PoeSerialTransactError::NotOpen(port_path.clone()))?;
+  let transaction_lock = connections
+    .transaction_lock(&port_path)
+    .ok_or_else(|| PoeSerialTransactError::NotOpen(port_path.clone()))?;
+  let escaped = connections
+    .get_config(&port_path)
+    .and_then(|config| config.poe_serial_escaped)
+    .unwrap_or(false);
+  let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TRANSACT_TIMEOUT_MS));
+
+  log(
+    LogLevel::Info,
+    "poe_serial_transact",
+    format!("Транзакция {} {} на порту {}", header, argument, port_path),
+  );
+
+  // Ожидание ответа блокирующее, поэтому выполняется вне асинхронного потока
+  let result = tauri::async_runtime::spawn_blocking(move || {
+    let _guard = transaction_lock.lock().unwrap();
+    transact(transport.as_ref(), escaped, &header, &argument, value.unwrap_or_default().as_bytes(), timeout)
+  })
+  .await
+  .map_err(|e| PoeSerialTransactError::Send(e.to_string()))?;
+
+  // Запрос считается отправленным, если устройство ответило или ответ не дождались
+  let sent = !matches!(result, Err(PoeSerialTransactError::Send(_)) | Err(PoeSerialTransactError::InvalidRequest(_)));
+  connections.record_send(&port_path, sent);
+  match &result {
+    Ok(reply) => log(
+      LogLevel::Info,
+      "poe_serial_transact",
+      format!("Получен ответ за {} мс: {}", reply.elapsed_ms, reply.value),
+    ),
+    Err(e) => log(LogLevel::Warn, "poe_serial_transact", format!("Транзакция не выполнена: {}", e)),
+  }
+
+  result
+}
@@ -1,14 +1,12 @@
-use lazy_static::lazy_static;
 use serde_json;
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tauri::ipc::Channel;
-use tauri::{command, AppHandle, Listener, State, Wry};
-use tauri_plugin_serialplugin::commands::write;
-use tauri_plugin_serialplugin::desktop_api::SerialPort;
+use tauri::{command, AppHandle, Wry};
 
-use crate::{log, LogLevel, ReadDataResult};
+use crate::codec::{subscribe_decoder, ProtocolCodec, ProtocolDecoder};
+use crate::models::SerialConfig;
+use crate::transports::Transport;
+use crate::{log, LogLevel};
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 struct SimpleSerialCommand {
@@ -16,12 +14,10 @@ struct SimpleSerialCommand {
   end_package: String,
 }
 
-lazy_static! {
-  static ref DATA_BUFFERS: Arc<Mutex<HashMap<String, Vec<String>>>> = Arc::new(Mutex::new(HashMap::new()));
-}
-
 /// Обрабатывает принятые данные по протоколу SimpleSerial и отправляет их через канал
 ///
+/// Строки разбирает декодер протокола из реестра, каждая строка отправляется отдельно.
+///
 /// # Arguments
 /// * `app` - дескриптор приложения Tauri
 /// * `port_path` - путь к серийному порту
@@ -32,84 +28,30 @@ lazy_static! {
 /// * `Err(String)` - ошибка при создании слушателя
 #[command]
 pub fn process_simple_serial(app: AppHandle<Wry>, port_path: String, on_event: Channel<String>) -> Result<u32, String> {
-  let app_clone = app.clone();
-
-  // Создаём буфер для накопления данных
-  let buffer: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
-  let buffer_clone = buffer.clone();
-
-  // Форматируем путь порта для использования в имени события
-  let formatted_port_path = port_path
-    .clone()
-    .replace(".", "-")
-    .replace("/", "-")
-    .replace("\\", "-");
-  log(
-    LogLevel::Info,
-    "process_simple_serial",
-    format!("Форматированный путь порта: {}", formatted_port_path),
-  );
-  let listen_event_name = format!("plugin-serialplugin-read-{}", formatted_port_path);
-
-  let event_id = app_clone.clone().listen(listen_event_name, move |event| {
-    // Разбираем полезную нагрузку события
-    if let Ok(payload) = serde_json::from_str::<ReadDataResult>(&event.payload()) {
-      // Преобразуем байты в строку
-      let data_str = match String::from_utf8(payload.data.clone()) {
-        Ok(s) => s,
-        Err(_) => String::from_utf8_lossy(&payload.data).to_string(),
-      };
-      log(LogLevel::Info, "process_simple_serial", format!("Данные в виде строки: {}", data_str));
-
-      // Добавляем данные в буфер
-      {
-        let mut buffer_guard = buffer_clone.lock().unwrap();
-        buffer_guard.push_str(&data_str);
-        log(
-          LogLevel::Info,
-          "process_simple_serial",
-          format!("Буфер обновлён, текущий размер: {}", buffer_guard.len()),
-        );
-        drop(buffer_guard);
+  subscribe_decoder(&app, &port_path, &["SimpleSerial"], "process_simple_serial", move |frames| {
+    for line in frames
+      .into_iter()
+      .filter_map(|frame| frame.as_str().map(str::to_string))
+    {
+      if let Err(e) = on_event.send(line) {
+        log(LogLevel::Err, "process_simple_serial", format!("Ошибка отправки через канал: {}", e));
       }
-
-      // Обработка буфера
-      let processed_remaining = {
-        let buffer_guard = buffer_clone.lock().unwrap();
-        let data = buffer_guard.clone();
-        drop(buffer_guard);
-
-        // Обрабатываем данные
-        match process_simple_serial_data(&data, &on_event, &port_path, false) {
-          Ok(remaining) => remaining,
-          Err(e) => {
-            log(LogLevel::Err, "process_simple_serial", format!("Ошибка обработки данных: {}", e));
-            String::new()
-          },
-        }
-      };
-
-      // Сохраняем остаток в буфере
-      let mut buffer_guard = buffer_clone.lock().unwrap();
-      *buffer_guard = processed_remaining;
     }
-  });
-
-  Ok(event_id)
+  })
 }
 
 /// Вспомогательная функция для обработки данных SimpleSerial
 ///
 /// # Arguments
 /// * `data` - строка с данными для обработки
-/// * `on_event` - канал для отправки обработанных данных
+/// * `lines` - принятые строки для отправки
 /// * `port_path` - путь к порту (для логирования)
 /// * `interruption` - флаг режима прерывания
 ///
 /// # Returns
 /// * `Ok(String)` - оставшиеся необработанные данные
 /// * `Err(String)` - ошибка обработки
-fn process_simple_serial_data(data: &str, on_event: &Channel<String>, port_path: &str, interruption: bool) -> Result<String, String> {
+pub(crate) fn process_simple_serial_data(data: &str, lines: &mut Vec<String>, port_path: &str, interruption: bool) -> Result<String, String> {
   log(
     LogLevel::Info,
     "process_simple_serial_data",
@@ -143,7 +85,7 @@ fn process_simple_serial_data(data: &str, on_event: &Channel<String>, port_path:
           "process_simple_serial_data",
           format!("Режим отправки: отправка строки через канал"),
         );
-        on_event.send(line.to_string()).map_err(|e| e.to_string())?;
+        lines.push(line.to_string());
       }
       processed_data.clear();
     } else {
@@ -169,9 +111,7 @@ fn process_simple_serial_data(data: &str, on_event: &Channel<String>, port_path:
           );
 
           // Отправляем накопленные данные через канал
-          on_event
-            .send(processed_data.clone())
-            .map_err(|e| e.to_string())?;
+          lines.push(processed_data.clone());
         }
         processed_data.clear();
       }
@@ -187,34 +127,20 @@ fn process_simple_serial_data(data: &str, on_event: &Channel<String>, port_path:
   Ok(processed_data)
 }
 
-/// Отправляет команду по протоколу SimpleSerial в серийный порт
+/// Кодирует команду SimpleSerial в байты для записи в порт
 ///
 /// # Arguments
-/// * `app` - дескриптор приложения Tauri
-/// * `serial` - состояние серийного порта
-/// * `port_path` - путь к серийному порту
 /// * `sending_data` - JSON-объект с командой
 ///
 /// # Returns
-/// * `Ok(())` - команда успешно отправлена
-/// * `Err(String)` - ошибка отправки
-pub fn send_simple_serial_command(
-  app: AppHandle,
-  serial: State<'_, SerialPort<Wry>>,
-  port_path: String,
-  sending_data: serde_json::Value,
-) -> Result<(), String> {
-  log(
-    LogLevel::Info,
-    "send_simple_serial_command",
-    format!("Начало отправки SimpleSerial команды на порт: {}", port_path),
-  );
-
+/// * `Ok(Vec<u8>)` - данные с признаком конца посылки
+/// * `Err(String)` - команда некорректна
+pub(crate) fn encode_simple_serial_command(sending_data: serde_json::Value) -> Result<Vec<u8>, String> {
   // Разбираем JSON в структуру команды
   let command: SimpleSerialCommand = serde_json::from_value(sending_data).map_err(|e| {
     log(
       LogLevel::Err,
-      "send_simple_serial_command",
+      "encode_simple_serial_command",
       format!("Не удалось разобрать команду SimpleSerial: {}", e),
     );
     format!("Failed to parse simple serial command: {}", e)
@@ -222,22 +148,92 @@ pub fn send_simple_serial_command(
 
   log(
     LogLevel::Info,
-    "send_simple_serial_command",
+    "encode_simple_serial_command",
     format!("Разобранные данные команды: data={}, end_package={}", command.data, command.end_package),
   );
 
+  Ok(format!("{}{}", command.data, command.end_package).into_bytes())
+}
+
+/// Отправляет команду по протоколу SimpleSerial в серийный порт
+///
+/// # Arguments
+/// * `transport` - транспорт открытого порта
+/// * `sending_data` - JSON-объект с командой
+///
+/// # Returns
+/// * `Ok(())` - команда успешно отправлена
+/// * `Err(String)` - ошибка отправки
+pub fn send_simple_serial_command(transport: &dyn Transport, sending_data: serde_json::Value) -> Result<(), String> {
+  log(
+    LogLevel::Info,
+    "send_simple_serial_command",
+    format!("Начало отправки SimpleSerial команды на порт: {}", transport.path()),
+  );
+
   // Отправляем команду в порт
-  write(
-    app.clone(),
-    serial.clone(),
-    port_path.clone(),
-    format!("{}{}", command.data, command.end_package),
-  )
-  .map_err(|e| {
-    log(LogLevel::Err, "send_simple_serial_command", format!("Не удалось записать данные в порт: {}", e));
-    format!("Failed to write: {}", e)
-  })?;
+  transport
+    .write(&encode_simple_serial_command(sending_data)?)
+    .map_err(|e| {
+      log(LogLevel::Err, "send_simple_serial_command", format!("Не удалось записать данные в порт: {}", e));
+      format!("Failed to write: {}", e)
+    })?;
 
   log(LogLevel::Info, "send_simple_serial_command", format!("Команда SimpleSerial успешно отправлена"));
   Ok(())
 }
+
+/// Протокол SimpleSerial: текстовые строки, завершённые '\r' или '\n'
+pub struct SimpleSerialCodec;
+
+/// Декодер SimpleSerial, накапливающий незавершённую строку
+struct SimpleSerialDecoder {
+  buffer: String,
+  port_path: String,
+}
+
+impl ProtocolDecoder for SimpleSerialDecoder {
+  fn decode(&mut self, data: &[u8]) -> Vec<serde_json::Value> {
+    self.buffer.push_str(&String::from_utf8_lossy(data));
+
+    let mut lines = Vec::new();
+    self.buffer = process_simple_serial_data(&self.buffer, &mut lines, &self.port_path, false).unwrap_or_default();
+    lines.into_iter().map(serde_json::Value::String).collect()
+  }
+}
+
+impl ProtocolCodec for SimpleSerialCodec {
+  fn name(&self) -> &str {
+    "SimpleSerial"
+  }
+
+  fn description(&self) -> &str {
+    "Plain text lines"
+  }
+
+  fn parameters(&self) -> serde_json::Value {
+    serde_json::json!({
+      "type": "object",
+      "properties": {
+        "data": { "type": "string" },
+        "end_package": { "type": "string" }
+      },
+      "required": ["data", "end_package"]
+    })
+  }
+
+  fn encode(&self, _config: &SerialConfig, command: serde_json::Value) -> Result<Vec<Vec<u8>>, String> {
+    Ok(vec![encode_simple_serial_command(command)?])
+  }
+
+  fn decoder(&self, _app: &AppHandle<Wry>, config: &SerialConfig) -> Box<dyn ProtocolDecoder> {
+    Box::new(SimpleSerialDecoder {
+      buffer: String::new(),
+      port_path: config.path.clone(),
+    })
+  }
+
+  fn send(&self, _app: &AppHandle<Wry>, transport: &dyn Transport, _config: &SerialConfig, command: serde_json::Value) -> Result<(), String> {
+    send_simple_serial_command(transport, command)
+  }
+}
@@ -0,0 +1,396 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::transports::Transport;
+use crate::{log, LogLevel};
+
+/// Время ожидания подтверждения команды SLCAN
+pub const SLCAN_ACK_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Время, после которого команда без подтверждения снимается с ожидания
+const SLCAN_ACK_EXPIRY: Duration = Duration::from_secs(2);
+
+/// Подтверждение ошибки адаптера SLCAN
+const BEL: u8 = 0x07;
+
+/// Максимальная хранимая длина строки ответа адаптера
+const MAX_REPLY_LENGTH: usize = 64;
+
+/// Число последних подтверждённых команд в истории
+const HISTORY_LENGTH: usize = 32;
+
+/// Обработчик подтверждения: `Ok` с текстом ответа (пустым для '\r') или `Err` с причиной,
+/// если адаптер отклонил команду
+pub type AckCallback = Box<dyn FnOnce(Result<String, String>) + Send>;
+
+/// Команда, ожидающая подтверждения адаптера
+struct PendingCommand {
+  id: u64,
+  command: String,
+  sent_at: Instant,
+  /// `None` - отправитель перестал ждать: запись остаётся в очереди, чтобы опоздавшее
+  /// подтверждение досталось ей, а не следующей команде
+  callback: Option<AckCallback>,
+}
+
+/// Сопоставляет подтверждения адаптера SLCAN с отправленными командами
+///
+/// Адаптер подтверждает каждую команду по порядку: '\r' (или ответ команды, например
+/// `Fxx\r`, `z\r`) при успехе и BEL при ошибке. Принятые фреймы подтверждениями не считаются.
+/// Команды, подтверждение которых не пришло за `SLCAN_ACK_EXPIRY`, снимаются с ожидания
+/// при записи следующей команды.
+pub struct SlcanAckTracker {
+  pending: Mutex<VecDeque<PendingCommand>>,
+  /// Последние подтверждённые команды: команда и признак её приёма адаптером
+  history: Mutex<VecDeque<(String, bool)>>,
+  /// Начало текущей строки потока, для отличия фреймов от ответов
+  line: Mutex<String>,
+  /// Порядок регистрации команд совпадает с порядком записи в порт
+  write_lock: Mutex<()>,
+  next_id: AtomicU64,
+}
+
+impl Default for SlcanAckTracker {
+  fn default() -> Self {
+    Self {
+      pending: Mutex::new(VecDeque::new()),
+      history: Mutex::new(VecDeque::new()),
+      line: Mutex::new(String::new()),
+      write_lock: Mutex::new(()),
+      next_id: AtomicU64::new(1),
+    }
+  }
+}
+
+impl SlcanAckTracker {
+  /// Обрабатывает принятые байты порта
+  pub fn push(&self, data: &[u8]) {
+    let mut line = self.line.lock().unwrap();
+    for &byte in data {
+      match byte {
+        BEL => {
+          line.clear();
+          self.resolve(Err(()));
+        },
+        b'\r' => {
+          let reply = std::mem::take(&mut *line);
+          let is_frame = reply.len() > 1 && matches!(reply.as_bytes()[0], b't' | b'T' | b'r' | b'R' | b'b' | b'B' | b'd' | b'D');
+          if !is_frame {
+            self.resolve(Ok(reply));
+          }
+        },
+        // Фреймы отличаются от ответов по началу строки, длинные ответы обрезаются
+        _ if line.len() < MAX_REPLY_LENGTH => line.push(byte as char),
+        _ => {},
+      }
+    }
+  }
+
+  /// Передаёт подтверждение первой ожидающей команде
+  fn resolve(&self, reply: Result<String, ()>) {
+    let pending = self.pending.lock().unwrap().pop_front();
+    let Some(pending) = pending else {
+      log(LogLevel::Info, "SlcanAckTracker", "Подтверждение без ожидающей команды".to_string());
+      return;
+    };
+
+    {
+      let mut history = self.history.lock().unwrap();
+      history.push_back((pending.command.clone(), reply.is_ok()));
+      if history.len() > HISTORY_LENGTH {
+        history.pop_front();
+      }
+    }
+
+    let Some(callback) = pending.callback else {
+      log(
+        LogLevel::Warn,
+        "SlcanAckTracker",
+        format!("Подтверждение команды {} пришло после окончания ожидания", pending.command),
+      );
+      return;
+    };
+    match reply {
+      Ok(reply) => callback(Ok(reply)),
+      Err(_) => {
+        log(LogLevel::Warn, "SlcanAckTracker", format!("Адаптер отклонил команду {}", pending.command));
+        callback(Err(format!("Adapter rejected command {}", pending.command)));
+      },
+    }
+  }
+
+  /// Снимает с ожидания команды, подтверждение которых не пришло за `SLCAN_ACK_EXPIRY`
+  fn prune_expired(&self, now: Instant) {
+    let mut queue = self.pending.lock().unwrap();
+    while queue
+      .front()
+      .is_some_and(|pending| now.saturating_duration_since(pending.sent_at) > SLCAN_ACK_EXPIRY)
+    {
+      let expired = queue.pop_front().unwrap();
+      log(LogLevel::Warn, "SlcanAckTracker", format!("Нет подтверждения команды {}", expired.command));
+    }
+  }
+
+  /// Сбрасывает ожидающие команды и начало строки, например после переподключения порта
+  ///
+  /// Подтверждения команд, записанных в прежнее подключение, уже не придут.
+  pub fn reset(&self) {
+    let dropped = std::mem::take(&mut *self.pending.lock().unwrap());
+    self.line.lock().unwrap().clear();
+    if !dropped.is_empty() {
+      log(
+        LogLevel::Info,
+        "SlcanAckTracker",
+        format!("Сброшено {} команд без подтверждения", dropped.len()),
+      );
+    }
+  }
+
+  /// Результат последнего подтверждения команды
+  ///
+  /// # Arguments
+  /// * `command` - команда без завершающего '\r'
+  ///
+  /// # Returns
+  /// * `Some(true)` / `Some(false)` - адаптер принял / отклонил команду
+  /// * `None` - подтверждения команды нет в истории
+  pub fn command_result(&self, command: &str) -> Option<bool> {
+    self
+      .history
+      .lock()
+      .unwrap()
+      .iter()
+      .rev()
+      .find(|(sent, _)| sent == command)
+      .map(|(_, accepted)| *accepted)
+  }
+
+  /// Прекращает ожидание подтверждения команды
+  ///
+  /// Команда остаётся в очереди без обработчика: адаптер подтверждает команды по порядку,
+  /// и опоздавшее подтверждение должно быть поглощено ею.
+  fn cancel(&self, id: u64) {
+    if let Some(pending) = self
+      .pending
+      .lock()
+      .unwrap()
+      .iter_mut()
+      .find(|pending| pending.id == id)
+    {
+      pending.callback = None;
+    }
+  }
+
+  /// Удаляет из очереди команду, которая не была записана в порт
+  fn forget(&self, id: u64) {
+    self
+      .pending
+      .lock()
+      .unwrap()
+      .retain(|pending| pending.id != id);
+  }
+
+  /// Записывает команду SLCAN, подтверждение передаётся обработчику после его приёма
+  ///
+  /// # Arguments
+  /// * `transport` - транспорт порта
+  /// * `command` - одна команда с завершающим '\r'
+  /// * `callback` - обработчик подтверждения; не вызывается, если подтверждение не пришло
+  ///
+  /// # Returns
+  /// * `Ok(u64)` - ID ожидающей команды
+  /// * `Err(String)` - ошибка записи в порт
+  pub fn write_with_callback(&self, transport: &dyn Transport, command: &[u8], callback: AckCallback) -> Result<u64, String> {
+    let _guard = self.write_lock.lock().unwrap();
+    let now = Instant::now();
+    self.prune_expired(now);
+    let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+    self.pending.lock().unwrap().push_back(PendingCommand {
+      id,
+      command: String::from_utf8_lossy(command).trim_end().to_string(),
+      sent_at: now,
+      callback: Some(callback),
+    });
+
+    if let Err(e) = transport.write(command) {
+      self.forget(id);
+      return Err(e);
+    }
+    Ok(id)
+  }
+
+  /// Записывает команду SLCAN и ждёт подтверждения адаптера
+  ///
+  /// Транспорты без подтверждений (например, `loop://`) не отвечают, поэтому отсутствие
+  /// подтверждения за `timeout` не считается ошибкой, а возвращается отдельно.
+  ///
+  /// # Returns
+  /// * `Ok(true)` - адаптер подтвердил команду
+  /// * `Ok(false)` - команда записана, но подтверждение не пришло
+  /// * `Err(String)` - ошибка записи или адаптер отклонил команду
+  pub fn write_confirmed(&self, transport: &dyn Transport, command: &[u8], timeout: Duration) -> Result<bool, String> {
+    self
+      .query(transport, command, timeout)
+      .map(|reply| reply.is_some())
+  }
+
+  /// Записывает команду-запрос SLCAN (`V`, `N` и т.п.) и ждёт ответ адаптера
+  ///
+  /// # Returns
+  /// * `Ok(Some(String))` - строка ответа без завершающего '\r'
+  /// * `Ok(None)` - ответ не пришёл за `timeout`
+  /// * `Err(String)` - ошибка записи или адаптер отклонил команду
+  pub fn query(&self, transport: &dyn Transport, command: &[u8], timeout: Duration) -> Result<Option<String>, String> {
+    let (sender, receiver) = mpsc::channel();
+    let id = self.write_with_callback(
+      transport,
+      command,
+      Box::new(move |result| {
+        let _ = sender.send(result);
+      }),
+    )?;
+
+    match receiver.recv_timeout(timeout) {
+      Ok(result) => result.map(Some),
+      Err(_) => {
+        self.cancel(id);
+        log(
+          LogLevel::Warn,
+          "SlcanAckTracker",
+          format!(
+            "Нет подтверждения команды {} за {} мс на порту {}",
+            String::from_utf8_lossy(command).trim_end(),
+            timeout.as_millis(),
+            transport.path()
+          ),
+        );
+        Ok(None)
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::SerialConfig;
+  use crate::transports::virtual_port::VirtualTransport;
+  use std::sync::Arc;
+
+  type Results = Arc<Mutex<Vec<(&'static str, Result<String, String>)>>>;
+
+  /// Открывает виртуальный порт без чтения: подтверждения в тестах передаются в `push` напрямую
+  fn open_port(path: &str) -> VirtualTransport {
+    let transport = VirtualTransport::loopback(path.to_string());
+    transport
+      .open(&SerialConfig::test(path, "POECanable"))
+      .unwrap();
+    transport
+  }
+
+  fn record(results: &Results, name: &'static str) -> AckCallback {
+    let results = results.clone();
+    Box::new(move |result| results.lock().unwrap().push((name, result)))
+  }
+
+  #[test]
+  fn acks_resolve_commands_in_order() {
+    let transport = open_port("loop://acks-order");
+    let tracker = SlcanAckTracker::default();
+    let results = Results::default();
+    tracker
+      .write_with_callback(&transport, b"O\r", record(&results, "O"))
+      .unwrap();
+    tracker
+      .write_with_callback(&transport, b"S9\r", record(&results, "S9"))
+      .unwrap();
+    tracker
+      .write_with_callback(&transport, b"F\r", record(&results, "F"))
+      .unwrap();
+
+    // Принятый фрейм не подтверждает команду
+    tracker.push(b"T0000012320102\r\r");
+    tracker.push(&[BEL]);
+    tracker.push(b"F0");
+    tracker.push(b"0\r");
+
+    assert_eq!(
+      *results.lock().unwrap(),
+      [
+        ("O", Ok(String::new())),
+        ("S9", Err("Adapter rejected command S9".to_string())),
+        ("F", Ok("F00".to_string())),
+      ]
+    );
+    assert_eq!(tracker.command_result("O"), Some(true));
+    assert_eq!(tracker.command_result("S9"), Some(false));
+  }
+
+  #[test]
+  fn late_ack_of_timed_out_command_is_consumed() {
+    let transport = open_port("loop://acks-late");
+    let tracker = SlcanAckTracker::default();
+    let results = Results::default();
+
+    assert_eq!(tracker.query(&transport, b"V\r", Duration::from_millis(10)), Ok(None));
+    tracker
+      .write_with_callback(&transport, b"N\r", record(&results, "N"))
+      .unwrap();
+
+    // Опоздавший ответ на V достаётся V, а не следующей команде
+    tracker.push(b"V1013\r");
+    assert!(results.lock().unwrap().is_empty());
+    assert_eq!(tracker.command_result("V"), Some(true));
+
+    tracker.push(b"NA1B2\r");
+    assert_eq!(*results.lock().unwrap(), [("N", Ok("NA1B2".to_string()))]);
+  }
+
+  #[test]
+  fn write_confirmed_reports_missing_ack() {
+    let transport = open_port("loop://acks-confirmed");
+    let tracker = SlcanAckTracker::default();
+
+    assert_eq!(tracker.write_confirmed(&transport, b"O\r", Duration::from_millis(10)), Ok(false));
+  }
+
+  #[test]
+  fn expired_commands_are_pruned_before_next_write() {
+    let transport = open_port("loop://acks-expired");
+    let tracker = SlcanAckTracker::default();
+    let results = Results::default();
+    tracker
+      .write_with_callback(&transport, b"O\r", record(&results, "O"))
+      .unwrap();
+
+    tracker.prune_expired(Instant::now() + SLCAN_ACK_EXPIRY + Duration::from_secs(1));
+    tracker
+      .write_with_callback(&transport, b"C\r", record(&results, "C"))
+      .unwrap();
+    tracker.push(b"\r");
+
+    assert_eq!(*results.lock().unwrap(), [("C", Ok(String::new()))]);
+  }
+
+  #[test]
+  fn reset_drops_pending_commands() {
+    let transport = open_port("loop://acks-reset");
+    let tracker = SlcanAckTracker::default();
+    let results = Results::default();
+    tracker
+      .write_with_callback(&transport, b"O\r", record(&results, "O"))
+      .unwrap();
+    tracker.push(b"F0");
+
+    tracker.reset();
+    tracker.push(b"\r");
+    tracker
+      .write_with_callback(&transport, b"C\r", record(&results, "C"))
+      .unwrap();
+    tracker.push(b"\r");
+
+    assert_eq!(*results.lock().unwrap(), [("C", Ok(String::new()))]);
+  }
+}
@@ -0,0 +1,205 @@
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::can_filter::adapter_filter_commands;
+use crate::codec::write_sequence;
+use crate::connections::ConnectionRegistry;
+use crate::models::{AdapterInfo, SerialConfig};
+use crate::poe_canable::CanMode;
+use crate::slcan_ack::SlcanAckTracker;
+use crate::transports::Transport;
+use crate::{log, LogLevel};
+
+/// Время ожидания ответа адаптера на запрос сведений
+const QUERY_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Отправляет запрос сведений адаптеру и возвращает ответ без буквы команды
+///
+/// # Returns
+/// * `Some(String)` - непустой ответ
+/// * `None` - адаптер не ответил, отклонил запрос или ответ пустой
+fn query_field(tracker: &SlcanAckTracker, transport: &dyn Transport, command: char) -> Option<String> {
+  let reply = match tracker.query(transport, format!("{}\r", command).as_bytes(), QUERY_TIMEOUT) {
+    Ok(reply) => reply?,
+    Err(e) => {
+      log(LogLevel::Info, "identify_adapter", format!("Запрос {} не выполнен: {}", command, e));
+      return None;
+    },
+  };
+  let value = reply
+    .strip_prefix(command)
+    .unwrap_or(&reply)
+    .trim()
+    .to_string();
+  (!value.is_empty()).then_some(value)
+}
+
+/// Определяет по ответу на `V`, поддерживает ли прошивка фильтр приёма `M`/`m`
+///
+/// Фильтр (код и маска SJA1000) есть в прошивках Lawicel, которые отвечают на `V` четырьмя
+/// цифрами версии, например `V1013`. В прошивках CANable команды `M`/`m` задают режим работы
+/// (`M1` - только прослушивание), а на `V` возвращается строка сборки, поэтому для остальных
+/// ответов фильтр адаптера не поддерживается.
+///
+/// # Returns
+/// * `Some(bool)` - поддержка фильтра по ответу адаптера
+/// * `None` - адаптер не ответил на `V`
+fn acceptance_filter_supported(info: &AdapterInfo) -> Option<bool> {
+  let hardware_version = info.hardware_version.as_ref()?;
+  let mentions_canable = [&info.hardware_version, &info.firmware_version]
+    .into_iter()
+    .flatten()
+    .any(|version| version.to_ascii_uppercase().contains("CANABLE"));
+  let lawicel_version = hardware_version.len() == 4 && hardware_version.chars().all(|c| c.is_ascii_digit());
+  Some(lawicel_version && !mentions_canable)
+}
+
+/// Запрашивает у адаптера SLCAN версии оборудования и прошивки и серийный номер
+///
+/// Вызывается после начала чтения порта, когда подтверждения последовательности
+/// инициализации уже получены: отклонённые адаптером команды скорости и отсутствие
+/// поддержки CAN FD при выбранном `POECanableFD` возвращаются как предупреждения, как и
+/// запрошенный фильтр адаптера, если прошивка его не поддерживает.
+///
+/// # Arguments
+/// * `tracker` - подтверждения команд адаптера на порту
+/// * `transport` - транспорт открытого порта
+/// * `config` - конфигурация подключения
+///
+/// # Returns
+/// * `AdapterInfo` - сведения об адаптере, отсутствующие ответы - `None`
+pub fn identify_adapter(tracker: &SlcanAckTracker, transport: &dyn Transport, config: &SerialConfig) -> AdapterInfo {
+  let mut info = AdapterInfo {
+    hardware_version: query_field(tracker, transport, 'V'),
+    firmware_version: query_field(tracker, transport, 'v'),
+    serial_number: query_field(tracker, transport, 'N'),
+    ..AdapterInfo::default()
+  };
+  log(
+    LogLevel::Info,
+    "identify_adapter",
+    format!(
+      "Адаптер на порту {}: оборудование {:?}, прошивка {:?}, серийный номер {:?}",
+      config.path, info.hardware_version, info.firmware_version, info.serial_number
+    ),
+  );
+
+  let mode = CanMode::from_protocol(&config.protocol).unwrap_or(CanMode::Classic);
+  let bitrates = match mode {
+    CanMode::Classic => vec![("CAN bitrate", &config.can_bitrate)],
+    CanMode::Fd => vec![("CAN FD bitrate", &config.canfd_bitrate), ("CAN FD data bitrate", &config.canfd_data_bitrate)],
+  };
+  for (label, bitrate) in bitrates {
+    let Some(bitrate) = bitrate else {
+      continue;
+    };
+    if tracker.command_result(bitrate) == Some(false) {
+      info
+        .warnings
+        .push(format!("Adapter does not support {} {}", label, bitrate));
+    }
+  }
+
+  // Прошивку с CAN FD выдаёт принятая команда скорости данных или упоминание FD в версии
+  let data_bitrate_accepted = config
+    .canfd_data_bitrate
+    .as_deref()
+    .filter(|_| mode == CanMode::Fd)
+    .and_then(|bitrate| tracker.command_result(bitrate));
+  let mentions_fd = [&info.hardware_version, &info.firmware_version]
+    .into_iter()
+    .flatten()
+    .any(|version| version.to_ascii_uppercase().contains("FD"));
+  info.fd_supported = data_bitrate_accepted.or(mentions_fd.then_some(true));
+
+  if mode == CanMode::Fd && info.fd_supported == Some(false) {
+    info
+      .warnings
+      .push(format!("Adapter firmware does not support CAN FD, but {} is selected", mode.protocol()));
+  }
+
+  info.acceptance_filter_supported = acceptance_filter_supported(&info);
+  let filters_set = config
+    .can_filters
+    .as_ref()
+    .is_some_and(|filters| !filters.is_empty());
+  if config.can_adapter_filter.unwrap_or(false) && filters_set && info.acceptance_filter_supported != Some(true) {
+    info
+      .warnings
+      .push("Adapter firmware does not support the M/m acceptance filter, frames are filtered in software only".to_string());
+  }
+
+  for warning in info.warnings.iter() {
+    log(
+      LogLevel::Warn,
+      "identify_adapter",
+      format!("Предупреждение для порта {}: {}", config.path, warning),
+    );
+  }
+  info
+}
+
+/// Задаёт фильтр приёма на адаптере SLCAN, если он запрошен и поддерживается прошивкой
+///
+/// Фильтр задаётся при закрытом канале, поэтому канал закрывается и открывается заново.
+/// Без подтверждённой поддержки (сведения об адаптере из `identify_adapter`) фреймы
+/// фильтруются только программно. Вызывается после подключения, после переподключения порта
+/// и после повторного открытия канала при bus-off.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `transport` - транспорт открытого порта
+/// * `config` - конфигурация подключения
+///
+/// # Returns
+/// * `Ok(())` - фильтр задан или не требуется
+/// * `Err(String)` - ошибка записи в порт
+pub fn apply_adapter_filter(app: &AppHandle<Wry>, transport: &dyn Transport, config: &SerialConfig) -> Result<(), String> {
+  if !config.can_adapter_filter.unwrap_or(false) {
+    return Ok(());
+  }
+  let supported = app
+    .state::<ConnectionRegistry>()
+    .adapter_info(&config.path)
+    .and_then(|info| info.acceptance_filter_supported);
+  if supported != Some(true) {
+    log(
+      LogLevel::Info,
+      "apply_adapter_filter",
+      format!("Фильтр адаптера на порту {} не используется, фильтрация только программная", config.path),
+    );
+    return Ok(());
+  }
+  let Some(commands) = adapter_filter_commands(config.can_filters.as_deref().unwrap_or_default()) else {
+    return Ok(());
+  };
+
+  let mut sequence = vec![b"C\r".to_vec()];
+  sequence.extend(commands);
+  sequence.push(b"O\r".to_vec());
+  write_sequence(app, transport, config, sequence, "apply_adapter_filter")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn adapter(hardware_version: Option<&str>, firmware_version: Option<&str>) -> AdapterInfo {
+    AdapterInfo {
+      hardware_version: hardware_version.map(str::to_string),
+      firmware_version: firmware_version.map(str::to_string),
+      ..AdapterInfo::default()
+    }
+  }
+
+  #[test]
+  fn acceptance_filter_only_on_lawicel_firmware() {
+    assert_eq!(acceptance_filter_supported(&adapter(Some("1013"), None)), Some(true));
+    assert_eq!(acceptance_filter_supported(&adapter(Some("1013"), Some("CANable 2.0"))), Some(false));
+    assert_eq!(
+      acceptance_filter_supported(&adapter(Some("b3a7f2e github.com/normaldotcom/canable-fw.git"), None)),
+      Some(false)
+    );
+    assert_eq!(acceptance_filter_supported(&adapter(None, Some("1013"))), None);
+  }
+}
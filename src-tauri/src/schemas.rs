@@ -0,0 +1,226 @@
+use jsonschema::Validator;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{command, AppHandle, Manager, Wry};
+
+use crate::dictionary::dictionary;
+use crate::{log, LogLevel};
+
+/// Каталог схем в каталоге настроек приложения
+const SCHEMAS_DIR_NAME: &str = "schemas";
+
+/// Схема значения одного аргумента
+struct ArgumentSchema {
+  /// Файл, из которого загружена схема
+  file_name: String,
+  schema: serde_json::Value,
+  validator: Validator,
+}
+
+lazy_static! {
+  /// Схемы значений по коду аргумента, общие для всех портов
+  static ref SCHEMAS: RwLock<HashMap<u32, ArgumentSchema>> = RwLock::new(HashMap::new());
+}
+
+/// Описание загруженной схемы для фронтенда
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ArgumentSchemaInfo {
+  pub argument_code: u32,
+  pub argument_name: Option<String>,
+  pub file_name: String,
+  pub schema: serde_json::Value,
+}
+
+/// Проверяет значение аргумента по его схеме
+///
+/// Аргументы без схемы не проверяются.
+///
+/// # Arguments
+/// * `argument_code` - код аргумента
+/// * `value` - значение (текст JSON)
+///
+/// # Returns
+/// * `Ok(())` - схемы нет или значение ей соответствует
+/// * `Err(String)` - значение не является JSON или не соответствует схеме
+pub fn validate_value(argument_code: u32, value: &str) -> Result<(), String> {
+  check_value(argument_code, &argument_code.to_string(), value)
+}
+
+/// Проверяет значение по схеме аргумента, `label` - имя аргумента в тексте ошибки
+fn check_value(argument_code: u32, label: &str, value: &str) -> Result<(), String> {
+  let schemas = SCHEMAS.read().unwrap();
+  let Some(schema) = schemas.get(&argument_code) else {
+    return Ok(());
+  };
+
+  let instance: serde_json::Value = serde_json::from_str(value).map_err(|e| format!("Value of argument {} is not valid JSON: {}", label, e))?;
+  let errors: Vec<String> = schema
+    .validator
+    .iter_errors(&instance)
+    .map(|error| {
+      let path = error.instance_path.to_string();
+      if path.is_empty() {
+        error.to_string()
+      } else {
+        format!("{}: {}", path, error)
+      }
+    })
+    .collect();
+  if errors.is_empty() {
+    return Ok(());
+  }
+
+  Err(format!(
+    "Value of argument {} does not match schema {}: {}",
+    label,
+    schema.file_name,
+    errors.join("; ")
+  ))
+}
+
+/// Проверяет значение аргумента POESerial, заданного именем
+///
+/// # Returns
+/// * `Ok(())` - аргумента нет в словаре, схемы нет или значение ей соответствует
+/// * `Err(String)` - значение не соответствует схеме
+pub fn validate_named_value(argument: &str, value: &[u8]) -> Result<(), String> {
+  match dictionary().argument_code(argument) {
+    Some(code) => check_value(code, argument, &String::from_utf8_lossy(value)),
+    None => Ok(()),
+  }
+}
+
+/// Определяет код аргумента по имени файла схемы: `24.json` или `ModuleConfig.json`
+fn argument_code_from_file(path: &Path) -> Option<u32> {
+  let stem = path.file_stem()?.to_str()?;
+  stem
+    .parse::<u32>()
+    .ok()
+    .or_else(|| dictionary().argument_code(stem))
+}
+
+/// Путь к каталогу схем в каталоге настроек приложения
+fn schemas_dir(app: &AppHandle<Wry>) -> Result<PathBuf, String> {
+  app
+    .path()
+    .app_config_dir()
+    .map(|dir| dir.join(SCHEMAS_DIR_NAME))
+    .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+/// Загружает схемы значений аргументов из каталога настроек, создавая каталог при его отсутствии
+///
+/// Файлы с ошибками пропускаются и записываются в лог, остальные схемы загружаются.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+///
+/// # Returns
+/// * `Ok(usize)` - число загруженных схем
+/// * `Err(String)` - каталог схем недоступен, текущие схемы не меняются
+pub fn load_schemas(app: &AppHandle<Wry>) -> Result<usize, String> {
+  let dir = schemas_dir(app)?;
+  fs::create_dir_all(&dir).map_err(|e| format!("Failed to create schemas directory: {}", e))?;
+  let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read schemas directory: {}", e))?;
+
+  let mut loaded = HashMap::new();
+  for entry in entries.flatten() {
+    let path = entry.path();
+    if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+      continue;
+    }
+    let file_name = path
+      .file_name()
+      .unwrap_or_default()
+      .to_string_lossy()
+      .to_string();
+
+    let Some(code) = argument_code_from_file(&path) else {
+      log(
+        LogLevel::Warn,
+        "load_schemas",
+        format!("Схема {} пропущена: аргумент не найден в словаре", file_name),
+      );
+      continue;
+    };
+    let schema = match fs::read_to_string(&path)
+      .map_err(|e| e.to_string())
+      .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string()))
+    {
+      Ok(schema) => schema,
+      Err(e) => {
+        log(LogLevel::Err, "load_schemas", format!("Не удалось прочитать схему {}: {}", file_name, e));
+        continue;
+      },
+    };
+    let validator = match jsonschema::validator_for(&schema) {
+      Ok(validator) => validator,
+      Err(e) => {
+        log(LogLevel::Err, "load_schemas", format!("Некорректная схема {}: {}", file_name, e));
+        continue;
+      },
+    };
+
+    if let Some(previous) = loaded.insert(
+      code,
+      ArgumentSchema {
+        file_name: file_name.clone(),
+        schema,
+        validator,
+      },
+    ) {
+      log(
+        LogLevel::Warn,
+        "load_schemas",
+        format!("Схема {} заменяет {} для аргумента {}", file_name, previous.file_name, code),
+      );
+    }
+  }
+
+  let count = loaded.len();
+  log(
+    LogLevel::Info,
+    "load_schemas",
+    format!("Загружено схем значений: {} из {}", count, dir.display()),
+  );
+  *SCHEMAS.write().unwrap() = loaded;
+  Ok(count)
+}
+
+/// Возвращает загруженные схемы значений аргументов.
+///
+/// # Returns
+/// * `Vec<ArgumentSchemaInfo>` - схемы, упорядоченные по коду аргумента
+#[command]
+pub fn get_schemas() -> Vec<ArgumentSchemaInfo> {
+  let dictionary = dictionary();
+  let mut schemas: Vec<ArgumentSchemaInfo> = SCHEMAS
+    .read()
+    .unwrap()
+    .iter()
+    .map(|(code, schema)| ArgumentSchemaInfo {
+      argument_code: *code,
+      argument_name: dictionary.argument_name(*code),
+      file_name: schema.file_name.clone(),
+      schema: schema.schema.clone(),
+    })
+    .collect();
+  schemas.sort_by_key(|schema| schema.argument_code);
+  schemas
+}
+
+/// Перечитывает схемы из каталога настроек после их редактирования.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+///
+/// # Returns
+/// * `Ok(usize)` - число загруженных схем
+/// * `Err(String)` - каталог схем недоступен
+#[command]
+pub fn reload_schemas(app: AppHandle<Wry>) -> Result<usize, String> {
+  load_schemas(&app)
+}
@@ -0,0 +1,347 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter, Manager, State, Wry};
+
+use crate::connections::ConnectionRegistry;
+use crate::models::{HeapSample, HeapTelemetryReport};
+use crate::{log, LogLevel};
+
+/// Максимальное число хранимых отсчётов свободной памяти на порт
+const MAX_HEAP_SAMPLES: usize = 3600;
+
+/// Число последних отсчётов, по которым оценивается тренд
+const TREND_WINDOW: usize = 120;
+
+/// Минимальное число отсчётов в окне для подозрения на утечку
+const LEAK_MIN_SAMPLES: usize = 30;
+
+/// Минимальная длительность окна для подозрения на утечку, мс
+const LEAK_MIN_SPAN_MS: u64 = 60_000;
+
+/// Скорость падения свободной памяти, начиная с которой подозревается утечка, байт/мин
+const LEAK_SLOPE_BYTES_PER_MIN: f64 = -64.0;
+
+/// Минимальный период событий heap-telemetry, мс
+const MIN_EVENT_INTERVAL_MS: u64 = 200;
+
+/// Отсчёт свободной памяти с монотонным временем приёма
+struct TimedHeapSample {
+  /// Время от первого отсчёта по монотонным часам, мс
+  elapsed_ms: u64,
+  sample: HeapSample,
+}
+
+/// История свободной памяти устройства, получаемой в пакетах POESerial
+///
+/// Метка времени отсчёта для фронтенда берётся по системным часам, а тренд считается
+/// по монотонному времени, чтобы перевод системных часов не искажал наклон.
+#[derive(Default)]
+pub struct HeapTelemetry {
+  origin: Option<Instant>,
+  samples: VecDeque<TimedHeapSample>,
+  min: Option<u32>,
+  max: Option<u32>,
+  leak_suspected: bool,
+}
+
+impl HeapTelemetry {
+  /// Добавляет отсчёт и пересчитывает признак утечки
+  ///
+  /// # Returns
+  /// * `true` - утечка заподозрена этим отсчётом (признак только что установился)
+  pub fn record(&mut self, free_heap: u32) -> bool {
+    self.record_at(free_heap, Instant::now())
+  }
+
+  /// Добавляет отсчёт, принятый в момент `now` по монотонным часам
+  fn record_at(&mut self, free_heap: u32, now: Instant) -> bool {
+    let origin = *self.origin.get_or_insert(now);
+    self.samples.push_back(TimedHeapSample {
+      elapsed_ms: now.saturating_duration_since(origin).as_millis() as u64,
+      sample: HeapSample {
+        timestamp: chrono::Local::now().timestamp_millis() as u64,
+        free_heap,
+      },
+    });
+    if self.samples.len() > MAX_HEAP_SAMPLES {
+      self.samples.pop_front();
+    }
+    self.min = Some(self.min.map_or(free_heap, |min| min.min(free_heap)));
+    self.max = Some(self.max.map_or(free_heap, |max| max.max(free_heap)));
+
+    let was_suspected = self.leak_suspected;
+    self.leak_suspected = self.detect_leak();
+    self.leak_suspected && !was_suspected
+  }
+
+  /// Окно последних отсчётов для оценки тренда
+  fn window(&self) -> impl Iterator<Item = &TimedHeapSample> {
+    self
+      .samples
+      .iter()
+      .skip(self.samples.len().saturating_sub(TREND_WINDOW))
+  }
+
+  /// Наклон линейной регрессии свободной памяти по времени в окне, байт/мин
+  fn trend(&self) -> Option<f64> {
+    let window: Vec<&TimedHeapSample> = self.window().collect();
+    if window.len() < 2 {
+      return None;
+    }
+
+    let origin = window[0].elapsed_ms;
+    let count = window.len() as f64;
+    let mean_x = window
+      .iter()
+      .map(|sample| sample.elapsed_ms.saturating_sub(origin) as f64)
+      .sum::<f64>()
+      / count;
+    let mean_y = window
+      .iter()
+      .map(|sample| sample.sample.free_heap as f64)
+      .sum::<f64>()
+      / count;
+
+    let mut covariance = 0.0;
+    let mut variance = 0.0;
+    for sample in window.iter() {
+      let dx = sample.elapsed_ms.saturating_sub(origin) as f64 - mean_x;
+      covariance += dx * (sample.sample.free_heap as f64 - mean_y);
+      variance += dx * dx;
+    }
+    if variance == 0.0 {
+      return None;
+    }
+
+    // Наклон в байтах за миллисекунду переводим в байты за минуту
+    Some(covariance / variance * 60_000.0)
+  }
+
+  /// Утечка подозревается, если память устойчиво падает на достаточно длинном окне
+  fn detect_leak(&self) -> bool {
+    let window: Vec<&TimedHeapSample> = self.window().collect();
+    if window.len() < LEAK_MIN_SAMPLES {
+      return false;
+    }
+    let span = window[window.len() - 1]
+      .elapsed_ms
+      .saturating_sub(window[0].elapsed_ms);
+    if span < LEAK_MIN_SPAN_MS {
+      return false;
+    }
+    self
+      .trend()
+      .is_some_and(|trend| trend <= LEAK_SLOPE_BYTES_PER_MIN)
+  }
+
+  /// Формирует отчёт для фронтенда
+  ///
+  /// # Arguments
+  /// * `path` - путь к порту
+  /// * `max_samples` - сколько последних отсчётов включить в отчёт, `None` - все
+  pub fn report(&self, path: &str, max_samples: Option<usize>) -> HeapTelemetryReport {
+    let skip = max_samples.map_or(0, |max_samples| self.samples.len().saturating_sub(max_samples));
+    HeapTelemetryReport {
+      path: path.to_string(),
+      current: self.samples.back().map(|sample| sample.sample.free_heap),
+      min: self.min,
+      max: self.max,
+      trend_bytes_per_min: self.trend(),
+      leak_suspected: self.leak_suspected,
+      sample_count: self.samples.len(),
+      samples: self
+        .samples
+        .iter()
+        .skip(skip)
+        .map(|sample| sample.sample.clone())
+        .collect(),
+    }
+  }
+}
+
+/// Учитывает свободную память из принятого пакета и предупреждает о подозрении на утечку
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `telemetry` - история свободной памяти порта
+/// * `path` - путь к порту
+/// * `free_heap` - поле свободной памяти из пакета
+pub fn record_free_heap(app: &AppHandle<Wry>, telemetry: &Mutex<HeapTelemetry>, path: &str, free_heap: &str) {
+  let Ok(free_heap) = free_heap.trim().parse::<u32>() else {
+    return;
+  };
+
+  let mut telemetry = telemetry.lock().unwrap();
+  if !telemetry.record(free_heap) {
+    return;
+  }
+
+  let trend = telemetry.trend().unwrap_or_default();
+  log(
+    LogLevel::Warn,
+    "record_free_heap",
+    format!("Подозрение на утечку памяти устройства на порту {}: {:.0} байт/мин", path, trend),
+  );
+  if let Err(e) = app.emit(
+    "app-status",
+    format!("Possible memory leak on {}: free heap is falling by {:.0} bytes/min", path, trend),
+  ) {
+    eprintln!("Failed to emit data: {}", e);
+  }
+}
+
+/// Периодическая публикация телеметрии порта
+#[derive(Default)]
+pub struct HeapTelemetryEvents {
+  /// Период событий, мс; 0 - публикация выключена
+  interval_ms: Arc<AtomicU64>,
+  running: Arc<AtomicBool>,
+}
+
+/// Возвращает историю свободной памяти устройства с минимумом, максимумом и трендом.
+///
+/// # Arguments
+/// * `connections` - реестр открытых портов
+/// * `path` - путь к порту
+/// * `max_samples` - сколько последних отсчётов вернуть, по умолчанию все
+///
+/// # Returns
+/// * `Ok(HeapTelemetryReport)` - телеметрия свободной памяти
+/// * `Err(String)` - порт не открыт
+#[command]
+pub fn get_heap_telemetry(connections: State<'_, ConnectionRegistry>, path: String, max_samples: Option<usize>) -> Result<HeapTelemetryReport, String> {
+  let telemetry = connections
+    .heap_telemetry(&path)
+    .ok_or_else(|| format!("Port {} is not open", path))?;
+  let report = telemetry.lock().unwrap().report(&path, max_samples);
+  Ok(report)
+}
+
+/// Включает или выключает периодическое событие `heap-telemetry` для порта.
+///
+/// Событие содержит отчёт с последним отсчётом и публикуется, пока порт открыт.
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `path` - путь к порту
+/// * `interval_ms` - период событий, `None` или 0 - выключить
+///
+/// # Returns
+/// * `Ok(())` - настройка применена
+/// * `Err(String)` - порт не открыт
+#[command]
+pub fn set_heap_telemetry_interval(app: AppHandle<Wry>, path: String, interval_ms: Option<u64>) -> Result<(), String> {
+  let connections = app.state::<ConnectionRegistry>();
+  let (telemetry, events) = connections
+    .heap_telemetry(&path)
+    .zip(connections.heap_telemetry_events(&path))
+    .ok_or_else(|| format!("Port {} is not open", path))?;
+
+  let interval_ms = interval_ms.unwrap_or(0);
+  let interval_ms = if interval_ms == 0 { 0 } else { interval_ms.max(MIN_EVENT_INTERVAL_MS) };
+  events.interval_ms.store(interval_ms, Ordering::SeqCst);
+  log(
+    LogLevel::Info,
+    "set_heap_telemetry_interval",
+    format!("Период событий телеметрии памяти для {}: {} мс", path, interval_ms),
+  );
+
+  if interval_ms == 0 || events.running.swap(true, Ordering::SeqCst) {
+    return Ok(());
+  }
+
+  let interval = events.interval_ms.clone();
+  let running = events.running.clone();
+  thread::spawn(move || {
+    loop {
+      let interval_ms = interval.load(Ordering::SeqCst);
+      if interval_ms == 0 {
+        break;
+      }
+      thread::sleep(Duration::from_millis(interval_ms));
+
+      // Поток завершается вместе с закрытием порта, в том числе если порт открыт заново
+      let current = app.state::<ConnectionRegistry>().heap_telemetry(&path);
+      if !current.is_some_and(|current| Arc::ptr_eq(&current, &telemetry)) {
+        break;
+      }
+      let report = telemetry.lock().unwrap().report(&path, Some(1));
+      if let Err(e) = app.emit("heap-telemetry", report) {
+        eprintln!("Failed to emit data: {}", e);
+      }
+    }
+    running.store(false, Ordering::SeqCst);
+  });
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Записывает отсчёты с заданным шагом по монотонным часам
+  fn record_series(telemetry: &mut HeapTelemetry, start: Instant, step: Duration, values: impl IntoIterator<Item = u32>) -> bool {
+    let mut suspected = false;
+    for (index, free_heap) in values.into_iter().enumerate() {
+      suspected |= telemetry.record_at(free_heap, start + step * index as u32);
+    }
+    suspected
+  }
+
+  #[test]
+  fn trend_is_measured_in_bytes_per_minute() {
+    let mut telemetry = HeapTelemetry::default();
+    record_series(&mut telemetry, Instant::now(), Duration::from_secs(1), (0..10).map(|index| 10_000 - index * 10));
+
+    let trend = telemetry.trend().unwrap();
+    assert!((trend + 600.0).abs() < 1e-6, "{}", trend);
+  }
+
+  #[test]
+  fn steady_decline_over_long_window_is_a_leak() {
+    let mut telemetry = HeapTelemetry::default();
+    let suspected = record_series(
+      &mut telemetry,
+      Instant::now(),
+      Duration::from_secs(3),
+      (0..40).map(|index| 50_000 - index * 100),
+    );
+
+    assert!(suspected);
+    assert!(telemetry.report("test", None).leak_suspected);
+  }
+
+  #[test]
+  fn short_or_flat_window_is_not_a_leak() {
+    let start = Instant::now();
+    let mut short = HeapTelemetry::default();
+    assert!(!record_series(
+      &mut short,
+      start,
+      Duration::from_millis(100),
+      (0..40).map(|index| 50_000 - index * 100)
+    ));
+
+    let mut flat = HeapTelemetry::default();
+    assert!(!record_series(&mut flat, start, Duration::from_secs(3), (0..40).map(|_| 50_000)));
+  }
+
+  #[test]
+  fn samples_out_of_order_do_not_panic() {
+    let start = Instant::now() + Duration::from_secs(10);
+    let mut telemetry = HeapTelemetry::default();
+    telemetry.record_at(1_000, start);
+    telemetry.record_at(900, start - Duration::from_secs(5));
+    telemetry.record_at(800, start + Duration::from_secs(5));
+
+    let report = telemetry.report("test", Some(2));
+    assert_eq!(report.sample_count, 3);
+    assert_eq!(report.samples.len(), 2);
+    assert_eq!(report.current, Some(800));
+    assert_eq!((report.min, report.max), (Some(800), Some(1_000)));
+  }
+}
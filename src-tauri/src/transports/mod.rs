@@ -0,0 +1,152 @@
+#[cfg(target_os = "linux")]
+pub mod pty;
+pub mod reconnect;
+pub mod rfc2217;
+pub mod serial_plugin;
+pub mod slcan;
+#[cfg(target_os = "linux")]
+pub mod socketcan;
+pub mod tcp;
+pub mod virtual_port;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Wry};
+
+use crate::models::SerialConfig;
+use crate::{log, LogLevel};
+#[cfg(target_os = "linux")]
+use pty::PtyTransport;
+use rfc2217::Rfc2217Transport;
+use serial_plugin::SerialPluginTransport;
+#[cfg(target_os = "linux")]
+use socketcan::SocketCanTransport;
+use tcp::TcpTransport;
+use virtual_port::VirtualTransport;
+
+/// Префикс пути для подключения по TCP (ser2net, ESP-Link)
+pub const TCP_PREFIX: &str = "tcp://";
+
+/// Префикс пути для подключения по TCP с управлением портом по RFC 2217
+pub const RFC2217_PREFIX: &str = "rfc2217://";
+
+/// Префикс пути для подключения к интерфейсу SocketCAN (Linux), например socketcan://vcan0
+pub const SOCKETCAN_PREFIX: &str = "socketcan://";
+
+/// Префикс пути порта на основе пары псевдотерминалов (Linux), например pty://1
+pub const PTY_PREFIX: &str = "pty://";
+
+/// Префикс пути виртуального порта, возвращающего записанные данные, например loop://1
+pub const LOOP_PREFIX: &str = "loop://";
+
+/// Префикс пути виртуального порта с симулятором устройства, например sim://poe
+pub const SIM_PREFIX: &str = "sim://";
+
+/// Обработчик принятых из транспорта данных
+pub type ReadHandler = Box<dyn Fn(Vec<u8>) + Send + Sync + 'static>;
+
+/// Общий интерфейс транспорта, через который протоколы работают с портом
+pub trait Transport: Send + Sync {
+  /// Путь к порту, с которым работает транспорт
+  fn path(&self) -> &str;
+
+  /// Открывает порт с заданной конфигурацией
+  fn open(&self, config: &SerialConfig) -> Result<(), String>;
+
+  /// Закрывает порт
+  fn close(&self) -> Result<(), String>;
+
+  /// Принудительно закрывает порт, если обычное закрытие не удалось
+  fn force_close(&self) -> Result<(), String>;
+
+  /// Записывает данные в порт
+  fn write(&self, data: &[u8]) -> Result<usize, String>;
+
+  /// Запускает чтение данных из порта
+  fn start_reading(&self) -> Result<(), String>;
+
+  /// Останавливает чтение данных из порта
+  fn stop_reading(&self) -> Result<(), String>;
+
+  /// Подписывает обработчик на принятые данные и возвращает ID подписки
+  fn subscribe(&self, handler: ReadHandler) -> u32;
+
+  /// Отписывает обработчик по ID подписки
+  fn unsubscribe(&self, id: u32);
+
+  /// Устанавливает линию DTR
+  fn write_dtr(&self, level: bool) -> Result<(), String>;
+
+  /// Устанавливает линию RTS
+  fn write_rts(&self, level: bool) -> Result<(), String>;
+
+  /// Транспорт сам восстанавливает соединение при пропаже устройства
+  fn auto_reconnect(&self) -> bool {
+    false
+  }
+}
+
+/// Список подписчиков на принятые данные для транспортов с собственным потоком чтения
+#[derive(Default)]
+pub struct ReadHandlers {
+  next_id: AtomicU32,
+  handlers: Mutex<HashMap<u32, Arc<ReadHandler>>>,
+}
+
+impl ReadHandlers {
+  /// Добавляет обработчик и возвращает ID подписки
+  pub fn subscribe(&self, handler: ReadHandler) -> u32 {
+    let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    self.handlers.lock().unwrap().insert(id, Arc::new(handler));
+    id
+  }
+
+  /// Удаляет обработчик по ID подписки
+  pub fn unsubscribe(&self, id: u32) {
+    self.handlers.lock().unwrap().remove(&id);
+  }
+
+  /// Передаёт принятые данные всем подписчикам
+  pub fn dispatch(&self, data: &[u8]) {
+    // Копируем список, чтобы обработчики могли подписываться и отписываться во время вызова
+    let handlers: Vec<Arc<ReadHandler>> = self.handlers.lock().unwrap().values().cloned().collect();
+    for handler in handlers {
+      handler(data.to_vec());
+    }
+  }
+}
+
+/// Создаёт транспорт, подходящий для указанного пути порта
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `path` - путь к порту
+///
+/// # Returns
+/// * `Arc<dyn Transport>` - созданный транспорт
+pub fn create_transport(app: &AppHandle<Wry>, path: &str) -> Arc<dyn Transport> {
+  log(LogLevel::Info, "create_transport", format!("Создание транспорта для порта: {}", path));
+
+  if let Some(address) = path.strip_prefix(TCP_PREFIX) {
+    return Arc::new(TcpTransport::new(app.clone(), path.to_string(), address.to_string()));
+  }
+  if let Some(address) = path.strip_prefix(RFC2217_PREFIX) {
+    return Arc::new(Rfc2217Transport::new(app.clone(), path.to_string(), address.to_string()));
+  }
+  #[cfg(target_os = "linux")]
+  if let Some(interface) = path.strip_prefix(SOCKETCAN_PREFIX) {
+    return Arc::new(SocketCanTransport::new(path.to_string(), interface.to_string()));
+  }
+  #[cfg(target_os = "linux")]
+  if path.starts_with(PTY_PREFIX) {
+    return Arc::new(PtyTransport::new(path.to_string()));
+  }
+  if path.starts_with(LOOP_PREFIX) {
+    return Arc::new(VirtualTransport::loopback(path.to_string()));
+  }
+  if let Some(device) = path.strip_prefix(SIM_PREFIX) {
+    return Arc::new(VirtualTransport::simulated(path.to_string(), device.to_string()));
+  }
+  Arc::new(SerialPluginTransport::new(app.clone(), path.to_string()))
+}
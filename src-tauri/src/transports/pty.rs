@@ -0,0 +1,433 @@
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::models::SerialConfig;
+use crate::transports::{ReadHandler, ReadHandlers, Transport};
+use crate::{log, LogLevel};
+
+/// Период проверки флага остановки в потоке чтения, мс
+const READ_POLL_INTERVAL_MS: libc::c_int = 100;
+
+lazy_static! {
+  /// Пути второй стороны открытых пар PTY (путь порта -> путь /dev/pts/N)
+  static ref PTY_PEERS: Arc<Mutex<HashMap<String, String>>> = Arc::new(Mutex::new(HashMap::new()));
+}
+
+/// Возвращает путь второй стороны пары PTY, открытой для порта
+///
+/// # Arguments
+/// * `path` - путь порта (pty://...)
+///
+/// # Returns
+/// * `Some(String)` - путь к подчинённому терминалу, который может открыть тестовый скрипт
+/// * `None` - пара для порта не открыта
+pub fn pty_peer_path(path: &str) -> Option<String> {
+  PTY_PEERS.lock().unwrap().get(path).cloned()
+}
+
+/// Создаёт пару псевдотерминалов в «сыром» режиме
+///
+/// # Returns
+/// * `Ok((OwnedFd, OwnedFd, String))` - ведущая сторона, подчинённая сторона и путь подчинённой стороны
+/// * `Err(String)` - ошибка создания пары
+fn open_pty_pair() -> Result<(OwnedFd, OwnedFd, String), String> {
+  let raw_master = unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC) };
+  if raw_master < 0 {
+    return Err(format!("Failed to open PTY master: {}", io::Error::last_os_error()));
+  }
+  let master = unsafe { OwnedFd::from_raw_fd(raw_master) };
+
+  if unsafe { libc::grantpt(master.as_raw_fd()) } < 0 || unsafe { libc::unlockpt(master.as_raw_fd()) } < 0 {
+    return Err(format!("Failed to unlock PTY: {}", io::Error::last_os_error()));
+  }
+
+  let mut name = [0 as libc::c_char; 128];
+  if unsafe { libc::ptsname_r(master.as_raw_fd(), name.as_mut_ptr(), name.len()) } != 0 {
+    return Err(format!("Failed to get PTY name: {}", io::Error::last_os_error()));
+  }
+  let peer_path = unsafe { CStr::from_ptr(name.as_ptr()) }
+    .to_string_lossy()
+    .to_string();
+
+  // Без эха и преобразования символов конца строки, чтобы данные проходили без изменений
+  let mut termios: libc::termios = unsafe { mem::zeroed() };
+  if unsafe { libc::tcgetattr(master.as_raw_fd(), &mut termios) } < 0 {
+    return Err(format!("Failed to get PTY attributes: {}", io::Error::last_os_error()));
+  }
+  unsafe { libc::cfmakeraw(&mut termios) };
+  if unsafe { libc::tcsetattr(master.as_raw_fd(), libc::TCSANOW, &termios) } < 0 {
+    return Err(format!("Failed to set PTY attributes: {}", io::Error::last_os_error()));
+  }
+
+  // Держим подчинённую сторону открытой, чтобы чтение не завершалось с EIO, пока тестовый скрипт не подключён
+  let peer_name = CString::new(peer_path.clone()).map_err(|e| e.to_string())?;
+  let raw_slave = unsafe { libc::open(peer_name.as_ptr(), libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC) };
+  if raw_slave < 0 {
+    return Err(format!("Failed to open PTY slave {}: {}", peer_path, io::Error::last_os_error()));
+  }
+  let slave = unsafe { OwnedFd::from_raw_fd(raw_slave) };
+
+  Ok((master, slave, peer_path))
+}
+
+/// Транспорт поверх пары псевдотерминалов (Linux) для интеграционного тестирования
+///
+/// Приложение работает с ведущей стороной пары, а путь подчинённой стороны
+/// передаётся тестовому скрипту, который играет роль устройства.
+pub struct PtyTransport {
+  path: String,
+  master: Mutex<Option<Arc<OwnedFd>>>,
+  slave: Mutex<Option<OwnedFd>>,
+  handlers: Arc<ReadHandlers>,
+  reading: Arc<AtomicBool>,
+  reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl PtyTransport {
+  pub fn new(path: String) -> Self {
+    Self {
+      path,
+      master: Mutex::new(None),
+      slave: Mutex::new(None),
+      handlers: Arc::new(ReadHandlers::default()),
+      reading: Arc::new(AtomicBool::new(false)),
+      reader: Mutex::new(None),
+    }
+  }
+
+  /// Возвращает ведущую сторону открытой пары
+  fn master(&self) -> Result<Arc<OwnedFd>, String> {
+    self
+      .master
+      .lock()
+      .unwrap()
+      .clone()
+      .ok_or_else(|| format!("Port {} is not open", self.path))
+  }
+
+  /// Освобождает пару и удаляет путь второй стороны из реестра
+  fn release(&self) -> bool {
+    PTY_PEERS.lock().unwrap().remove(&self.path);
+    self.slave.lock().unwrap().take();
+    self.master.lock().unwrap().take().is_some()
+  }
+}
+
+impl Transport for PtyTransport {
+  fn path(&self) -> &str {
+    &self.path
+  }
+
+  fn open(&self, _config: &SerialConfig) -> Result<(), String> {
+    let (master, slave, peer_path) = open_pty_pair()?;
+    *self.master.lock().unwrap() = Some(Arc::new(master));
+    *self.slave.lock().unwrap() = Some(slave);
+    PTY_PEERS
+      .lock()
+      .unwrap()
+      .insert(self.path.clone(), peer_path.clone());
+
+    log(
+      LogLevel::Info,
+      "PtyTransport",
+      format!("Открыта пара PTY для {}, вторая сторона: {}", self.path, peer_path),
+    );
+    Ok(())
+  }
+
+  fn close(&self) -> Result<(), String> {
+    self.stop_reading()?;
+    if !self.release() {
+      return Err(format!("Port {} is not open", self.path));
+    }
+    Ok(())
+  }
+
+  fn force_close(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    self.release();
+    Ok(())
+  }
+
+  fn write(&self, data: &[u8]) -> Result<usize, String> {
+    let master = self.master()?;
+    let mut written = 0;
+
+    while written < data.len() {
+      let result = unsafe { libc::write(master.as_raw_fd(), data[written..].as_ptr() as *const libc::c_void, data.len() - written) };
+      if result < 0 {
+        let error = io::Error::last_os_error();
+        if error.kind() == io::ErrorKind::Interrupted {
+          continue;
+        }
+        return Err(error.to_string());
+      }
+      written += result as usize;
+    }
+
+    Ok(written)
+  }
+
+  fn start_reading(&self) -> Result<(), String> {
+    let master = self.master()?;
+
+    if self.reading.swap(true, Ordering::SeqCst) {
+      log(LogLevel::Warn, "PtyTransport", format!("Чтение {} уже запущено", self.path));
+      return Ok(());
+    }
+
+    let handlers = self.handlers.clone();
+    let reading = self.reading.clone();
+    let path = self.path.clone();
+    let reader = thread::spawn(move || {
+      let mut buffer = [0u8; 1024];
+
+      while reading.load(Ordering::SeqCst) {
+        let mut poll_fd = libc::pollfd {
+          fd: master.as_raw_fd(),
+          events: libc::POLLIN,
+          revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut poll_fd, 1, READ_POLL_INTERVAL_MS) };
+        if ready < 0 {
+          let error = io::Error::last_os_error();
+          if error.kind() == io::ErrorKind::Interrupted {
+            continue;
+          }
+          log(LogLevel::Err, "PtyTransport", format!("Ошибка ожидания данных из {}: {}", path, error));
+          break;
+        }
+        if ready == 0 || poll_fd.revents & libc::POLLIN == 0 {
+          continue;
+        }
+
+        let size = unsafe { libc::read(master.as_raw_fd(), buffer.as_mut_ptr() as *mut libc::c_void, buffer.len()) };
+        if size < 0 {
+          let error = io::Error::last_os_error();
+          if matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted) {
+            continue;
+          }
+          log(LogLevel::Err, "PtyTransport", format!("Ошибка чтения из {}: {}", path, error));
+          break;
+        }
+        if size > 0 {
+          handlers.dispatch(&buffer[..size as usize]);
+        }
+      }
+
+      reading.store(false, Ordering::SeqCst);
+      log(LogLevel::Info, "PtyTransport", format!("Поток чтения {} остановлен", path));
+    });
+    *self.reader.lock().unwrap() = Some(reader);
+    Ok(())
+  }
+
+  fn stop_reading(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    if let Some(reader) = self.reader.lock().unwrap().take() {
+      reader
+        .join()
+        .map_err(|_| format!("Reader thread for {} panicked", self.path))?;
+    }
+    Ok(())
+  }
+
+  fn subscribe(&self, handler: ReadHandler) -> u32 {
+    self.handlers.subscribe(handler)
+  }
+
+  fn unsubscribe(&self, id: u32) {
+    self.handlers.unsubscribe(id);
+  }
+
+  fn write_dtr(&self, _level: bool) -> Result<(), String> {
+    Err(format!("DTR control is not supported by PTY port {}", self.path))
+  }
+
+  fn write_rts(&self, _level: bool) -> Result<(), String> {
+    Err(format!("RTS control is not supported by PTY port {}", self.path))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs::OpenOptions;
+  use std::io::{Read, Write};
+  use std::sync::mpsc;
+  use std::time::Duration;
+
+  use crate::poe_canable::{format_can_frame, process_poe_canable_data, reset_port_state, MessageData};
+  use crate::poe_serial::{encode_poe_serial_packet, process_poe_serial_data, PoeSerialData};
+  use crate::poe_serial_framer::PoeSerialFramer;
+  use crate::simple_serial::process_simple_serial_data;
+
+  const RECEIVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+  /// Открывает пару PTY и возвращает порт и открытую сторону устройства
+  fn open_with_peer(name: &str, protocol: &str) -> (PtyTransport, std::fs::File) {
+    let path = format!("{}{}", crate::transports::PTY_PREFIX, name);
+    let transport = PtyTransport::new(path.clone());
+    transport
+      .open(&SerialConfig::test(&path, protocol))
+      .unwrap();
+    let peer_path = pty_peer_path(&path).unwrap();
+    let peer = OpenOptions::new()
+      .read(true)
+      .write(true)
+      .open(&peer_path)
+      .unwrap();
+    (transport, peer)
+  }
+
+  #[test]
+  fn data_loops_between_port_and_peer() {
+    let path = format!("{}test-loop", crate::transports::PTY_PREFIX);
+    let transport = PtyTransport::new(path.clone());
+    transport
+      .open(&SerialConfig::test(&path, "SimpleSerial"))
+      .unwrap();
+    let peer_path = pty_peer_path(&path).unwrap();
+    let mut peer = OpenOptions::new()
+      .read(true)
+      .write(true)
+      .open(&peer_path)
+      .unwrap();
+
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    transport.subscribe(Box::new(move |data| {
+      let _ = sender.lock().unwrap().send(data);
+    }));
+    transport.start_reading().unwrap();
+
+    // Сценарий устройства: принять команду и ответить на неё
+    peer.write_all(b"hello\r\n\x00\xff").unwrap();
+    let mut received = Vec::new();
+    while received.len() < 9 {
+      received.extend(receiver.recv_timeout(Duration::from_secs(2)).unwrap());
+    }
+    assert_eq!(received, b"hello\r\n\x00\xff");
+
+    assert_eq!(transport.write(b"reply\r").unwrap(), 6);
+    let mut reply = [0u8; 6];
+    peer.read_exact(&mut reply).unwrap();
+    assert_eq!(&reply, b"reply\r");
+
+    transport.close().unwrap();
+    assert!(pty_peer_path(&path).is_none());
+    assert!(transport.write(b"x").is_err());
+    assert!(transport.close().is_err());
+  }
+
+  #[test]
+  fn simple_serial_lines_are_decoded_from_peer() {
+    let (transport, mut peer) = open_with_peer("test-simple", "SimpleSerial");
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    let buffer = Mutex::new(String::new());
+    transport.subscribe(Box::new(move |data| {
+      let mut buffer = buffer.lock().unwrap();
+      buffer.push_str(&String::from_utf8_lossy(&data));
+      let mut lines = Vec::new();
+      *buffer = process_simple_serial_data(&buffer, &mut lines, "test-simple", false).unwrap();
+      for line in lines {
+        let _ = sender.lock().unwrap().send(line);
+      }
+    }));
+    transport.start_reading().unwrap();
+
+    // Строка приходит двумя частями
+    peer.write_all(b"temp=2").unwrap();
+    peer.flush().unwrap();
+    thread::sleep(Duration::from_millis(50));
+    peer.write_all(b"5\nok\r").unwrap();
+    assert_eq!(receiver.recv_timeout(RECEIVE_TIMEOUT).unwrap(), "temp=25");
+    assert_eq!(receiver.recv_timeout(RECEIVE_TIMEOUT).unwrap(), "ok");
+
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn poe_serial_packets_are_decoded_from_peer() {
+    let (transport, mut peer) = open_with_peer("test-poe-serial", "POESerial");
+    let (sender, receiver) = mpsc::channel::<PoeSerialData>();
+    let sender = Mutex::new(sender);
+    let framer = Mutex::new(PoeSerialFramer::new(false));
+    transport.subscribe(Box::new(move |data| {
+      let mut framer = framer.lock().unwrap();
+      for packet in process_poe_serial_data(&data, &mut framer, "test-poe-serial", false, None) {
+        let _ = sender.lock().unwrap().send(packet);
+      }
+    }));
+    transport.start_reading().unwrap();
+
+    let mut script = encode_poe_serial_packet(b"OK!", b"ARG", b"{\"a\":1}", Some(180_000), false);
+    let mut corrupted = encode_poe_serial_packet(b"OK!", b"ARG", b"{\"a\":2}", None, false);
+    let value_at = corrupted.iter().position(|&byte| byte == b'2').unwrap();
+    corrupted[value_at] = b'3';
+    script.extend(corrupted);
+    peer.write_all(&script).unwrap();
+
+    let packet = serde_json::to_value(receiver.recv_timeout(RECEIVE_TIMEOUT).unwrap()).unwrap();
+    assert_eq!(packet["header"], "OK!");
+    assert_eq!(packet["argument"], "ARG");
+    assert_eq!(packet["value"], "{\"a\":1}");
+    assert_eq!(packet["crc_ok"], true);
+    assert_eq!(packet["free_heap_size"], "180000");
+
+    let packet = serde_json::to_value(receiver.recv_timeout(RECEIVE_TIMEOUT).unwrap()).unwrap();
+    assert_eq!(packet["value"], "{\"a\":3}");
+    assert_eq!(packet["crc_ok"], false);
+
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn poe_canable_frames_are_decoded_from_peer() {
+    let port = "test-poe-canable";
+    reset_port_state(port);
+    let (transport, mut peer) = open_with_peer(port, "POECanable");
+    let (sender, receiver) = mpsc::channel::<MessageData>();
+    let sender = Mutex::new(sender);
+    let buffer = Mutex::new(String::new());
+    transport.subscribe(Box::new(move |data| {
+      let mut buffer = buffer.lock().unwrap();
+      buffer.push_str(&String::from_utf8_lossy(&data));
+      let mut messages = Vec::new();
+      *buffer = process_poe_canable_data(&buffer, &mut messages, port, &[]).unwrap();
+      for (_, message) in messages {
+        let _ = sender.lock().unwrap().send(message);
+      }
+    }));
+    transport.start_reading().unwrap();
+
+    // Подтверждение адаптера и ответ OK! из двух фреймов: аргумент 0x12, адресат 0x02, отправитель 0x01
+    let can_id = (2 << 26) | (0x12 << 16) | (0x02 << 8) | 0x01;
+    let first = format_can_frame('T', can_id, Some(b"{\"a\":1".to_vec()), 6).unwrap();
+    let last = format_can_frame('T', can_id | (1 << 28), Some(b"}".to_vec()), 1).unwrap();
+    peer.write_all(format!("\r{}", first).as_bytes()).unwrap();
+    peer.flush().unwrap();
+    thread::sleep(Duration::from_millis(50));
+    peer.write_all(last.as_bytes()).unwrap();
+
+    let message = receiver.recv_timeout(RECEIVE_TIMEOUT).unwrap();
+    assert!(message.is_complete);
+    assert!(!message.is_remote);
+    assert_eq!(message.full_id.header_code, 2);
+    assert_eq!(message.full_id.argument_code, 0x12);
+    assert_eq!(message.full_id.target_id, 0x02);
+    assert_eq!(message.full_id.return_id, 0x01);
+    assert_eq!(message.can_data, b"{\"a\":1}");
+
+    transport.close().unwrap();
+    reset_port_state(port);
+  }
+}
@@ -0,0 +1,453 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::codec::init_protocol;
+use crate::connections::ConnectionRegistry;
+use crate::models::{ReconnectPolicy, ReconnectStatus, SerialConfig};
+use crate::ports::enumerate_ports;
+use crate::slcan_adapter::apply_adapter_filter;
+use crate::transports::{create_transport, ReadHandler, Transport};
+use crate::{log, LogLevel};
+
+/// Период проверки наличия устройства в системе
+const SUPERVISE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Пауза после открытия порта перед инициализацией адаптера
+const REOPEN_SETTLE_DELAY: Duration = Duration::from_millis(100);
+
+/// Подписчик с ID подписки на текущем внутреннем транспорте
+struct Subscription {
+  handler: Arc<ReadHandler>,
+  inner_id: u32,
+}
+
+/// Признак закрытия порта, ожидание которого прерывается при закрытии
+#[derive(Default)]
+struct StopSignal {
+  stopped: Mutex<bool>,
+  condvar: Condvar,
+}
+
+impl StopSignal {
+  /// Устанавливает признак и будит ожидающие потоки
+  fn stop(&self) {
+    *self.stopped.lock().unwrap() = true;
+    self.condvar.notify_all();
+  }
+
+  fn is_stopped(&self) -> bool {
+    *self.stopped.lock().unwrap()
+  }
+
+  /// Ждёт закрытия порта не дольше `timeout`
+  ///
+  /// # Returns
+  /// * `true` - порт закрыт
+  fn wait(&self, timeout: Duration) -> bool {
+    let stopped = self.stopped.lock().unwrap();
+    let (stopped, _) = self
+      .condvar
+      .wait_timeout_while(stopped, timeout, |stopped| !*stopped)
+      .unwrap();
+    *stopped
+  }
+}
+
+/// Транспорт, который восстанавливает соединение после пропажи устройства
+///
+/// Оборачивает транспорт серийного порта и следит за его наличием в системе. Когда устройство
+/// пропадает (например, при перезагрузке платы), порт переоткрывается по тому же пути
+/// или по серийному номеру USB, адаптер CAN инициализируется заново, а обработчики
+/// протоколов переподписываются. Для фронтенда путь и ID подписок не меняются.
+/// Если все попытки исчерпаны, порт удаляется из реестра подключений.
+pub struct ReconnectingTransport {
+  app: AppHandle<Wry>,
+  path: String,
+  config: SerialConfig,
+  policy: ReconnectPolicy,
+  serial_number: Option<String>,
+  inner: Mutex<Arc<dyn Transport>>,
+  subscriptions: Mutex<HashMap<u32, Subscription>>,
+  next_id: AtomicU32,
+  reading: AtomicBool,
+  closed: Arc<StopSignal>,
+  supervisor: Mutex<Option<JoinHandle<()>>>,
+  me: Weak<ReconnectingTransport>,
+}
+
+impl ReconnectingTransport {
+  /// Создаёт транспорт с переподключением поверх транспорта порта
+  ///
+  /// # Arguments
+  /// * `app` - дескриптор приложения Tauri
+  /// * `inner` - транспорт порта, созданный для `config.path`
+  /// * `config` - конфигурация подключения
+  /// * `policy` - политика переподключения
+  pub fn new(app: AppHandle<Wry>, inner: Arc<dyn Transport>, config: SerialConfig, policy: ReconnectPolicy) -> Arc<Self> {
+    // Серийный номер запоминаем сразу, пока устройство подключено
+    let serial_number = enumerate_ports()
+      .unwrap_or_default()
+      .into_iter()
+      .find(|port| port.path == config.path)
+      .and_then(|port| port.serial_number);
+
+    log(
+      LogLevel::Info,
+      "ReconnectingTransport",
+      format!("Переподключение для {} включено, серийный номер: {:?}", config.path, serial_number),
+    );
+
+    Arc::new_cyclic(|me| Self {
+      app,
+      path: config.path.clone(),
+      config,
+      policy,
+      serial_number,
+      inner: Mutex::new(inner),
+      subscriptions: Mutex::new(HashMap::new()),
+      next_id: AtomicU32::new(0),
+      reading: AtomicBool::new(false),
+      closed: Arc::new(StopSignal::default()),
+      supervisor: Mutex::new(None),
+      me: me.clone(),
+    })
+  }
+
+  /// Возвращает текущий внутренний транспорт
+  fn inner(&self) -> Arc<dyn Transport> {
+    self.inner.lock().unwrap().clone()
+  }
+
+  /// Подписывает обработчик на внутренний транспорт
+  fn subscribe_inner(inner: &Arc<dyn Transport>, handler: &Arc<ReadHandler>) -> u32 {
+    let handler = handler.clone();
+    inner.subscribe(Box::new(move |data| handler(data)))
+  }
+
+  /// Запускает поток наблюдения за устройством
+  fn start_supervisor(&self) {
+    let mut supervisor = self.supervisor.lock().unwrap();
+    if supervisor.is_some() {
+      return;
+    }
+
+    let me = self.me.clone();
+    let closed = self.closed.clone();
+    *supervisor = Some(thread::spawn(move || {
+      while !closed.wait(SUPERVISE_INTERVAL) {
+        let Some(transport) = me.upgrade() else {
+          break;
+        };
+        if transport.device_present() {
+          continue;
+        }
+        if !transport.reconnect() {
+          if !closed.is_stopped() {
+            transport.release();
+          }
+          break;
+        }
+      }
+    }));
+  }
+
+  /// Проверяет, что устройство текущего внутреннего транспорта есть в системе
+  fn device_present(&self) -> bool {
+    let device_path = self.inner().path().to_string();
+    match enumerate_ports() {
+      Ok(ports) => ports.iter().any(|port| port.path == device_path),
+      // При ошибке перечисления портов не считаем устройство пропавшим
+      Err(_) => true,
+    }
+  }
+
+  /// Ищет путь устройства: по серийному номеру USB или по исходному пути
+  fn find_device_path(&self) -> Option<String> {
+    let ports = enumerate_ports().ok()?;
+    if self.policy.match_serial_number {
+      if let Some(serial_number) = &self.serial_number {
+        return ports
+          .into_iter()
+          .find(|port| port.serial_number.as_ref() == Some(serial_number))
+          .map(|port| port.path);
+      }
+    }
+    ports
+      .into_iter()
+      .find(|port| port.path == self.path)
+      .map(|port| port.path)
+  }
+
+  /// Публикует состояние переподключения
+  fn emit_status(&self, event: &str, device_path: Option<String>, attempt: u32) {
+    let status = ReconnectStatus {
+      path: self.path.clone(),
+      device_path,
+      attempt,
+    };
+    if let Err(e) = self.app.emit(event, status) {
+      eprintln!("Failed to emit data: {}", e);
+    }
+  }
+
+  /// Переносит подписки обработчиков протоколов на новый внутренний транспорт
+  fn replace_inner(&self, inner: Arc<dyn Transport>) {
+    let mut subscriptions = self.subscriptions.lock().unwrap();
+    let old_inner = self.inner();
+    for subscription in subscriptions.values_mut() {
+      old_inner.unsubscribe(subscription.inner_id);
+      subscription.inner_id = Self::subscribe_inner(&inner, &subscription.handler);
+    }
+    *self.inner.lock().unwrap() = inner;
+  }
+
+  /// Открывает порт на найденном устройстве и восстанавливает его состояние
+  ///
+  /// Подписки переносятся до инициализации протокола, чтобы подтверждения адаптера
+  /// на команды инициализации дошли до обработчиков после начала чтения.
+  fn reopen(&self, device_path: &str) -> Result<(), String> {
+    let inner = create_transport(&self.app, device_path);
+    inner.open(&self.config)?;
+    thread::sleep(REOPEN_SETTLE_DELAY);
+    let connections = self.app.state::<ConnectionRegistry>();
+    connections.reset_protocol_state(&self.path);
+    // Подтверждения команд прежнего подключения не придут
+    if let Some(acks) = connections.slcan_acks(&self.path) {
+      acks.reset();
+    }
+    self.replace_inner(inner.clone());
+
+    if let Err(e) = init_protocol(&self.app, inner.as_ref(), &self.config) {
+      let _ = inner.force_close();
+      return Err(e);
+    }
+    // Поддержка фильтра адаптера известна с первого подключения, фильтр задаётся заново
+    if let Err(e) = apply_adapter_filter(&self.app, inner.as_ref(), &self.config) {
+      log(
+        LogLevel::Warn,
+        "ReconnectingTransport",
+        format!("Фильтр адаптера на порту {} не задан: {}", self.path, e),
+      );
+    }
+    let _ = inner.write_dtr(false);
+    let _ = inner.write_rts(false);
+
+    if self.reading.load(Ordering::SeqCst) {
+      if let Err(e) = inner.start_reading() {
+        let _ = inner.force_close();
+        return Err(e);
+      }
+    }
+    Ok(())
+  }
+
+  /// Удаляет порт из реестра подключений после исчерпания попыток переподключения
+  fn release(&self) {
+    self.closed.stop();
+    let _ = self.inner().force_close();
+
+    let connections = self.app.state::<ConnectionRegistry>();
+    let registered = connections
+      .find_transport(&self.path)
+      .is_some_and(|transport| std::ptr::addr_eq(Arc::as_ptr(&transport), self as *const Self));
+    if registered {
+      connections.remove(&self.path);
+    }
+
+    log(
+      LogLevel::Warn,
+      "ReconnectingTransport",
+      format!("Порт {} закрыт и удалён из реестра подключений", self.path),
+    );
+    if let Err(e) = self.app.emit(
+      "app-status",
+      format!("Port {} closed after {} failed reconnect attempts", self.path, self.policy.max_attempts),
+    ) {
+      eprintln!("Failed to emit data: {}", e);
+    }
+  }
+
+  /// Переподключается к устройству согласно политике
+  ///
+  /// # Returns
+  /// * `true` - соединение восстановлено
+  /// * `false` - попытки исчерпаны или транспорт закрыт
+  fn reconnect(&self) -> bool {
+    log(LogLevel::Warn, "ReconnectingTransport", format!("Устройство порта {} пропало", self.path));
+    if let Err(e) = self
+      .app
+      .emit("app-status", format!("Port {} lost, reconnecting", self.path))
+    {
+      eprintln!("Failed to emit data: {}", e);
+    }
+
+    // Освобождаем старый порт, чтобы его можно было открыть заново
+    let old_inner = self.inner();
+    let _ = old_inner.stop_reading();
+    let _ = old_inner.force_close();
+
+    let mut backoff = self.policy.backoff_ms;
+    for attempt in 1..=self.policy.max_attempts {
+      if self.closed.wait(Duration::from_millis(backoff)) {
+        return false;
+      }
+      backoff = backoff.saturating_mul(2);
+      if let Some(max_backoff) = self.policy.max_backoff_ms {
+        backoff = backoff.min(max_backoff);
+      }
+
+      self.emit_status("port-reconnecting", None, attempt);
+      let Some(device_path) = self.find_device_path() else {
+        log(
+          LogLevel::Info,
+          "ReconnectingTransport",
+          format!("Попытка {}: устройство для {} не найдено", attempt, self.path),
+        );
+        continue;
+      };
+
+      match self.reopen(&device_path) {
+        Ok(()) => {
+          log(
+            LogLevel::Info,
+            "ReconnectingTransport",
+            format!("Порт {} переподключён к {} с попытки {}", self.path, device_path, attempt),
+          );
+          self.emit_status("port-reconnected", Some(device_path.clone()), attempt);
+          if let Err(e) = self
+            .app
+            .emit("app-status", format!("Port {} reconnected ({})", self.path, device_path))
+          {
+            eprintln!("Failed to emit data: {}", e);
+          }
+          return true;
+        },
+        Err(e) => {
+          log(
+            LogLevel::Warn,
+            "ReconnectingTransport",
+            format!("Попытка {}: не удалось открыть {}: {}", attempt, device_path, e),
+          );
+        },
+      }
+    }
+
+    log(
+      LogLevel::Err,
+      "ReconnectingTransport",
+      format!("Не удалось переподключить порт {} за {} попыток", self.path, self.policy.max_attempts),
+    );
+    self.emit_status("port-reconnect-failed", None, self.policy.max_attempts);
+    if let Err(e) = self
+      .app
+      .emit("app-status", format!("Failed to reconnect port {}", self.path))
+    {
+      eprintln!("Failed to emit data: {}", e);
+    }
+    false
+  }
+
+  /// Останавливает поток наблюдения, прерывая ожидание между попытками
+  fn stop_supervisor(&self) {
+    self.closed.stop();
+    if let Some(supervisor) = self.supervisor.lock().unwrap().take() {
+      // Поток наблюдения не ждёт сам себя, если порт закрывается из него
+      if supervisor.thread().id() != thread::current().id() {
+        let _ = supervisor.join();
+      }
+    }
+  }
+}
+
+impl Transport for ReconnectingTransport {
+  fn path(&self) -> &str {
+    &self.path
+  }
+
+  fn open(&self, config: &SerialConfig) -> Result<(), String> {
+    self.inner().open(config)?;
+    self.start_supervisor();
+    Ok(())
+  }
+
+  fn close(&self) -> Result<(), String> {
+    self.stop_supervisor();
+    self.inner().close()
+  }
+
+  fn force_close(&self) -> Result<(), String> {
+    self.stop_supervisor();
+    self.inner().force_close()
+  }
+
+  fn write(&self, data: &[u8]) -> Result<usize, String> {
+    self.inner().write(data)
+  }
+
+  fn start_reading(&self) -> Result<(), String> {
+    self.reading.store(true, Ordering::SeqCst);
+    self.inner().start_reading()
+  }
+
+  fn stop_reading(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    self.inner().stop_reading()
+  }
+
+  fn subscribe(&self, handler: ReadHandler) -> u32 {
+    let handler = Arc::new(handler);
+    let inner_id = Self::subscribe_inner(&self.inner(), &handler);
+    let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    self
+      .subscriptions
+      .lock()
+      .unwrap()
+      .insert(id, Subscription { handler, inner_id });
+    id
+  }
+
+  fn unsubscribe(&self, id: u32) {
+    if let Some(subscription) = self.subscriptions.lock().unwrap().remove(&id) {
+      self.inner().unsubscribe(subscription.inner_id);
+    }
+  }
+
+  fn write_dtr(&self, level: bool) -> Result<(), String> {
+    self.inner().write_dtr(level)
+  }
+
+  fn write_rts(&self, level: bool) -> Result<(), String> {
+    self.inner().write_rts(level)
+  }
+
+  fn auto_reconnect(&self) -> bool {
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Instant;
+
+  #[test]
+  fn stop_interrupts_wait() {
+    let signal = Arc::new(StopSignal::default());
+    assert!(!signal.wait(Duration::from_millis(10)));
+
+    let signal_clone = signal.clone();
+    let started = Instant::now();
+    let waiter = thread::spawn(move || signal_clone.wait(Duration::from_secs(30)));
+    thread::sleep(Duration::from_millis(50));
+    signal.stop();
+
+    assert!(waiter.join().unwrap());
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(signal.is_stopped());
+    assert!(signal.wait(Duration::from_secs(30)));
+  }
+}
@@ -0,0 +1,704 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime, Wry};
+
+use crate::models::SerialConfig;
+use crate::transports::tcp::{connect_tcp, spawn_tcp_reader};
+use crate::transports::{ReadHandler, ReadHandlers, Transport};
+use crate::{log, LogLevel};
+
+/* Команды Telnet (RFC 854) */
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+/* Опции Telnet */
+const OPTION_BINARY: u8 = 0;
+const OPTION_SGA: u8 = 3;
+const OPTION_COM_PORT: u8 = 44;
+
+/* Подкоманды COM-PORT-OPTION (RFC 2217), клиент -> сервер */
+const SET_BAUDRATE: u8 = 1;
+const SET_DATASIZE: u8 = 2;
+const SET_PARITY: u8 = 3;
+const SET_STOPSIZE: u8 = 4;
+const SET_CONTROL: u8 = 5;
+
+/* Значения для SET-CONTROL */
+const CONTROL_FLOW_NONE: u8 = 1;
+const CONTROL_FLOW_XON_XOFF: u8 = 2;
+const CONTROL_FLOW_HARDWARE: u8 = 3;
+const CONTROL_DTR_ON: u8 = 8;
+const CONTROL_DTR_OFF: u8 = 9;
+const CONTROL_RTS_ON: u8 = 11;
+const CONTROL_RTS_OFF: u8 = 12;
+
+/// Смещение кодов ответов сервера относительно кодов команд клиента
+const SERVER_RESPONSE_OFFSET: u8 = 100;
+
+/// Период проверки флага остановки в потоке чтения
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Время ожидания ответа сервера на запрос COM-PORT-OPTION при открытии порта
+const NEGOTIATION_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Состояние разбора потока Telnet
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TelnetState {
+  Data,
+  Iac,
+  Negotiation(u8),
+  Subnegotiation,
+  SubnegotiationIac,
+}
+
+/// Разборщик потока Telnet: отделяет полезные данные от команд и формирует ответы на согласование опций
+pub struct TelnetParser {
+  state: TelnetState,
+  subnegotiation: Vec<u8>,
+  com_port: Option<bool>,
+}
+
+impl Default for TelnetParser {
+  fn default() -> Self {
+    Self {
+      state: TelnetState::Data,
+      subnegotiation: Vec::new(),
+      com_port: None,
+    }
+  }
+}
+
+impl TelnetParser {
+  /// Разбирает очередной блок принятых байт
+  ///
+  /// # Arguments
+  /// * `input` - принятые из сокета байты
+  ///
+  /// # Returns
+  /// * `(Vec<u8>, Vec<u8>)` - полезные данные порта и ответы, которые нужно отправить серверу
+  pub fn feed(&mut self, input: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut data = Vec::with_capacity(input.len());
+    let mut replies = Vec::new();
+
+    for &byte in input {
+      self.state = match self.state {
+        TelnetState::Data => {
+          if byte == IAC {
+            TelnetState::Iac
+          } else {
+            data.push(byte);
+            TelnetState::Data
+          }
+        },
+        TelnetState::Iac => match byte {
+          IAC => {
+            data.push(IAC);
+            TelnetState::Data
+          },
+          DO | DONT | WILL | WONT => TelnetState::Negotiation(byte),
+          SB => {
+            self.subnegotiation.clear();
+            TelnetState::Subnegotiation
+          },
+          _ => TelnetState::Data,
+        },
+        TelnetState::Negotiation(command) => {
+          if byte == OPTION_COM_PORT && matches!(command, DO | DONT) {
+            self.com_port = Some(command == DO);
+          }
+          replies.extend(negotiation_reply(command, byte));
+          TelnetState::Data
+        },
+        TelnetState::Subnegotiation => {
+          if byte == IAC {
+            TelnetState::SubnegotiationIac
+          } else {
+            self.subnegotiation.push(byte);
+            TelnetState::Subnegotiation
+          }
+        },
+        TelnetState::SubnegotiationIac => match byte {
+          SE => {
+            handle_subnegotiation(&self.subnegotiation);
+            TelnetState::Data
+          },
+          IAC => {
+            self.subnegotiation.push(IAC);
+            TelnetState::Subnegotiation
+          },
+          _ => TelnetState::Subnegotiation,
+        },
+      };
+    }
+
+    (data, replies)
+  }
+
+  /// Ответ сервера на запрос COM-PORT-OPTION
+  ///
+  /// # Returns
+  /// * `Some(true)` / `Some(false)` - сервер принял (DO) / отклонил (DONT) опцию
+  /// * `None` - сервер ещё не ответил
+  pub fn com_port_accepted(&self) -> Option<bool> {
+    self.com_port
+  }
+}
+
+/// Формирует ответ на запрос согласования опции от сервера
+///
+/// Клиент сам запрашивает нужные опции при подключении, поэтому подтверждения сервера
+/// не требуют ответа, а неизвестные опции отклоняются.
+fn negotiation_reply(command: u8, option: u8) -> Vec<u8> {
+  let supported = matches!(option, OPTION_BINARY | OPTION_SGA | OPTION_COM_PORT);
+
+  match command {
+    DO if option == OPTION_COM_PORT => {
+      log(LogLevel::Info, "negotiation_reply", "Сервер принял опцию COM-PORT-OPTION".to_string());
+      Vec::new()
+    },
+    DONT if option == OPTION_COM_PORT => {
+      log(
+        LogLevel::Warn,
+        "negotiation_reply",
+        "Сервер отклонил опцию COM-PORT-OPTION, настройки порта не будут переданы".to_string(),
+      );
+      Vec::new()
+    },
+    DO if !supported => vec![IAC, WONT, option],
+    WILL if !supported => vec![IAC, DONT, option],
+    _ => Vec::new(),
+  }
+}
+
+/// Обрабатывает подсогласование, принятое от сервера
+fn handle_subnegotiation(payload: &[u8]) {
+  if payload.len() < 2 || payload[0] != OPTION_COM_PORT {
+    return;
+  }
+
+  let code = payload[1].wrapping_sub(SERVER_RESPONSE_OFFSET);
+  let value = &payload[2..];
+  log(
+    LogLevel::Info,
+    "handle_subnegotiation",
+    format!("Ответ сервера RFC 2217: команда {}, значение {:02X?}", code, value),
+  );
+}
+
+/// Формирует подсогласование COM-PORT-OPTION с экранированием IAC
+fn com_port_command(command: u8, value: &[u8]) -> Vec<u8> {
+  let mut packet = vec![IAC, SB, OPTION_COM_PORT, command];
+  packet.extend(escape_iac(value));
+  packet.extend([IAC, SE]);
+  packet
+}
+
+/// Удваивает байты IAC в пользовательских данных
+fn escape_iac(data: &[u8]) -> Vec<u8> {
+  let mut escaped = Vec::with_capacity(data.len());
+  for &byte in data {
+    escaped.push(byte);
+    if byte == IAC {
+      escaped.push(IAC);
+    }
+  }
+  escaped
+}
+
+/// Согласование COM-PORT-OPTION и подкоманды, ожидающие его завершения
+#[derive(Default)]
+struct ComPortNegotiation {
+  accepted: Option<bool>,
+  queued: Vec<u8>,
+}
+
+/// Фиксирует ответ сервера на COM-PORT-OPTION и отправляет отложенные подкоманды
+///
+/// # Arguments
+/// * `negotiation` - состояние согласования порта
+/// * `accepted` - ответ сервера из разборщика Telnet
+/// * `stream` - соединение с сервером
+fn complete_negotiation(negotiation: &Mutex<ComPortNegotiation>, accepted: Option<bool>, stream: &Mutex<Option<TcpStream>>) -> Result<(), String> {
+  let mut negotiation = negotiation.lock().unwrap();
+  if negotiation.accepted.is_some() || accepted.is_none() {
+    return Ok(());
+  }
+  negotiation.accepted = accepted;
+
+  let queued = std::mem::take(&mut negotiation.queued);
+  if accepted != Some(true) || queued.is_empty() {
+    return Ok(());
+  }
+  let mut stream = stream.lock().unwrap();
+  let stream = stream
+    .as_mut()
+    .ok_or_else(|| "Port is not open".to_string())?;
+  stream.write_all(&queued).map_err(|e| e.to_string())
+}
+
+/// Транспорт поверх TCP с управлением удалённым портом по RFC 2217
+///
+/// Подкоманды COM-PORT-OPTION отправляются только после подтверждения опции сервером (DO),
+/// до этого они накапливаются и передаются разом при получении подтверждения.
+pub struct Rfc2217Transport<R: Runtime = Wry> {
+  app: AppHandle<R>,
+  path: String,
+  address: String,
+  stream: Arc<Mutex<Option<TcpStream>>>,
+  parser: Arc<Mutex<TelnetParser>>,
+  negotiation: Arc<Mutex<ComPortNegotiation>>,
+  /// Данные порта, принятые при согласовании до запуска чтения
+  early_data: Mutex<Vec<u8>>,
+  handlers: Arc<ReadHandlers>,
+  reading: Arc<AtomicBool>,
+  reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl<R: Runtime> Rfc2217Transport<R> {
+  pub fn new(app: AppHandle<R>, path: String, address: String) -> Self {
+    Self {
+      app,
+      path,
+      address,
+      stream: Arc::new(Mutex::new(None)),
+      parser: Arc::new(Mutex::new(TelnetParser::default())),
+      negotiation: Arc::new(Mutex::new(ComPortNegotiation::default())),
+      early_data: Mutex::new(Vec::new()),
+      handlers: Arc::new(ReadHandlers::default()),
+      reading: Arc::new(AtomicBool::new(false)),
+      reader: Mutex::new(None),
+    }
+  }
+
+  /// Отправляет в сокет байты без экранирования
+  fn send_raw(&self, packet: &[u8]) -> Result<(), String> {
+    let mut stream = self.stream.lock().unwrap();
+    let stream = stream
+      .as_mut()
+      .ok_or_else(|| format!("Port {} is not open", self.path))?;
+    stream.write_all(packet).map_err(|e| e.to_string())
+  }
+
+  /// Отправляет подкоманду COM-PORT-OPTION или откладывает её до подтверждения опции сервером
+  fn send_com_port(&self, packet: &[u8]) -> Result<(), String> {
+    let mut negotiation = self.negotiation.lock().unwrap();
+    match negotiation.accepted {
+      Some(true) => self.send_raw(packet),
+      Some(false) => Err(format!("RFC 2217 server {} refused COM-PORT-OPTION", self.path)),
+      None => {
+        negotiation.queued.extend_from_slice(packet);
+        Ok(())
+      },
+    }
+  }
+
+  /// Отправляет команду управления линиями DTR/RTS
+  ///
+  /// В отличие от настроек порта такие команды не откладываются: переключения линий,
+  /// накопленные до ответа сервера, пришли бы одной пачкой и потеряли бы свою длительность.
+  fn send_control(&self, value: u8) -> Result<(), String> {
+    if self.negotiation.lock().unwrap().accepted.is_none() {
+      return Err(format!("RFC 2217 server {} has not answered COM-PORT-OPTION yet", self.path));
+    }
+    self.send_com_port(&com_port_command(SET_CONTROL, &[value]))
+  }
+
+  /// Ждёт ответа сервера на запрос COM-PORT-OPTION, принятые при этом данные порта сохраняются
+  fn wait_negotiation(&self) -> Result<(), String> {
+    let mut stream = {
+      let stream = self.stream.lock().unwrap();
+      stream
+        .as_ref()
+        .ok_or_else(|| format!("Port {} is not open", self.path))?
+        .try_clone()
+        .map_err(|e| e.to_string())?
+    };
+    stream
+      .set_read_timeout(Some(READ_POLL_INTERVAL))
+      .map_err(|e| e.to_string())?;
+
+    let deadline = Instant::now() + NEGOTIATION_TIMEOUT;
+    let mut buffer = [0u8; 1024];
+    let mut accepted = None;
+    while accepted.is_none() && Instant::now() < deadline {
+      let size = match stream.read(&mut buffer) {
+        Ok(0) => return Err(format!("Connection {} closed by remote host", self.path)),
+        Ok(size) => size,
+        Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+        Err(e) => return Err(e.to_string()),
+      };
+      let mut parser = self.parser.lock().unwrap();
+      let (data, replies) = parser.feed(&buffer[..size]);
+      self.early_data.lock().unwrap().extend(data);
+      if !replies.is_empty() {
+        self.send_raw(&replies)?;
+      }
+      accepted = parser.com_port_accepted();
+    }
+
+    if accepted.is_none() {
+      log(
+        LogLevel::Warn,
+        "Rfc2217Transport",
+        format!(
+          "Сервер {} не ответил на COM-PORT-OPTION за {} мс, настройки порта будут переданы после согласования",
+          self.path,
+          NEGOTIATION_TIMEOUT.as_millis()
+        ),
+      );
+    }
+    complete_negotiation(&self.negotiation, accepted, &self.stream)
+  }
+
+  /// Передаёт на удалённый порт настройки из конфигурации подключения
+  fn apply_config(&self, config: &SerialConfig) -> Result<(), String> {
+    let parity = match config.parity {
+      1 => 2,
+      2 => 3,
+      _ => 1,
+    };
+    let stop_size = if config.stop_bits == 2 { 2 } else { 1 };
+    let flow_control = match config.flow_control {
+      1 => CONTROL_FLOW_XON_XOFF,
+      2 => CONTROL_FLOW_HARDWARE,
+      _ => CONTROL_FLOW_NONE,
+    };
+
+    log(
+      LogLevel::Info,
+      "Rfc2217Transport",
+      format!(
+        "Передача настроек порта {}: скорость {}, биты данных {}, чётность {}, стоп-биты {}, управление потоком {}",
+        self.path, config.baud_rate, config.data_bits, config.parity, config.stop_bits, config.flow_control
+      ),
+    );
+
+    // Сервер, отклонивший опцию, настройки порта не принимает
+    if self.negotiation.lock().unwrap().accepted == Some(false) {
+      return Ok(());
+    }
+
+    let mut packets = com_port_command(SET_BAUDRATE, &config.baud_rate.to_be_bytes());
+    packets.extend(com_port_command(SET_DATASIZE, &[config.data_bits as u8]));
+    packets.extend(com_port_command(SET_PARITY, &[parity]));
+    packets.extend(com_port_command(SET_STOPSIZE, &[stop_size]));
+    packets.extend(com_port_command(SET_CONTROL, &[flow_control]));
+    self.send_com_port(&packets)
+  }
+}
+
+impl<R: Runtime> Transport for Rfc2217Transport<R> {
+  fn path(&self) -> &str {
+    &self.path
+  }
+
+  fn open(&self, config: &SerialConfig) -> Result<(), String> {
+    let stream = connect_tcp(&self.address, config.connect_timeout_ms)?;
+    *self.stream.lock().unwrap() = Some(stream);
+    *self.parser.lock().unwrap() = TelnetParser::default();
+    *self.negotiation.lock().unwrap() = ComPortNegotiation::default();
+    self.early_data.lock().unwrap().clear();
+
+    // Запрашиваем двоичный режим, подавление GA и управление COM-портом
+    let mut negotiation = Vec::new();
+    negotiation.extend([IAC, WILL, OPTION_BINARY, IAC, DO, OPTION_BINARY]);
+    negotiation.extend([IAC, WILL, OPTION_SGA, IAC, DO, OPTION_SGA]);
+    negotiation.extend([IAC, WILL, OPTION_COM_PORT]);
+    self.send_raw(&negotiation)?;
+
+    self.wait_negotiation()?;
+    self.apply_config(config)
+  }
+
+  fn close(&self) -> Result<(), String> {
+    self.stop_reading()?;
+    match self.stream.lock().unwrap().take() {
+      Some(stream) => stream.shutdown(Shutdown::Both).map_err(|e| e.to_string()),
+      None => Err(format!("Port {} is not open", self.path)),
+    }
+  }
+
+  fn force_close(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    if let Some(stream) = self.stream.lock().unwrap().take() {
+      let _ = stream.shutdown(Shutdown::Both);
+    }
+    Ok(())
+  }
+
+  fn write(&self, data: &[u8]) -> Result<usize, String> {
+    self.send_raw(&escape_iac(data))?;
+    Ok(data.len())
+  }
+
+  fn start_reading(&self) -> Result<(), String> {
+    let stream = {
+      let stream = self.stream.lock().unwrap();
+      stream
+        .as_ref()
+        .ok_or_else(|| format!("Port {} is not open", self.path))?
+        .try_clone()
+        .map_err(|e| e.to_string())?
+    };
+    stream
+      .set_read_timeout(Some(READ_POLL_INTERVAL))
+      .map_err(|e| e.to_string())?;
+
+    if self.reading.swap(true, Ordering::SeqCst) {
+      log(LogLevel::Warn, "Rfc2217Transport", format!("Чтение {} уже запущено", self.path));
+      return Ok(());
+    }
+
+    let early_data = std::mem::take(&mut *self.early_data.lock().unwrap());
+    if !early_data.is_empty() {
+      self.handlers.dispatch(&early_data);
+    }
+
+    let handlers = self.handlers.clone();
+    let writer = self.stream.clone();
+    let parser = self.parser.clone();
+    let negotiation = self.negotiation.clone();
+    let path = self.path.clone();
+    let reader = spawn_tcp_reader(self.app.clone(), self.path.clone(), stream, self.reading.clone(), move |input| {
+      let mut parser = parser.lock().unwrap();
+      let (data, replies) = parser.feed(input);
+      if !replies.is_empty() {
+        if let Some(stream) = writer.lock().unwrap().as_mut() {
+          let _ = stream.write_all(&replies);
+        }
+      }
+      if let Err(e) = complete_negotiation(&negotiation, parser.com_port_accepted(), &writer) {
+        log(
+          LogLevel::Err,
+          "Rfc2217Transport",
+          format!("Не удалось передать настройки порта {}: {}", path, e),
+        );
+      }
+      if !data.is_empty() {
+        handlers.dispatch(&data);
+      }
+    });
+    *self.reader.lock().unwrap() = Some(reader);
+    Ok(())
+  }
+
+  fn stop_reading(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    if let Some(reader) = self.reader.lock().unwrap().take() {
+      reader
+        .join()
+        .map_err(|_| format!("Reader thread for {} panicked", self.path))?;
+    }
+    Ok(())
+  }
+
+  fn subscribe(&self, handler: ReadHandler) -> u32 {
+    self.handlers.subscribe(handler)
+  }
+
+  fn unsubscribe(&self, id: u32) {
+    self.handlers.unsubscribe(id);
+  }
+
+  fn write_dtr(&self, level: bool) -> Result<(), String> {
+    self.send_control(if level { CONTROL_DTR_ON } else { CONTROL_DTR_OFF })
+  }
+
+  fn write_rts(&self, level: bool) -> Result<(), String> {
+    self.send_control(if level { CONTROL_RTS_ON } else { CONTROL_RTS_OFF })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::net::TcpListener;
+  use std::sync::mpsc;
+  use std::thread;
+
+  const TEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+  /// Читает из соединения, пока в принятых данных не появится последовательность
+  fn read_until(stream: &mut TcpStream, pattern: &[u8]) -> Vec<u8> {
+    stream.set_read_timeout(Some(TEST_TIMEOUT)).unwrap();
+    let mut received = Vec::new();
+    let mut buffer = [0u8; 256];
+    while !received
+      .windows(pattern.len())
+      .any(|window| window == pattern)
+    {
+      let size = stream.read(&mut buffer).unwrap();
+      assert!(size > 0, "connection closed before {:02X?}", pattern);
+      received.extend(&buffer[..size]);
+    }
+    received
+  }
+
+  /// Запускает тестовый сервер RFC 2217 и возвращает адрес и канал с принятыми сервером байтами
+  fn spawn_server<F>(script: F) -> (String, mpsc::Receiver<Vec<u8>>)
+  where
+    F: FnOnce(&mut TcpStream) -> Vec<u8> + Send + 'static,
+  {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+      let (mut stream, _) = listener.accept().unwrap();
+      let _ = sender.send(script(&mut stream));
+      // Соединение держится открытым до завершения теста
+      let _ = stream.read(&mut [0u8; 1]);
+    });
+    (address, receiver)
+  }
+
+  #[test]
+  fn telnet_data_passes_through_and_iac_is_unescaped() {
+    let mut parser = TelnetParser::default();
+    let (data, replies) = parser.feed(&[b'a', IAC, IAC, b'b']);
+    assert_eq!(data, vec![b'a', IAC, b'b']);
+    assert!(replies.is_empty());
+  }
+
+  #[test]
+  fn telnet_unsupported_options_are_refused() {
+    let mut parser = TelnetParser::default();
+    let (data, replies) = parser.feed(&[IAC, DO, 24, IAC, WILL, 31, IAC, DO, OPTION_BINARY]);
+    assert!(data.is_empty());
+    assert_eq!(replies, vec![IAC, WONT, 24, IAC, DONT, 31]);
+  }
+
+  #[test]
+  fn telnet_subnegotiation_split_across_reads_is_not_data() {
+    let mut parser = TelnetParser::default();
+    let (first, _) = parser.feed(&[b'x', IAC, SB, OPTION_COM_PORT, SET_BAUDRATE + SERVER_RESPONSE_OFFSET, 0, IAC]);
+    let (second, _) = parser.feed(&[IAC, 0, 0x25, IAC, SE, b'y']);
+    assert_eq!(first, vec![b'x']);
+    assert_eq!(second, vec![b'y']);
+  }
+
+  #[test]
+  fn telnet_com_port_answer_is_tracked() {
+    let mut parser = TelnetParser::default();
+    assert_eq!(parser.com_port_accepted(), None);
+    parser.feed(&[IAC, DO, OPTION_SGA]);
+    assert_eq!(parser.com_port_accepted(), None);
+    parser.feed(&[IAC, DO, OPTION_COM_PORT]);
+    assert_eq!(parser.com_port_accepted(), Some(true));
+
+    let mut parser = TelnetParser::default();
+    parser.feed(&[IAC, DONT, OPTION_COM_PORT]);
+    assert_eq!(parser.com_port_accepted(), Some(false));
+  }
+
+  #[test]
+  fn com_port_command_is_encoded_with_escaped_iac() {
+    assert_eq!(
+      com_port_command(SET_BAUDRATE, &115200u32.to_be_bytes()),
+      vec![IAC, SB, OPTION_COM_PORT, SET_BAUDRATE, 0x00, 0x01, 0xC2, 0x00, IAC, SE]
+    );
+    assert_eq!(
+      com_port_command(SET_BAUDRATE, &0x0000_FF00u32.to_be_bytes()),
+      vec![IAC, SB, OPTION_COM_PORT, SET_BAUDRATE, 0x00, 0x00, IAC, IAC, 0x00, IAC, SE]
+    );
+    assert_eq!(
+      com_port_command(SET_CONTROL, &[CONTROL_DTR_ON]),
+      vec![IAC, SB, OPTION_COM_PORT, SET_CONTROL, CONTROL_DTR_ON, IAC, SE]
+    );
+  }
+
+  #[test]
+  fn settings_are_sent_after_server_accepts_option() {
+    let (address, received) = spawn_server(|stream| {
+      let mut received = read_until(stream, &[IAC, WILL, OPTION_COM_PORT]);
+      // До подтверждения опции подкоманды не отправляются
+      stream
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+      let mut buffer = [0u8; 256];
+      if let Ok(size) = stream.read(&mut buffer) {
+        received.extend(&buffer[..size]);
+      }
+      assert!(!received.windows(2).any(|window| window == [IAC, SB]));
+
+      stream.write_all(&[IAC, DO, OPTION_COM_PORT]).unwrap();
+      received.extend(read_until(stream, &com_port_command(SET_CONTROL, &[CONTROL_FLOW_NONE])));
+      received
+    });
+
+    let app = tauri::test::mock_app();
+    let path = format!("{}{}", crate::transports::RFC2217_PREFIX, address);
+    let transport = Rfc2217Transport::new(app.handle().clone(), path.clone(), address);
+    transport
+      .open(&SerialConfig::test(&path, "SimpleSerial"))
+      .unwrap();
+
+    let received = received.recv_timeout(TEST_TIMEOUT).unwrap();
+    let baudrate = com_port_command(SET_BAUDRATE, &115200u32.to_be_bytes());
+    assert!(received
+      .windows(baudrate.len())
+      .any(|window| window == baudrate));
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn settings_wait_for_late_acceptance_and_early_data_is_kept() {
+    let (address, received) = spawn_server(|stream| {
+      read_until(stream, &[IAC, WILL, OPTION_COM_PORT]);
+      stream.write_all(b"hi").unwrap();
+      thread::sleep(NEGOTIATION_TIMEOUT + Duration::from_millis(200));
+      stream.write_all(&[IAC, DO, OPTION_COM_PORT]).unwrap();
+      let mut received = read_until(stream, &com_port_command(SET_CONTROL, &[CONTROL_FLOW_NONE]));
+      received.extend(read_until(stream, &com_port_command(SET_CONTROL, &[CONTROL_DTR_ON])));
+      received
+    });
+
+    let app = tauri::test::mock_app();
+    let path = format!("{}{}", crate::transports::RFC2217_PREFIX, address);
+    let transport = Rfc2217Transport::new(app.handle().clone(), path.clone(), address);
+    transport
+      .open(&SerialConfig::test(&path, "SimpleSerial"))
+      .unwrap();
+    // Переключение линий до ответа сервера не откладывается, а отклоняется
+    assert!(transport.write_dtr(true).is_err());
+    assert!(transport.write_rts(true).is_err());
+
+    let (sender, data) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    transport.subscribe(Box::new(move |input| {
+      let _ = sender.lock().unwrap().send(input);
+    }));
+    transport.start_reading().unwrap();
+    assert_eq!(data.recv_timeout(TEST_TIMEOUT).unwrap(), b"hi");
+
+    let deadline = Instant::now() + TEST_TIMEOUT;
+    while transport.write_dtr(true).is_err() {
+      assert!(Instant::now() < deadline, "COM-PORT-OPTION was not accepted");
+      thread::sleep(Duration::from_millis(20));
+    }
+
+    let received = received.recv_timeout(TEST_TIMEOUT).unwrap();
+    let settings = com_port_command(SET_BAUDRATE, &115200u32.to_be_bytes());
+    let settings_at = received
+      .windows(settings.len())
+      .position(|window| window == settings)
+      .unwrap();
+    let dtr = com_port_command(SET_CONTROL, &[CONTROL_DTR_ON]);
+    let dtr_at = received
+      .windows(dtr.len())
+      .position(|window| window == dtr)
+      .unwrap();
+    assert!(settings_at < dtr_at);
+    let rts = com_port_command(SET_CONTROL, &[CONTROL_RTS_ON]);
+    assert!(!received.windows(rts.len()).any(|window| window == rts));
+    transport.close().unwrap();
+  }
+}
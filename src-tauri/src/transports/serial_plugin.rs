@@ -0,0 +1,99 @@
+use tauri::{AppHandle, Listener, Manager, Wry};
+use tauri_plugin_serialplugin::commands::{
+  close, force_close, open, start_listening, stop_listening, write_binary, write_data_terminal_ready, write_request_to_send,
+};
+use tauri_plugin_serialplugin::desktop_api::SerialPort;
+
+use crate::convertation::*;
+use crate::models::{ReadDataResult, SerialConfig};
+use crate::transports::{ReadHandler, Transport};
+use crate::{log, LogLevel};
+
+/// Транспорт поверх tauri_plugin_serialplugin
+pub struct SerialPluginTransport {
+  app: AppHandle<Wry>,
+  path: String,
+}
+
+impl SerialPluginTransport {
+  pub fn new(app: AppHandle<Wry>, path: String) -> Self {
+    Self { app, path }
+  }
+
+  /// Формирует имя события чтения, которое публикует плагин для порта
+  fn read_event_name(&self) -> String {
+    let formatted_port_path = self
+      .path
+      .replace(".", "-")
+      .replace("/", "-")
+      .replace("\\", "-");
+    log(
+      LogLevel::Info,
+      "SerialPluginTransport",
+      format!("Форматированный путь порта: {}", formatted_port_path),
+    );
+    format!("plugin-serialplugin-read-{}", formatted_port_path)
+  }
+}
+
+impl Transport for SerialPluginTransport {
+  fn path(&self) -> &str {
+    &self.path
+  }
+
+  fn open(&self, config: &SerialConfig) -> Result<(), String> {
+    open(
+      self.app.clone(),
+      self.app.state::<SerialPort<Wry>>(),
+      self.path.clone(),
+      config.baud_rate,
+      data_bits_from_u32(config.data_bits),
+      flow_control_from_u32(config.flow_control),
+      parity_from_u32(config.parity),
+      stop_bits_from_u32(config.stop_bits),
+      config.timeout,
+    )
+    .map_err(|e| e.to_string())
+  }
+
+  fn close(&self) -> Result<(), String> {
+    close(self.app.clone(), self.app.state::<SerialPort<Wry>>(), self.path.clone()).map_err(|e| e.to_string())
+  }
+
+  fn force_close(&self) -> Result<(), String> {
+    force_close(self.app.clone(), self.app.state::<SerialPort<Wry>>(), self.path.clone()).map_err(|e| e.to_string())
+  }
+
+  fn write(&self, data: &[u8]) -> Result<usize, String> {
+    write_binary(self.app.clone(), self.app.state::<SerialPort<Wry>>(), self.path.clone(), data.to_vec()).map_err(|e| e.to_string())
+  }
+
+  fn start_reading(&self) -> Result<(), String> {
+    start_listening(self.app.clone(), self.app.state::<SerialPort<Wry>>(), self.path.clone(), None, None).map_err(|e| e.to_string())
+  }
+
+  fn stop_reading(&self) -> Result<(), String> {
+    stop_listening(self.app.clone(), self.app.state::<SerialPort<Wry>>(), self.path.clone()).map_err(|e| e.to_string())
+  }
+
+  fn subscribe(&self, handler: ReadHandler) -> u32 {
+    self.app.listen(self.read_event_name(), move |event| {
+      // Разбираем полезную нагрузку события
+      if let Ok(payload) = serde_json::from_str::<ReadDataResult>(event.payload()) {
+        handler(payload.data);
+      }
+    })
+  }
+
+  fn unsubscribe(&self, id: u32) {
+    self.app.unlisten(id);
+  }
+
+  fn write_dtr(&self, level: bool) -> Result<(), String> {
+    write_data_terminal_ready(self.app.clone(), self.app.state::<SerialPort<Wry>>(), self.path.clone(), level).map_err(|e| e.to_string())
+  }
+
+  fn write_rts(&self, level: bool) -> Result<(), String> {
+    write_request_to_send(self.app.clone(), self.app.state::<SerialPort<Wry>>(), self.path.clone(), level).map_err(|e| e.to_string())
+  }
+}
@@ -0,0 +1,159 @@
+/// Фрейм, разобранный из команды SLCAN
+#[derive(Debug)]
+pub(crate) struct SlcanFrame {
+  pub(crate) id: u32,
+  pub(crate) extended: bool,
+  pub(crate) remote: bool,
+  pub(crate) fd: bool,
+  pub(crate) brs: bool,
+  pub(crate) dlc: u8,
+  pub(crate) data: Vec<u8>,
+}
+
+/// Преобразует код DLC CAN-FD в длину данных
+pub(crate) fn canfd_dlc_to_len(dlc: u8) -> usize {
+  match dlc {
+    0..=8 => dlc as usize,
+    9 => 12,
+    10 => 16,
+    11 => 20,
+    12 => 24,
+    13 => 32,
+    14 => 48,
+    _ => 64,
+  }
+}
+
+/// Разбирает команду передачи фрейма SLCAN (t, T, r, R, b, B, d, D) без завершающего '\r'
+///
+/// Символы b/B передают CAN-FD фрейм без переключения скорости, d/D - с переключением (BRS).
+pub(crate) fn parse_slcan_frame(command: &str) -> Result<SlcanFrame, String> {
+  if !command.is_ascii() || command.is_empty() {
+    return Err(format!("Invalid SLCAN frame: {:?}", command));
+  }
+
+  let kind = command.as_bytes()[0] as char;
+  let extended = kind.is_ascii_uppercase();
+  let id_len = if extended { 8 } else { 3 };
+  if command.len() < id_len + 2 {
+    return Err(format!("SLCAN frame is too short: {}", command));
+  }
+
+  let id = u32::from_str_radix(&command[1..1 + id_len], 16).map_err(|e| format!("Failed to parse CAN ID: {}", e))?;
+  let dlc = u8::from_str_radix(&command[1 + id_len..2 + id_len], 16).map_err(|e| format!("Failed to parse DLC: {}", e))?;
+  let fd = matches!(kind, 'b' | 'B' | 'd' | 'D');
+  let remote = matches!(kind, 'r' | 'R');
+  let length = if fd { canfd_dlc_to_len(dlc) } else { std::cmp::min(dlc as usize, 8) };
+
+  let mut data = Vec::with_capacity(length);
+  if !remote {
+    let hex_data = &command[2 + id_len..];
+    for i in (0..hex_data.len().saturating_sub(1)).step_by(2) {
+      if data.len() >= length {
+        break;
+      }
+      data.push(u8::from_str_radix(&hex_data[i..i + 2], 16).map_err(|e| format!("Failed to parse data byte: {}", e))?);
+    }
+    data.resize(length, 0);
+  }
+
+  Ok(SlcanFrame {
+    id,
+    extended,
+    remote,
+    fd,
+    brs: matches!(kind, 'd' | 'D'),
+    dlc,
+    data,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::poe_canable::format_can_frame;
+
+  /// Формирует строку фрейма и разбирает её обратно
+  fn round_trip(frame_type: char, id: u32, data: Option<Vec<u8>>, dlc: u32) -> (String, SlcanFrame) {
+    let text = format_can_frame(frame_type, id, data, dlc).unwrap();
+    let frame = parse_slcan_frame(text.strip_suffix('\r').unwrap()).unwrap();
+    (text, frame)
+  }
+
+  #[test]
+  fn classic_frames_round_trip() {
+    let (text, frame) = round_trip('t', 0x123, Some(vec![0x11, 0x22, 0xAB]), 3);
+    assert_eq!(text, "t12331122AB\r");
+    assert_eq!(
+      (frame.id, frame.extended, frame.remote, frame.fd, frame.brs),
+      (0x123, false, false, false, false)
+    );
+    assert_eq!(frame.data, vec![0x11, 0x22, 0xAB]);
+
+    let (text, frame) = round_trip('T', 0x1ABCDEF0, Some(vec![1, 2, 3, 4, 5, 6, 7, 8]), 8);
+    assert_eq!(text, "T1ABCDEF080102030405060708\r");
+    assert_eq!((frame.id, frame.extended, frame.dlc), (0x1ABCDEF0, true, 8));
+    assert_eq!(frame.data, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+  }
+
+  #[test]
+  fn remote_frames_round_trip_without_data() {
+    let (text, frame) = round_trip('r', 0x7FF, None, 4);
+    assert_eq!(text, "r7FF4\r");
+    assert!(frame.remote && !frame.extended);
+    assert_eq!(frame.dlc, 4);
+    assert!(frame.data.is_empty());
+
+    let (text, frame) = round_trip('R', 0x00000010, Some(vec![1, 2]), 2);
+    assert_eq!(text, "R000000102\r");
+    assert!(frame.remote && frame.extended);
+    assert!(frame.data.is_empty());
+  }
+
+  #[test]
+  fn canfd_frames_round_trip_with_padding_and_brs() {
+    let (text, frame) = round_trip('b', 0x010, Some(vec![0xAA; 10]), 10);
+    assert_eq!(text, format!("b0109{}0000\r", "AA".repeat(10)));
+    assert!(frame.fd && !frame.brs);
+    assert_eq!(frame.dlc, 9);
+    assert_eq!(frame.data.len(), 12);
+    assert_eq!(&frame.data[..10], &[0xAA; 10]);
+    assert_eq!(&frame.data[10..], &[0, 0]);
+
+    let data: Vec<u8> = (0..64).collect();
+    let (_, frame) = round_trip('D', 0x18FF0102, Some(data.clone()), 64);
+    assert!(frame.fd && frame.brs && frame.extended);
+    assert_eq!(frame.dlc, 0xF);
+    assert_eq!(frame.data, data);
+  }
+
+  #[test]
+  fn classic_dlc_is_limited_to_eight_bytes() {
+    let (text, frame) = round_trip('t', 0x001, Some(vec![0x55; 12]), 12);
+    assert_eq!(text, format!("t0018{}\r", "55".repeat(8)));
+    assert_eq!(frame.data, vec![0x55; 8]);
+  }
+
+  #[test]
+  fn canfd_dlc_codes_map_to_lengths() {
+    let lengths: Vec<usize> = (0..=15).map(canfd_dlc_to_len).collect();
+    assert_eq!(lengths, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64]);
+  }
+
+  #[test]
+  fn short_data_is_zero_padded() {
+    let frame = parse_slcan_frame("t12341122").unwrap();
+    assert_eq!(frame.data, vec![0x11, 0x22, 0, 0]);
+  }
+
+  #[test]
+  fn malformed_frames_are_rejected() {
+    assert!(parse_slcan_frame("").is_err());
+    assert!(parse_slcan_frame("t12").is_err());
+    assert!(parse_slcan_frame("T1234").is_err());
+    assert!(parse_slcan_frame("tXYZ1").is_err());
+    assert!(parse_slcan_frame("t123G").is_err());
+    assert!(parse_slcan_frame("t1232ZZ00").is_err());
+    assert!(parse_slcan_frame("t123\u{0430}").is_err());
+  }
+}
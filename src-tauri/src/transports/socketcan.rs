@@ -0,0 +1,402 @@
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::models::SerialConfig;
+use crate::poe_canable::format_can_frame;
+use crate::transports::slcan::{parse_slcan_frame, SlcanFrame};
+use crate::transports::{ReadHandler, ReadHandlers, Transport};
+use crate::{log, LogLevel};
+
+/// Период проверки флага остановки в потоке чтения, мкс
+const READ_POLL_INTERVAL_US: libc::suseconds_t = 100_000;
+
+/// Записывает фрейм в сокет CAN
+fn write_native_frame(socket: RawFd, frame: &SlcanFrame) -> Result<(), String> {
+  let mut can_id = frame.id;
+  if frame.extended {
+    can_id = (can_id & libc::CAN_EFF_MASK) | libc::CAN_EFF_FLAG;
+  } else {
+    can_id &= libc::CAN_SFF_MASK;
+  }
+  if frame.remote {
+    can_id |= libc::CAN_RTR_FLAG;
+  }
+
+  let result = if frame.fd {
+    let mut native: libc::canfd_frame = unsafe { mem::zeroed() };
+    native.can_id = can_id;
+    native.len = frame.data.len() as u8;
+    native.flags = (libc::CANFD_FDF | if frame.brs { libc::CANFD_BRS } else { 0 }) as u8;
+    native.data[..frame.data.len()].copy_from_slice(&frame.data);
+    unsafe { libc::write(socket, &native as *const libc::canfd_frame as *const libc::c_void, libc::CANFD_MTU) }
+  } else {
+    let mut native: libc::can_frame = unsafe { mem::zeroed() };
+    native.can_id = can_id;
+    native.can_dlc = if frame.remote { std::cmp::min(frame.dlc, 8) } else { frame.data.len() as u8 };
+    native.data[..frame.data.len()].copy_from_slice(&frame.data);
+    unsafe { libc::write(socket, &native as *const libc::can_frame as *const libc::c_void, libc::CAN_MTU) }
+  };
+
+  if result < 0 {
+    return Err(io::Error::last_os_error().to_string());
+  }
+  Ok(())
+}
+
+/// Преобразует принятый из сокета фрейм в строку SLCAN
+///
+/// # Arguments
+/// * `native` - буфер фрейма (классический фрейм занимает его начало)
+/// * `size` - количество прочитанных байт
+///
+/// # Returns
+/// * `Some(String)` - строка фрейма SLCAN с завершающим '\r'
+/// * `None` - фрейм ошибки или неизвестного размера
+fn native_frame_to_slcan(native: &libc::canfd_frame, size: usize) -> Option<String> {
+  if size != libc::CAN_MTU && size != libc::CANFD_MTU {
+    log(LogLevel::Warn, "native_frame_to_slcan", format!("Неизвестный размер фрейма: {}", size));
+    return None;
+  }
+  if native.can_id & libc::CAN_ERR_FLAG != 0 {
+    log(
+      LogLevel::Warn,
+      "native_frame_to_slcan",
+      format!("Принят фрейм ошибки шины: 0x{:08X}", native.can_id),
+    );
+    return None;
+  }
+
+  let is_fd = size == libc::CANFD_MTU;
+  let extended = native.can_id & libc::CAN_EFF_FLAG != 0;
+  let remote = native.can_id & libc::CAN_RTR_FLAG != 0;
+  let brs = native.flags as libc::c_int & libc::CANFD_BRS != 0;
+  let id = if extended {
+    native.can_id & libc::CAN_EFF_MASK
+  } else {
+    native.can_id & libc::CAN_SFF_MASK
+  };
+  let length = std::cmp::min(native.len as usize, if is_fd { 64 } else { 8 });
+
+  let frame_type = match (is_fd, remote, brs, extended) {
+    (false, true, _, false) => 'r',
+    (false, true, _, true) => 'R',
+    (false, false, _, false) => 't',
+    (false, false, _, true) => 'T',
+    (true, _, false, false) => 'b',
+    (true, _, false, true) => 'B',
+    (true, _, true, false) => 'd',
+    (true, _, true, true) => 'D',
+  };
+  let data = if remote { None } else { Some(native.data[..length].to_vec()) };
+
+  format_can_frame(frame_type, id, data, length as u32).ok()
+}
+
+/// Открывает сырой сокет CAN и привязывает его к интерфейсу
+fn open_can_socket(interface: &str) -> Result<OwnedFd, String> {
+  let name = CString::new(interface).map_err(|e| e.to_string())?;
+  let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+  if index == 0 {
+    return Err(format!("CAN interface {} not found", interface));
+  }
+
+  let raw_socket = unsafe { libc::socket(libc::AF_CAN, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::CAN_RAW) };
+  if raw_socket < 0 {
+    return Err(format!("Failed to create CAN socket: {}", io::Error::last_os_error()));
+  }
+  let socket = unsafe { OwnedFd::from_raw_fd(raw_socket) };
+
+  // Включаем приём и передачу фреймов CAN-FD
+  let enable: libc::c_int = 1;
+  let result = unsafe {
+    libc::setsockopt(
+      socket.as_raw_fd(),
+      libc::SOL_CAN_RAW,
+      libc::CAN_RAW_FD_FRAMES,
+      &enable as *const libc::c_int as *const libc::c_void,
+      mem::size_of::<libc::c_int>() as libc::socklen_t,
+    )
+  };
+  if result < 0 {
+    log(
+      LogLevel::Warn,
+      "open_can_socket",
+      format!("Интерфейс {} не поддерживает CAN-FD: {}", interface, io::Error::last_os_error()),
+    );
+  }
+
+  // Таймаут чтения, чтобы поток мог проверять флаг остановки
+  let timeout = libc::timeval {
+    tv_sec: 0,
+    tv_usec: READ_POLL_INTERVAL_US,
+  };
+  let result = unsafe {
+    libc::setsockopt(
+      socket.as_raw_fd(),
+      libc::SOL_SOCKET,
+      libc::SO_RCVTIMEO,
+      &timeout as *const libc::timeval as *const libc::c_void,
+      mem::size_of::<libc::timeval>() as libc::socklen_t,
+    )
+  };
+  if result < 0 {
+    return Err(format!("Failed to set CAN socket timeout: {}", io::Error::last_os_error()));
+  }
+
+  let mut address: libc::sockaddr_can = unsafe { mem::zeroed() };
+  address.can_family = libc::AF_CAN as libc::sa_family_t;
+  address.can_ifindex = index as libc::c_int;
+  let result = unsafe {
+    libc::bind(
+      socket.as_raw_fd(),
+      &address as *const libc::sockaddr_can as *const libc::sockaddr,
+      mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+    )
+  };
+  if result < 0 {
+    return Err(format!("Failed to bind CAN socket to {}: {}", interface, io::Error::last_os_error()));
+  }
+
+  log(LogLevel::Info, "open_can_socket", format!("Сокет CAN открыт на интерфейсе {}", interface));
+  Ok(socket)
+}
+
+/// Транспорт поверх SocketCAN (Linux), представляющий фреймы шины в виде команд SLCAN
+///
+/// Протокол POECanable работает с транспортом так же, как с адаптером CANable: команды
+/// передачи фреймов преобразуются в `can_frame`/`canfd_frame`, а принятые фреймы - в строки SLCAN.
+/// Команды настройки адаптера (C, O, S, Y, M, A) игнорируются: скорость шины задаётся через `ip link`.
+pub struct SocketCanTransport {
+  path: String,
+  interface: String,
+  socket: Mutex<Option<Arc<OwnedFd>>>,
+  handlers: Arc<ReadHandlers>,
+  reading: Arc<AtomicBool>,
+  reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SocketCanTransport {
+  pub fn new(path: String, interface: String) -> Self {
+    Self {
+      path,
+      interface,
+      socket: Mutex::new(None),
+      handlers: Arc::new(ReadHandlers::default()),
+      reading: Arc::new(AtomicBool::new(false)),
+      reader: Mutex::new(None),
+    }
+  }
+
+  /// Возвращает открытый сокет
+  fn socket(&self) -> Result<Arc<OwnedFd>, String> {
+    self
+      .socket
+      .lock()
+      .unwrap()
+      .clone()
+      .ok_or_else(|| format!("Port {} is not open", self.path))
+  }
+}
+
+impl Transport for SocketCanTransport {
+  fn path(&self) -> &str {
+    &self.path
+  }
+
+  fn open(&self, _config: &SerialConfig) -> Result<(), String> {
+    let socket = open_can_socket(&self.interface)?;
+    *self.socket.lock().unwrap() = Some(Arc::new(socket));
+    Ok(())
+  }
+
+  fn close(&self) -> Result<(), String> {
+    self.stop_reading()?;
+    match self.socket.lock().unwrap().take() {
+      Some(_) => Ok(()),
+      None => Err(format!("Port {} is not open", self.path)),
+    }
+  }
+
+  fn force_close(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    self.socket.lock().unwrap().take();
+    Ok(())
+  }
+
+  fn write(&self, data: &[u8]) -> Result<usize, String> {
+    let socket = self.socket()?;
+    let text = String::from_utf8_lossy(data);
+
+    // Подтверждения эмулируют адаптер SLCAN: '\r' - команда принята, BEL - не поддерживается
+    for command in text.split('\r').filter(|command| !command.is_empty()) {
+      match command.as_bytes()[0] {
+        b't' | b'T' | b'r' | b'R' | b'b' | b'B' | b'd' | b'D' => {
+          let frame = parse_slcan_frame(command)?;
+          write_native_frame(socket.as_raw_fd(), &frame)?;
+          self.handlers.dispatch(b"\r");
+        },
+        // Флаги состояния адаптера у интерфейса SocketCAN недоступны
+        b'F' => self.handlers.dispatch(&[0x07]),
+        _ => {
+          log(
+            LogLevel::Info,
+            "SocketCanTransport",
+            format!("Команда адаптера {} пропущена для интерфейса {}", command, self.interface),
+          );
+          self.handlers.dispatch(b"\r");
+        },
+      }
+    }
+
+    Ok(data.len())
+  }
+
+  fn start_reading(&self) -> Result<(), String> {
+    let socket = self.socket()?;
+
+    if self.reading.swap(true, Ordering::SeqCst) {
+      log(LogLevel::Warn, "SocketCanTransport", format!("Чтение {} уже запущено", self.path));
+      return Ok(());
+    }
+
+    let handlers = self.handlers.clone();
+    let reading = self.reading.clone();
+    let path = self.path.clone();
+    let reader = thread::spawn(move || {
+      let mut native: libc::canfd_frame = unsafe { mem::zeroed() };
+
+      while reading.load(Ordering::SeqCst) {
+        let size = unsafe { libc::read(socket.as_raw_fd(), &mut native as *mut libc::canfd_frame as *mut libc::c_void, libc::CANFD_MTU) };
+
+        if size < 0 {
+          let error = io::Error::last_os_error();
+          if matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted) {
+            continue;
+          }
+          log(LogLevel::Err, "SocketCanTransport", format!("Ошибка чтения из {}: {}", path, error));
+          break;
+        }
+
+        if let Some(frame) = native_frame_to_slcan(&native, size as usize) {
+          handlers.dispatch(frame.as_bytes());
+        }
+      }
+
+      reading.store(false, Ordering::SeqCst);
+      log(LogLevel::Info, "SocketCanTransport", format!("Поток чтения {} остановлен", path));
+    });
+    *self.reader.lock().unwrap() = Some(reader);
+    Ok(())
+  }
+
+  fn stop_reading(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    if let Some(reader) = self.reader.lock().unwrap().take() {
+      reader
+        .join()
+        .map_err(|_| format!("Reader thread for {} panicked", self.path))?;
+    }
+    Ok(())
+  }
+
+  fn subscribe(&self, handler: ReadHandler) -> u32 {
+    self.handlers.subscribe(handler)
+  }
+
+  fn unsubscribe(&self, id: u32) {
+    self.handlers.unsubscribe(id);
+  }
+
+  fn write_dtr(&self, _level: bool) -> Result<(), String> {
+    Err(format!("DTR control is not supported by CAN interface {}", self.interface))
+  }
+
+  fn write_rts(&self, _level: bool) -> Result<(), String> {
+    Err(format!("RTS control is not supported by CAN interface {}", self.interface))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::mpsc;
+  use std::time::Duration;
+
+  /// Интерфейс vcan для теста; создаётся командами `ip link add dev vcan0 type vcan && ip link set up vcan0`
+  fn vcan_interface() -> Option<String> {
+    let interface = std::env::var("VCAN_INTERFACE").unwrap_or_else(|_| "vcan0".to_string());
+    let name = CString::new(interface.clone()).ok()?;
+    if unsafe { libc::if_nametoindex(name.as_ptr()) } == 0 {
+      eprintln!("Interface {} not found, SocketCAN test skipped", interface);
+      return None;
+    }
+    Some(interface)
+  }
+
+  /// Открывает транспорт на интерфейсе и подписывает канал на принятые данные
+  fn open_transport(interface: &str) -> (SocketCanTransport, mpsc::Receiver<Vec<u8>>) {
+    let path = format!("{}{}", crate::transports::SOCKETCAN_PREFIX, interface);
+    let transport = SocketCanTransport::new(path.clone(), interface.to_string());
+    transport
+      .open(&SerialConfig::test(&path, "POECanable"))
+      .unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    transport.subscribe(Box::new(move |data| {
+      let _ = sender.lock().unwrap().send(data);
+    }));
+    transport.start_reading().unwrap();
+    (transport, receiver)
+  }
+
+  #[test]
+  fn native_frames_convert_to_slcan() {
+    let mut native: libc::canfd_frame = unsafe { mem::zeroed() };
+    native.can_id = 0x18FF0102 | libc::CAN_EFF_FLAG;
+    native.len = 3;
+    native.data[..3].copy_from_slice(&[0x01, 0x02, 0x03]);
+    assert_eq!(native_frame_to_slcan(&native, libc::CAN_MTU).unwrap(), "T18FF01023010203\r");
+
+    native.can_id = 0x123;
+    native.len = 12;
+    native.flags = libc::CANFD_BRS as u8;
+    native.data[..12].copy_from_slice(&[0xAB; 12]);
+    assert_eq!(native_frame_to_slcan(&native, libc::CANFD_MTU).unwrap(), format!("d1239{}\r", "AB".repeat(12)));
+
+    native.can_id = 0x7FF | libc::CAN_RTR_FLAG;
+    native.len = 2;
+    assert_eq!(native_frame_to_slcan(&native, libc::CAN_MTU).unwrap(), "r7FF2\r");
+
+    native.can_id = libc::CAN_ERR_FLAG;
+    assert!(native_frame_to_slcan(&native, libc::CAN_MTU).is_none());
+    assert!(native_frame_to_slcan(&native, 4).is_none());
+  }
+
+  #[test]
+  fn frames_pass_through_vcan() {
+    let Some(interface) = vcan_interface() else {
+      return;
+    };
+    let (sender, acks) = open_transport(&interface);
+    let (receiver, frames) = open_transport(&interface);
+
+    for frame in ["T18FF0102301020304\r", "t1232AABB\r", "D00000010910111213141516171819202122\r"] {
+      sender.write(frame.as_bytes()).unwrap();
+      assert_eq!(acks.recv_timeout(Duration::from_secs(1)).unwrap(), b"\r");
+      let received = frames.recv_timeout(Duration::from_secs(1)).unwrap();
+      assert_eq!(String::from_utf8(received).unwrap(), frame);
+    }
+
+    // Запрос состояния адаптера интерфейсом не поддерживается
+    sender.write(b"F\r").unwrap();
+    assert_eq!(acks.recv_timeout(Duration::from_secs(1)).unwrap(), vec![0x07]);
+
+    sender.close().unwrap();
+    receiver.close().unwrap();
+  }
+}
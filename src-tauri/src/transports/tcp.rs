@@ -0,0 +1,288 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime, Wry};
+
+use crate::models::SerialConfig;
+use crate::transports::{ReadHandler, ReadHandlers, Transport};
+use crate::{log, LogLevel};
+
+/// Таймаут подключения по умолчанию, мс
+const CONNECT_TIMEOUT_MS: u64 = 1000;
+
+/// Период проверки флага остановки в потоке чтения
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Транспорт поверх сырого TCP-соединения (ser2net, ESP-Link и аналогичные мосты)
+pub struct TcpTransport<R: Runtime = Wry> {
+  app: AppHandle<R>,
+  path: String,
+  address: String,
+  stream: Mutex<Option<TcpStream>>,
+  handlers: Arc<ReadHandlers>,
+  reading: Arc<AtomicBool>,
+  reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl<R: Runtime> TcpTransport<R> {
+  pub fn new(app: AppHandle<R>, path: String, address: String) -> Self {
+    Self {
+      app,
+      path,
+      address,
+      stream: Mutex::new(None),
+      handlers: Arc::new(ReadHandlers::default()),
+      reading: Arc::new(AtomicBool::new(false)),
+      reader: Mutex::new(None),
+    }
+  }
+
+  /// Возвращает копию дескриптора открытого соединения
+  fn clone_stream(&self) -> Result<TcpStream, String> {
+    let stream = self.stream.lock().unwrap();
+    stream
+      .as_ref()
+      .ok_or_else(|| format!("Port {} is not open", self.path))?
+      .try_clone()
+      .map_err(|e| e.to_string())
+  }
+}
+
+/// Устанавливает TCP-соединение с удалённым портом
+///
+/// # Arguments
+/// * `address` - адрес в формате host:port
+/// * `timeout` - таймаут подключения в миллисекундах (`None` - 1000 мс)
+///
+/// # Returns
+/// * `Ok(TcpStream)` - открытое соединение
+/// * `Err(String)` - ошибка подключения
+pub fn connect_tcp(address: &str, timeout: Option<u64>) -> Result<TcpStream, String> {
+  let socket_address = address
+    .to_socket_addrs()
+    .map_err(|e| format!("Invalid address {}: {}", address, e))?
+    .next()
+    .ok_or_else(|| format!("Invalid address {}", address))?;
+
+  let stream = TcpStream::connect_timeout(&socket_address, Duration::from_millis(timeout.unwrap_or(CONNECT_TIMEOUT_MS)))
+    .map_err(|e| format!("Failed to connect to {}: {}", address, e))?;
+  stream.set_nodelay(true).map_err(|e| e.to_string())?;
+
+  log(LogLevel::Info, "connect_tcp", format!("Установлено TCP соединение с {}", address));
+  Ok(stream)
+}
+
+/// Запускает поток чтения из TCP-соединения
+///
+/// # Arguments
+/// * `app` - дескриптор приложения Tauri
+/// * `path` - путь к порту (для логирования и статуса)
+/// * `stream` - копия дескриптора соединения
+/// * `reading` - флаг работы потока
+/// * `on_data` - обработчик каждого принятого блока данных
+///
+/// # Returns
+/// * `JoinHandle<()>` - дескриптор потока чтения
+pub fn spawn_tcp_reader<R, F>(app: AppHandle<R>, path: String, mut stream: TcpStream, reading: Arc<AtomicBool>, mut on_data: F) -> JoinHandle<()>
+where
+  R: Runtime,
+  F: FnMut(&[u8]) + Send + 'static,
+{
+  thread::spawn(move || {
+    let mut buffer = [0u8; 1024];
+
+    while reading.load(Ordering::SeqCst) {
+      match stream.read(&mut buffer) {
+        Ok(0) => {
+          log(LogLevel::Warn, "spawn_tcp_reader", format!("Удалённая сторона закрыла соединение {}", path));
+          if let Err(e) = app.emit("app-status", format!("Connection {} closed by remote host", path)) {
+            eprintln!("Failed to emit data: {}", e);
+          }
+          break;
+        },
+        Ok(size) => on_data(&buffer[..size]),
+        Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+        Err(e) => {
+          log(LogLevel::Err, "spawn_tcp_reader", format!("Ошибка чтения из {}: {}", path, e));
+          break;
+        },
+      }
+    }
+
+    reading.store(false, Ordering::SeqCst);
+    log(LogLevel::Info, "spawn_tcp_reader", format!("Поток чтения {} остановлен", path));
+  })
+}
+
+impl<R: Runtime> Transport for TcpTransport<R> {
+  fn path(&self) -> &str {
+    &self.path
+  }
+
+  fn open(&self, config: &SerialConfig) -> Result<(), String> {
+    let stream = connect_tcp(&self.address, config.connect_timeout_ms)?;
+    *self.stream.lock().unwrap() = Some(stream);
+    Ok(())
+  }
+
+  fn close(&self) -> Result<(), String> {
+    self.stop_reading()?;
+    match self.stream.lock().unwrap().take() {
+      Some(stream) => stream.shutdown(Shutdown::Both).map_err(|e| e.to_string()),
+      None => Err(format!("Port {} is not open", self.path)),
+    }
+  }
+
+  fn force_close(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    if let Some(stream) = self.stream.lock().unwrap().take() {
+      let _ = stream.shutdown(Shutdown::Both);
+    }
+    Ok(())
+  }
+
+  fn write(&self, data: &[u8]) -> Result<usize, String> {
+    let mut stream = self.stream.lock().unwrap();
+    let stream = stream
+      .as_mut()
+      .ok_or_else(|| format!("Port {} is not open", self.path))?;
+    stream.write_all(data).map_err(|e| e.to_string())?;
+    Ok(data.len())
+  }
+
+  fn start_reading(&self) -> Result<(), String> {
+    let stream = self.clone_stream()?;
+    stream
+      .set_read_timeout(Some(READ_POLL_INTERVAL))
+      .map_err(|e| e.to_string())?;
+
+    if self.reading.swap(true, Ordering::SeqCst) {
+      log(LogLevel::Warn, "TcpTransport", format!("Чтение {} уже запущено", self.path));
+      return Ok(());
+    }
+
+    let handlers = self.handlers.clone();
+    let reader = spawn_tcp_reader(self.app.clone(), self.path.clone(), stream, self.reading.clone(), move |data| {
+      handlers.dispatch(data)
+    });
+    *self.reader.lock().unwrap() = Some(reader);
+    Ok(())
+  }
+
+  fn stop_reading(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    if let Some(reader) = self.reader.lock().unwrap().take() {
+      reader
+        .join()
+        .map_err(|_| format!("Reader thread for {} panicked", self.path))?;
+    }
+    Ok(())
+  }
+
+  fn subscribe(&self, handler: ReadHandler) -> u32 {
+    self.handlers.subscribe(handler)
+  }
+
+  fn unsubscribe(&self, id: u32) {
+    self.handlers.unsubscribe(id);
+  }
+
+  fn write_dtr(&self, _level: bool) -> Result<(), String> {
+    Err(format!("DTR control is not supported by raw TCP port {}", self.path))
+  }
+
+  fn write_rts(&self, _level: bool) -> Result<(), String> {
+    Err(format!("RTS control is not supported by raw TCP port {}", self.path))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::net::TcpListener;
+  use std::sync::mpsc;
+
+  const RECEIVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+  /// Открывает транспорт к локальному слушателю и возвращает серверную сторону соединения
+  fn open_pair(app: &tauri::App<tauri::test::MockRuntime>) -> (TcpTransport<tauri::test::MockRuntime>, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let path = format!("{}{}", crate::transports::TCP_PREFIX, address);
+    let transport = TcpTransport::new(app.handle().clone(), path.clone(), address);
+    transport
+      .open(&SerialConfig::test(&path, "SimpleSerial"))
+      .unwrap();
+    let (server, _) = listener.accept().unwrap();
+    server.set_read_timeout(Some(RECEIVE_TIMEOUT)).unwrap();
+    (transport, server)
+  }
+
+  #[test]
+  fn write_reaches_remote_side() {
+    let app = tauri::test::mock_app();
+    let (transport, mut server) = open_pair(&app);
+
+    assert_eq!(transport.write(b"ping\r\n").unwrap(), 6);
+    let mut buffer = [0u8; 6];
+    server.read_exact(&mut buffer).unwrap();
+    assert_eq!(&buffer, b"ping\r\n");
+
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn received_data_is_dispatched_to_subscribers() {
+    let app = tauri::test::mock_app();
+    let (transport, mut server) = open_pair(&app);
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    transport.subscribe(Box::new(move |data| {
+      let _ = sender.lock().unwrap().send(data);
+    }));
+    transport.start_reading().unwrap();
+
+    server.write_all(b"pong").unwrap();
+    let mut received = Vec::new();
+    while received.len() < 4 {
+      received.extend(receiver.recv_timeout(RECEIVE_TIMEOUT).unwrap());
+    }
+    assert_eq!(received, b"pong");
+
+    transport.close().unwrap();
+    assert!(transport.write(b"x").is_err());
+  }
+
+  #[test]
+  fn reader_stops_when_remote_closes() {
+    let app = tauri::test::mock_app();
+    let (transport, server) = open_pair(&app);
+    transport.start_reading().unwrap();
+
+    drop(server);
+    let deadline = std::time::Instant::now() + RECEIVE_TIMEOUT;
+    while transport.reading.load(Ordering::SeqCst) && std::time::Instant::now() < deadline {
+      thread::sleep(READ_POLL_INTERVAL);
+    }
+    assert!(!transport.reading.load(Ordering::SeqCst));
+
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn open_fails_without_listener() {
+    let app = tauri::test::mock_app();
+    let address = {
+      let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+      listener.local_addr().unwrap().to_string()
+    };
+    let transport = TcpTransport::new(app.handle().clone(), format!("tcp://{}", address), address);
+    assert!(transport
+      .open(&SerialConfig::test("tcp://closed", "SimpleSerial"))
+      .is_err());
+    assert!(transport.close().is_err());
+  }
+}
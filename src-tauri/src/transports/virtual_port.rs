@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::models::SerialConfig;
+use crate::poe_canable::{format_can_frame, CanMode};
+use crate::poe_serial::{calculate_crc8, encode_poe_serial_packet};
+use crate::poe_serial_framer::{FrameEvent, PoeSerialFramer, RawPoeSerialPacket};
+use crate::transports::slcan::{parse_slcan_frame, SlcanFrame};
+use crate::transports::{ReadHandler, ReadHandlers, Transport};
+use crate::{log, LogLevel};
+
+/// Имя симулятора устройства POE в пути sim://
+pub const SIM_POE_DEVICE: &str = "poe";
+
+/// Период проверки флага остановки в потоке чтения
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Начальное значение свободной памяти симулятора, байт
+const SIM_FREE_HEAP: u32 = 180_000;
+
+/// Коды заголовков POECanable
+const CAN_HEADER_GET: u32 = 0;
+const CAN_HEADER_SET: u32 = 1;
+const CAN_HEADER_OK: u32 = 2;
+
+/// Режим работы виртуального порта
+enum VirtualMode {
+  /// Все записанные данные возвращаются в поток чтения
+  Loopback,
+  /// Записанные данные обрабатываются симулятором устройства POE
+  PoeDevice(SimulatedPoeDevice),
+}
+
+impl VirtualMode {
+  /// Обрабатывает записанные данные и возвращает ответ порта
+  fn handle(&mut self, data: &[u8]) -> Vec<u8> {
+    match self {
+      VirtualMode::Loopback => data.to_vec(),
+      VirtualMode::PoeDevice(device) => device.handle(data),
+    }
+  }
+}
+
+/// Симулятор платы POE, отвечающий на команды POESerial и POECanable
+///
+/// Значения, записанные командой SET, сохраняются и возвращаются последующими GET.
+struct SimulatedPoeDevice {
+  values: HashMap<String, String>,
+  escaped: bool,
+  framer: PoeSerialFramer,
+  slcan_line: String,
+  can_partial: HashMap<u32, Vec<u8>>,
+  can_mode: CanMode,
+  /// Начало отсчёта меток времени SLCAN, `None` - метки выключены (Z0)
+  timestamps_since: Option<Instant>,
+  replies: u32,
+}
+
+impl SimulatedPoeDevice {
+  fn new(escaped: bool) -> Self {
+    Self {
+      values: HashMap::new(),
+      escaped,
+      framer: PoeSerialFramer::new(escaped),
+      slcan_line: String::new(),
+      can_partial: HashMap::new(),
+      can_mode: CanMode::Classic,
+      timestamps_since: None,
+      replies: 0,
+    }
+  }
+
+  /// Текущий размер свободной памяти, медленно меняющийся от ответа к ответу
+  fn free_heap(&mut self) -> u32 {
+    self.replies = self.replies.wrapping_add(1);
+    SIM_FREE_HEAP - (self.replies % 64) * 16
+  }
+
+  /// Разбирает поток байт: пакеты POESerial выделяются фреймером, остальное считается командами SLCAN
+  fn handle(&mut self, data: &[u8]) -> Vec<u8> {
+    let mut reply = Vec::new();
+
+    for &byte in data {
+      match self.framer.push(byte) {
+        FrameEvent::Pending => {},
+        FrameEvent::Packet(packet) => reply.extend(self.handle_serial_packet(&packet)),
+        FrameEvent::Discarded(reason) => {
+          log(LogLevel::Warn, "SimulatedPoeDevice", format!("Пакет POESerial отброшен: {}", reason));
+        },
+        FrameEvent::Outside(b'\r') => {
+          let line = std::mem::take(&mut self.slcan_line);
+          reply.extend(self.handle_slcan_command(&line));
+        },
+        FrameEvent::Outside(byte) => self.slcan_line.push(byte as char),
+      }
+    }
+
+    reply
+  }
+
+  /// Формирует ответный пакет POESerial с корректным CRC8
+  fn serial_reply(&mut self, header: &str, argument: &str, value: &str) -> Vec<u8> {
+    let free_heap = self.free_heap();
+    encode_poe_serial_packet(header.as_bytes(), argument.as_bytes(), value.as_bytes(), Some(free_heap), self.escaped)
+  }
+
+  /// Отвечает на пакет POESerial
+  fn handle_serial_packet(&mut self, packet: &RawPoeSerialPacket) -> Vec<u8> {
+    let header = String::from_utf8_lossy(&packet.header).to_string();
+    let argument = String::from_utf8_lossy(&packet.argument).to_string();
+    let value = String::from_utf8_lossy(&packet.value).to_string();
+    let crc_hex = String::from_utf8_lossy(&packet.crc).to_string();
+
+    let expected_crc = calculate_crc8(&packet.checksum_data());
+    if u8::from_str_radix(crc_hex.trim(), 16).ok() != Some(expected_crc) {
+      log(
+        LogLevel::Warn,
+        "SimulatedPoeDevice",
+        format!("Неверный CRC {} для {}, ожидался {:02X}", crc_hex, argument, expected_crc),
+      );
+      return self.serial_reply("ER!", &argument, "{\"error\":\"CRC mismatch\"}");
+    }
+
+    log(
+      LogLevel::Info,
+      "SimulatedPoeDevice",
+      format!("Принята команда POESerial: {} {} {}", header, argument, value),
+    );
+
+    match header.as_str() {
+      "GET" => {
+        let stored = self
+          .values
+          .get(&argument)
+          .cloned()
+          .unwrap_or_else(|| "{}".to_string());
+        self.serial_reply("OK!", &argument, &stored)
+      },
+      "SET" => {
+        let reply = self.serial_reply("OK!", &argument, &value);
+        self.values.insert(argument, value);
+        reply
+      },
+      _ => self.serial_reply("ER!", &argument, "{\"error\":\"Unknown header\"}"),
+    }
+  }
+
+  /// Формирует ответ POECanable с заголовком OK!, разбитый на фреймы
+  fn can_reply(&self, request_id: u32, value: &str) -> Vec<u8> {
+    let argument = (request_id >> 16) & 0x3ff;
+    let target_id = (request_id >> 8) & 0xff;
+    let return_id = request_id & 0xff;
+    // Ответ адресуется отправителю запроса
+    let can_id = (CAN_HEADER_OK << 26) | (argument << 16) | (return_id << 8) | target_id;
+
+    let bytes = value.as_bytes();
+    let max_frame_size = self.can_mode.max_frame_size();
+    let frame_type = self.can_mode.data_frame_type(false);
+    let mut reply = String::new();
+
+    for (offset, chunk) in bytes.chunks(max_frame_size).enumerate() {
+      let is_final = offset * max_frame_size + chunk.len() >= bytes.len();
+      let frame_id = can_id | if is_final { 1 << 28 } else { 0 };
+      if let Ok(frame) = format_can_frame(frame_type, frame_id, Some(chunk.to_vec()), chunk.len() as u32) {
+        match self.timestamps_since {
+          // Метка адаптера добавляется перед завершающим '\r'
+          Some(since) => {
+            let timestamp = since.elapsed().as_millis() % 60_000;
+            reply.push_str(&format!("{}{:04X}\r", frame.trim_end_matches('\r'), timestamp));
+          },
+          None => reply.push_str(&frame),
+        }
+      }
+    }
+
+    reply.into_bytes()
+  }
+
+  /// Отвечает на команду SLCAN как адаптер: подтверждение '\r' или BEL, затем ответ платы
+  fn handle_slcan_command(&mut self, line: &str) -> Vec<u8> {
+    let line = line.trim_matches(|c: char| c.is_ascii_control());
+    let Some(kind) = line.chars().next() else {
+      return Vec::new();
+    };
+    if kind == 'Z' {
+      self.timestamps_since = (line == "Z1").then(Instant::now);
+      log(LogLevel::Info, "SimulatedPoeDevice", format!("Метки времени SLCAN: {}", line));
+      return b"\r".to_vec();
+    }
+    // Запросы состояния и сведений об адаптере: флаги, версии, серийный номер
+    match kind {
+      'F' => return b"F00\r".to_vec(),
+      'V' => return b"V0100\r".to_vec(),
+      'v' => return b"vsim-poe-canfd\r".to_vec(),
+      'N' => return b"NSIM0\r".to_vec(),
+      _ => {},
+    }
+    if !matches!(kind, 't' | 'T' | 'r' | 'R' | 'b' | 'B' | 'd' | 'D') {
+      log(LogLevel::Info, "SimulatedPoeDevice", format!("Команда адаптера {} пропущена", line));
+      return b"\r".to_vec();
+    }
+
+    let frame = match parse_slcan_frame(line) {
+      Ok(frame) => frame,
+      Err(e) => {
+        log(LogLevel::Warn, "SimulatedPoeDevice", format!("Не удалось разобрать фрейм {}: {}", line, e));
+        return vec![0x07];
+      },
+    };
+    let mut reply = b"\r".to_vec();
+    reply.extend(self.handle_can_frame(frame));
+    reply
+  }
+
+  /// Обрабатывает фрейм SLCAN как запрос POECanable
+  fn handle_can_frame(&mut self, frame: SlcanFrame) -> Vec<u8> {
+    if !frame.extended {
+      return Vec::new();
+    }
+    if frame.fd {
+      self.can_mode = CanMode::Fd;
+    }
+
+    let header = (frame.id >> 26) & 0x03;
+    let argument = (frame.id >> 16) & 0x3ff;
+    let main_id = (frame.id >> 16) & 0xfff;
+    let is_full_packet = (frame.id >> 28) & 0x01 == 1;
+    let key = format!("can:{}", argument);
+
+    if frame.remote {
+      log(LogLevel::Info, "SimulatedPoeDevice", format!("Принят remote фрейм, аргумент {}", argument));
+      return match header {
+        CAN_HEADER_GET => {
+          let stored = self
+            .values
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| "{}".to_string());
+          self.can_reply(frame.id, &stored)
+        },
+        CAN_HEADER_SET => self.can_reply(frame.id, "{}"),
+        _ => Vec::new(),
+      };
+    }
+
+    // Собираем данные из частей до получения последнего фрейма
+    let partial = self.can_partial.entry(main_id).or_default();
+    partial.extend_from_slice(&frame.data);
+    if !is_full_packet {
+      return Vec::new();
+    }
+    let mut data = self.can_partial.remove(&main_id).unwrap_or_default();
+    if let Some(last_non_zero) = data.iter().rposition(|&b| b != 0) {
+      data.truncate(last_non_zero + 1);
+    } else {
+      data.clear();
+    }
+    let value = String::from_utf8_lossy(&data).to_string();
+
+    log(
+      LogLevel::Info,
+      "SimulatedPoeDevice",
+      format!("Принята команда POECanable, аргумент {}: {}", argument, value),
+    );
+
+    match header {
+      CAN_HEADER_SET => {
+        self.values.insert(key, value.clone());
+        self.can_reply(frame.id, &value)
+      },
+      CAN_HEADER_GET => {
+        let stored = self
+          .values
+          .get(&key)
+          .cloned()
+          .unwrap_or_else(|| "{}".to_string());
+        self.can_reply(frame.id, &stored)
+      },
+      _ => Vec::new(),
+    }
+  }
+}
+
+/// Виртуальный порт для работы без подключённой платы
+///
+/// `loop://<имя>` возвращает все записанные данные в поток чтения,
+/// `sim://poe` отвечает как плата POE на команды POESerial и POECanable.
+pub struct VirtualTransport {
+  path: String,
+  device: Option<String>,
+  mode: Arc<Mutex<Option<VirtualMode>>>,
+  sender: Mutex<Option<Sender<Vec<u8>>>>,
+  receiver: Mutex<Option<Receiver<Vec<u8>>>>,
+  handlers: Arc<ReadHandlers>,
+  reading: Arc<AtomicBool>,
+  reader: Mutex<Option<JoinHandle<Receiver<Vec<u8>>>>>,
+}
+
+impl VirtualTransport {
+  /// Создаёт порт, возвращающий записанные данные
+  pub fn loopback(path: String) -> Self {
+    Self::new(path, None)
+  }
+
+  /// Создаёт порт, подключённый к симулятору устройства с указанным именем
+  pub fn simulated(path: String, device: String) -> Self {
+    Self::new(path, Some(device))
+  }
+
+  fn new(path: String, device: Option<String>) -> Self {
+    Self {
+      path,
+      device,
+      mode: Arc::new(Mutex::new(None)),
+      sender: Mutex::new(None),
+      receiver: Mutex::new(None),
+      handlers: Arc::new(ReadHandlers::default()),
+      reading: Arc::new(AtomicBool::new(false)),
+      reader: Mutex::new(None),
+    }
+  }
+}
+
+impl Transport for VirtualTransport {
+  fn path(&self) -> &str {
+    &self.path
+  }
+
+  fn open(&self, config: &SerialConfig) -> Result<(), String> {
+    let mode = match self.device.as_deref() {
+      None => VirtualMode::Loopback,
+      Some(SIM_POE_DEVICE) => {
+        let mut device = SimulatedPoeDevice::new(config.poe_serial_escaped.unwrap_or(false));
+        device.can_mode = CanMode::from_protocol(&config.protocol).unwrap_or(CanMode::Classic);
+        VirtualMode::PoeDevice(device)
+      },
+      Some(device) => return Err(format!("Unknown simulated device: {}", device)),
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    *self.mode.lock().unwrap() = Some(mode);
+    *self.sender.lock().unwrap() = Some(sender);
+    *self.receiver.lock().unwrap() = Some(receiver);
+
+    log(LogLevel::Info, "VirtualTransport", format!("Виртуальный порт {} открыт", self.path));
+    Ok(())
+  }
+
+  fn close(&self) -> Result<(), String> {
+    self.stop_reading()?;
+    if self.sender.lock().unwrap().take().is_none() {
+      return Err(format!("Port {} is not open", self.path));
+    }
+    self.receiver.lock().unwrap().take();
+    self.mode.lock().unwrap().take();
+    Ok(())
+  }
+
+  fn force_close(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    self.sender.lock().unwrap().take();
+    self.receiver.lock().unwrap().take();
+    self.mode.lock().unwrap().take();
+    Ok(())
+  }
+
+  fn write(&self, data: &[u8]) -> Result<usize, String> {
+    let sender = self.sender.lock().unwrap();
+    let sender = sender
+      .as_ref()
+      .ok_or_else(|| format!("Port {} is not open", self.path))?;
+    sender.send(data.to_vec()).map_err(|e| e.to_string())?;
+    Ok(data.len())
+  }
+
+  fn start_reading(&self) -> Result<(), String> {
+    if self.reading.swap(true, Ordering::SeqCst) {
+      log(LogLevel::Warn, "VirtualTransport", format!("Чтение {} уже запущено", self.path));
+      return Ok(());
+    }
+
+    let Some(receiver) = self.receiver.lock().unwrap().take() else {
+      self.reading.store(false, Ordering::SeqCst);
+      return Err(format!("Port {} is not open", self.path));
+    };
+
+    let handlers = self.handlers.clone();
+    let reading = self.reading.clone();
+    let mode = self.mode.clone();
+    let path = self.path.clone();
+    let reader = thread::spawn(move || {
+      while reading.load(Ordering::SeqCst) {
+        match receiver.recv_timeout(READ_POLL_INTERVAL) {
+          Ok(data) => {
+            let reply = match mode.lock().unwrap().as_mut() {
+              Some(mode) => mode.handle(&data),
+              None => Vec::new(),
+            };
+            if !reply.is_empty() {
+              handlers.dispatch(&reply);
+            }
+          },
+          Err(RecvTimeoutError::Timeout) => continue,
+          Err(RecvTimeoutError::Disconnected) => break,
+        }
+      }
+
+      reading.store(false, Ordering::SeqCst);
+      log(LogLevel::Info, "VirtualTransport", format!("Поток чтения {} остановлен", path));
+      receiver
+    });
+    *self.reader.lock().unwrap() = Some(reader);
+    Ok(())
+  }
+
+  fn stop_reading(&self) -> Result<(), String> {
+    self.reading.store(false, Ordering::SeqCst);
+    if let Some(reader) = self.reader.lock().unwrap().take() {
+      let receiver = reader
+        .join()
+        .map_err(|_| format!("Reader thread for {} panicked", self.path))?;
+      // Возвращаем канал, чтобы чтение можно было запустить повторно
+      *self.receiver.lock().unwrap() = Some(receiver);
+    }
+    Ok(())
+  }
+
+  fn subscribe(&self, handler: ReadHandler) -> u32 {
+    self.handlers.subscribe(handler)
+  }
+
+  fn unsubscribe(&self, id: u32) {
+    self.handlers.unsubscribe(id);
+  }
+
+  fn write_dtr(&self, level: bool) -> Result<(), String> {
+    log(LogLevel::Info, "VirtualTransport", format!("DTR {} на виртуальном порту {}", level, self.path));
+    Ok(())
+  }
+
+  fn write_rts(&self, level: bool) -> Result<(), String> {
+    log(LogLevel::Info, "VirtualTransport", format!("RTS {} на виртуальном порту {}", level, self.path));
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::poe_serial::build_poe_serial_packet;
+
+  const RECEIVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+  /// Открывает виртуальный порт и возвращает канал с ответами порта
+  fn open_port(path: &str, protocol: &str) -> (VirtualTransport, Receiver<Vec<u8>>) {
+    let transport = match path.strip_prefix("sim://") {
+      Some(device) => VirtualTransport::simulated(path.to_string(), device.to_string()),
+      None => VirtualTransport::loopback(path.to_string()),
+    };
+    transport.open(&SerialConfig::test(path, protocol)).unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    transport.subscribe(Box::new(move |data| {
+      let _ = sender.lock().unwrap().send(data);
+    }));
+    transport.start_reading().unwrap();
+    (transport, receiver)
+  }
+
+  /// Записывает данные в порт и ждёт ответа
+  fn exchange(transport: &VirtualTransport, replies: &Receiver<Vec<u8>>, data: &[u8]) -> Vec<u8> {
+    transport.write(data).unwrap();
+    replies.recv_timeout(RECEIVE_TIMEOUT).unwrap()
+  }
+
+  /// Разбирает ответ симулятора как единственный пакет POESerial и проверяет его CRC8
+  fn decode_serial_reply(reply: &[u8]) -> RawPoeSerialPacket {
+    let mut packets = PoeSerialFramer::new(false).push_bytes(reply, "sim://poe");
+    assert_eq!(packets.len(), 1);
+    let packet = packets.remove(0);
+    let crc = u8::from_str_radix(std::str::from_utf8(&packet.crc).unwrap(), 16).unwrap();
+    assert_eq!(crc, calculate_crc8(&packet.checksum_data()));
+    assert!(!packet.free_heap.is_empty());
+    packet
+  }
+
+  /// ID фрейма POECanable: заголовок, аргумент, адресат и отправитель, признак последнего фрейма
+  fn can_id(header: u32, argument: u32, target_id: u32, return_id: u32) -> u32 {
+    (1 << 28) | (header << 26) | (argument << 16) | (target_id << 8) | return_id
+  }
+
+  #[test]
+  fn loopback_echoes_writes() {
+    let (transport, replies) = open_port("loop://test", "SimpleSerial");
+    assert_eq!(exchange(&transport, &replies, b"ping\r\n"), b"ping\r\n");
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn sim_poe_answers_serial_set_and_get() {
+    let (transport, replies) = open_port("sim://poe", "POESerial");
+
+    let set = build_poe_serial_packet(b"SET", b"ARG", b"{\"a\":1}", false).unwrap();
+    let packet = decode_serial_reply(&exchange(&transport, &replies, &set));
+    assert_eq!(packet.header, b"OK!");
+    assert_eq!(packet.argument, b"ARG");
+    assert_eq!(packet.value, b"{\"a\":1}");
+
+    let get = build_poe_serial_packet(b"GET", b"ARG", b"", false).unwrap();
+    let packet = decode_serial_reply(&exchange(&transport, &replies, &get));
+    assert_eq!(packet.header, b"OK!");
+    assert_eq!(packet.value, b"{\"a\":1}");
+
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn sim_poe_answers_error_on_crc_mismatch() {
+    let (transport, replies) = open_port("sim://poe", "POESerial");
+
+    let mut set = build_poe_serial_packet(b"SET", b"ARG", b"{\"a\":1}", false).unwrap();
+    let value_at = set.iter().position(|&byte| byte == b'1').unwrap();
+    set[value_at] = b'2';
+    let packet = decode_serial_reply(&exchange(&transport, &replies, &set));
+    assert_eq!(packet.header, b"ER!");
+    assert_eq!(packet.argument, b"ARG");
+
+    // Значение с неверным CRC не сохраняется
+    let get = build_poe_serial_packet(b"GET", b"ARG", b"", false).unwrap();
+    assert_eq!(decode_serial_reply(&exchange(&transport, &replies, &get)).value, b"{}");
+
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn sim_poe_answers_slcan_adapter_commands() {
+    let (transport, replies) = open_port("sim://poe", "POECanable");
+
+    assert_eq!(exchange(&transport, &replies, b"F\r"), b"F00\r");
+    assert_eq!(exchange(&transport, &replies, b"V\r"), b"V0100\r");
+    assert_eq!(exchange(&transport, &replies, b"v\r"), b"vsim-poe-canfd\r");
+    assert_eq!(exchange(&transport, &replies, b"N\r"), b"NSIM0\r");
+    assert_eq!(exchange(&transport, &replies, b"S6\r"), b"\r");
+    assert_eq!(exchange(&transport, &replies, b"Z1\r"), b"\r");
+    // Фрейм, который не удалось разобрать, отклоняется символом BEL
+    assert_eq!(exchange(&transport, &replies, b"TXYZ\r"), [0x07]);
+
+    transport.close().unwrap();
+  }
+
+  #[test]
+  fn sim_poe_acks_and_answers_can_frames() {
+    let (transport, replies) = open_port("sim://poe", "POECanable");
+    assert_eq!(exchange(&transport, &replies, b"Z1\r"), b"\r");
+
+    let request = format_can_frame('T', can_id(CAN_HEADER_SET, 0x12, 0x01, 0x02), Some(b"{\"a\":1}".to_vec()), 7).unwrap();
+    let reply = exchange(&transport, &replies, request.as_bytes());
+    // Сначала подтверждение адаптера, затем ответ платы с меткой времени
+    assert_eq!(reply[0], b'\r');
+    let line = std::str::from_utf8(&reply[1..]).unwrap();
+    let line = line.strip_suffix('\r').unwrap();
+    assert_eq!(line.len(), request.len() - 1 + 4);
+
+    let frame = parse_slcan_frame(line).unwrap();
+    assert!(frame.extended);
+    assert_eq!(frame.id, can_id(CAN_HEADER_OK, 0x12, 0x02, 0x01));
+    assert_eq!(frame.data, b"{\"a\":1}");
+
+    transport.close().unwrap();
+  }
+}
@@ -14,6 +14,7 @@ export interface POECanableData {
   return_id: string | number
   convert_to_base64?: number
   data?: string
+  brs?: boolean
 }
 
 /* Интерфейсы для получения данных */
@@ -23,16 +24,66 @@ export interface FullId {
   argument_code: number
   target_id: number
   return_id: number
+  header_name?: string | null
+  argument_name?: string | null
+  is_known?: boolean
 }
 
 export interface MessageData {
   timestamp: number
+  timestamp_us: number
+  device_timestamp?: number | null
   full_id: FullId
   mainID: number
   can_data: Uint8Array
   json: string
   is_remote: boolean
   is_complete: boolean
+  schema_error?: string | null
+}
+
+/* Фильтр принимаемых фреймов по полю расширенного ID POE */
+export interface CanFilter {
+  field: "target_id" | "return_id" | "argument_code"
+  id: number
+  mask?: number
+}
+
+const CAN_FILTER_FIELDS: Record<string, CanFilter["field"]> = {
+  target: "target_id",
+  return: "return_id",
+  argument: "argument_code",
+}
+
+/* Разбор фильтров вида "target:0x10, argument:24/0x3F0" (поле:значение[/маска]), неверные части пропускаются */
+export const parseCanFilters = (text: string): CanFilter[] =>
+  text
+    .split(",")
+    .map(part => part.trim())
+    .filter(Boolean)
+    .flatMap(part => {
+      const match = part.match(/^(\w+)\s*:\s*(\w+)(?:\s*\/\s*(\w+))?$/)
+      const field = match ? CAN_FILTER_FIELDS[match[1].toLowerCase()] : undefined
+      if (!match || !field) return []
+      const id = Number(match[2])
+      const mask = match[3] !== undefined ? Number(match[3]) : undefined
+      if (Number.isNaN(id) || (mask !== undefined && Number.isNaN(mask))) return []
+      return [{ field, id, mask }]
+    })
+
+/* Состояние шины по флагам адаптера (событие can-bus-status) */
+export interface CanBusStatus {
+  path: string
+  flags: number
+  rx_fifo_full: boolean
+  tx_fifo_full: boolean
+  error_warning: boolean
+  data_overrun: boolean
+  error_passive: boolean
+  arbitration_lost: boolean
+  bus_error: boolean
+  bus_off: boolean
+  timestamp: number
 }
 
 export interface IPOECanableTableRow {
@@ -5,8 +5,14 @@ export interface POESerialData {
   header: string
   argument: string
   value: string
+  value_bytes?: number[]
   crc_hex?: string
+  crc_ok?: boolean
   free_heap_size?: string
+  header_code?: number | null
+  argument_code?: number | null
+  is_known?: boolean
+  schema_error?: string | null
 }
 
 export interface IPOESerialTableRow {
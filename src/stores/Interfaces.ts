@@ -10,6 +10,60 @@ export interface ISelectOption<T = unknown> {
   disabled?: boolean
 }
 
+/* Информация о порте, возвращаемая командой list_ports */
+export interface IPortInfo {
+  path: string
+  port_type: string
+  vid: number | null
+  pid: number | null
+  serial_number: string | null
+  manufacturer: string | null
+  product: string | null
+  interface: number | null
+  device: string | null
+  protocol: string | null
+}
+
+/* Итог отправки команды, приходящий в событии app-status */
+export interface ICommandStatus {
+  path: string
+  protocol: string
+  success: boolean
+  attempts: number
+  elapsed_ms: number
+  error: string | null
+  message: string
+}
+
+/* Протокол из реестра бэкенда (list_protocols) с JSON Schema параметров команды */
+export interface IProtocolInfo {
+  name: string
+  description: string
+  parameters: Record<string, unknown>
+  supports_reply: boolean
+}
+
+/* Сведения об адаптере SLCAN, полученные при подключении */
+export interface IAdapterInfo {
+  hardware_version: string | null
+  firmware_version: string | null
+  serial_number: string | null
+  fd_supported: boolean | null
+  acceptance_filter_supported: boolean | null
+  warnings: string[]
+}
+
+/* Результат подключения к порту */
+export interface IConnectResult {
+  path: string
+  adapter: IAdapterInfo | null
+}
+
+/* Вариант выбора порта с протоколом, предложенным по VID/PID */
+export interface IPortOption extends ISelectOption<string> {
+  protocol?: string
+}
+
 export interface SavedCommands {
   SimpleSerial?: {
     data: string